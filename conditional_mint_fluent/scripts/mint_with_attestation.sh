@@ -54,9 +54,10 @@ echo "STRUCT_HASH = $STRUCT_HASH"
 SIG=$(cast wallet sign --private-key "$ATTESTOR_PRIVKEY" "$STRUCT_HASH")
 echo "SIG = $SIG"
 
-# Call with tuple in parentheses
+# Call with tuple in parentheses; add co-signers' signatures to the array
+# until it reaches the registry's threshold
 cast send "$ROUTER" \
-  "mintWithAttestation((bytes32,bytes32,address,uint256,uint256),bytes)" \
-  "($LOCK_ID,$MONERO_TX,$DEST,$AMOUNT,$EXPIRY)" "$SIG" \
+  "mintWithAttestation((bytes32,bytes32,address,uint256,uint256),bytes[])" \
+  "($LOCK_ID,$MONERO_TX,$DEST,$AMOUNT,$EXPIRY)" "[$SIG]" \
   --private-key "$PRIVATE_KEY" \
   --rpc-url "$FLUENT_RPC"
\ No newline at end of file
@@ -5,20 +5,54 @@ import {Ownable} from "@openzeppelin/contracts/access/Ownable.sol";
 
 contract AttestorRegistry is Ownable {
     mapping(address => bool) public isAttestor;
+    // The operator each key signs for. A key added by `rotateAttestor` shares
+    // its predecessor's, so one operator never counts twice towards `threshold`.
+    mapping(address => address) public operatorOf;
+    mapping(address => uint256) public keysOf;
+    uint256 public operatorCount;
+    // Distinct operators whose signatures BridgeRouter requires per mint
+    uint256 public threshold = 1;
 
     event AttestorAdded(address attestor);
     event AttestorRemoved(address attestor);
+    event ThresholdChanged(uint256 threshold);
 
     // OZ v5: must pass the initial owner
     constructor(address initialOwner) Ownable(initialOwner) {}
 
     function addAttestor(address a) external onlyOwner {
-        isAttestor[a] = true;
-        emit AttestorAdded(a);
+        _add(a, a);
     }
 
+    // Registers `next` as another key of `previous`'s operator, for key rotations
+    function rotateAttestor(address previous, address next) external onlyOwner {
+        require(isAttestor[previous], "unknown attestor");
+        _add(next, operatorOf[previous]);
+    }
+
+    // Dropping below `threshold` operators stops minting until one is added back
     function removeAttestor(address a) external onlyOwner {
+        require(isAttestor[a], "unknown attestor");
+        address operator = operatorOf[a];
         isAttestor[a] = false;
+        delete operatorOf[a];
+        keysOf[operator] -= 1;
+        if (keysOf[operator] == 0) operatorCount -= 1;
         emit AttestorRemoved(a);
     }
+
+    function setThreshold(uint256 t) external onlyOwner {
+        require(t > 0 && t <= operatorCount, "bad threshold");
+        threshold = t;
+        emit ThresholdChanged(t);
+    }
+
+    function _add(address a, address operator) internal {
+        require(a != address(0) && !isAttestor[a], "bad attestor");
+        isAttestor[a] = true;
+        operatorOf[a] = operator;
+        if (keysOf[operator] == 0) operatorCount += 1;
+        keysOf[operator] += 1;
+        emit AttestorAdded(a);
+    }
 }
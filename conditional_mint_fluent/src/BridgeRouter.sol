@@ -52,20 +52,32 @@ contract BridgeRouter {
         );
     }
 
-    function mintWithAttestation(LockProof calldata p, bytes calldata sig) external {
+    // `sigs` must hold signatures over `p` by registered attestors of at
+    // least `registry.threshold()` distinct operators
+    function mintWithAttestation(LockProof calldata p, bytes[] calldata sigs) external {
         require(block.timestamp <= p.expiry, "attestation expired");
         require(!consumed[p.lockId], "lockId used");
         require(p.amount > 0, "zero amount");
         require(p.dest != address(0), "bad dest");
+        require(sigs.length >= registry.threshold(), "too few signatures");
 
         bytes32 digest = _hash(p).toEthSignedMessageHash(); // OZ v5 path
-        address signer = ECDSA.recover(digest, sig);
-        require(registry.isAttestor(signer), "bad signer");
+        address[] memory operators = new address[](sigs.length);
+        for (uint256 i = 0; i < sigs.length; i++) {
+            address signer = ECDSA.recover(digest, sigs[i]);
+            require(registry.isAttestor(signer), "bad signer");
+            address operator = registry.operatorOf(signer);
+            for (uint256 j = 0; j < i; j++) {
+                require(operators[j] != operator, "duplicate operator");
+            }
+            operators[i] = operator;
+        }
 
         consumed[p.lockId] = true;
         wFOID.mint(p.dest, p.amount);
 
-        emit Minted(p.lockId, p.dest, p.amount, signer);
+        // The first signer, usually the node that issued the attestation
+        emit Minted(p.lockId, p.dest, p.amount, ECDSA.recover(digest, sigs[0]));
     }
 
     function burnForRedeem(uint256 amount, bytes calldata moneroDest) external {
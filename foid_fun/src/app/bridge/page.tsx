@@ -119,7 +119,11 @@ export default function BridgePage() {
           amount: parseUnits(mintAmount, 18),
           expiry: BigInt(expiry),
         },
-        signature: toHexString(signature),
+        // One signature per line, as many as the registry's threshold
+        signatures: signature
+          .split(/\s+/)
+          .filter(Boolean)
+          .map((line) => toHexString(line)),
       };
     } catch {
       return null;
@@ -232,7 +236,7 @@ export default function BridgePage() {
             />
             <textarea
               className="w-full p-2 rounded-lg bg-neutral-800"
-              placeholder="Signature (0x...)"
+              placeholder="Signatures (0x..., one per line)"
               value={signature}
               onChange={(event) => setSignature(event.target.value)}
             />
@@ -259,7 +263,7 @@ export default function BridgePage() {
                 mintInputs
                   ? [
                       mintInputs.attestation,
-                      mintInputs.signature,
+                      mintInputs.signatures,
                     ]
                   : undefined
               }
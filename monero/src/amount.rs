@@ -0,0 +1,211 @@
+// Amounts in piconero, the unit every Monero RPC reports, and their
+// conversion to the wFOID base units BridgeRouter mints
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::config::BridgeConfig;
+
+// 1 XMR = 10^12 piconero
+pub const XMR_DECIMALS: u32 = 12;
+pub const PICONERO_PER_XMR: u64 = 10u64.pow(XMR_DECIMALS);
+
+// Serialized as the bare integer, like the RPC amounts it comes from.
+// `Display` and `FromStr` use XMR, e.g. "1.250000000000".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Piconero(pub u64);
+
+impl Piconero {
+    pub const ZERO: Self = Self(0);
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    // None on overflow rather than wrapping
+    pub fn checked_sum(amounts: impl IntoIterator<Item = Self>) -> Option<Self> {
+        amounts.into_iter().try_fold(Self::ZERO, Self::checked_add)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn xmr(self) -> String {
+        self.to_string()
+    }
+}
+
+impl std::fmt::Display for Piconero {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{:0width$}",
+            self.0 / PICONERO_PER_XMR,
+            self.0 % PICONERO_PER_XMR,
+            width = XMR_DECIMALS as usize
+        )
+    }
+}
+
+// "1", "0.5" or "0.000000000001"; at most 12 decimals
+impl std::str::FromStr for Piconero {
+    type Err = anyhow::Error;
+
+    fn from_str(xmr: &str) -> Result<Self> {
+        let (units, piconero) = parse_decimal(xmr.trim(), XMR_DECIMALS).context("invalid XMR amount")?;
+        units
+            .checked_mul(u128::from(PICONERO_PER_XMR))
+            .and_then(|units| units.checked_add(piconero))
+            .and_then(|total| u64::try_from(total).ok())
+            .map(Self)
+            .ok_or_else(|| anyhow!("{} XMR is too large", xmr))
+    }
+}
+
+// A decimal string split into its integer part and its fraction scaled to
+// `decimals` digits
+fn parse_decimal(value: &str, decimals: u32) -> Result<(u128, u128)> {
+    let (units, fraction) = match value.split_once('.') {
+        Some((_, "")) => bail!("{:?} is not a decimal number", value),
+        Some(parts) => parts,
+        None => (value, ""),
+    };
+    if units.is_empty() || !units.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+        bail!("{:?} is not a decimal number", value);
+    }
+    if fraction.len() > decimals as usize {
+        bail!("{:?} has more than {} decimals", value, decimals);
+    }
+    let units: u128 = units.parse().with_context(|| format!("{:?} is too large", value))?;
+    let fraction = format!("{:0<width$}", fraction, width = decimals as usize);
+    Ok((units, fraction.parse().unwrap_or_default()))
+}
+
+// Piconero to wFOID base units: `wfoid_per_xmr` wFOID per XMR at
+// `wfoid_decimals`. Rounds down, so a mint is never worth more than the
+// deposit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WfoidConversion {
+    decimals: u8,
+    // wFOID per XMR as rate / 10^rate_scale
+    rate: u128,
+    rate_scale: u32,
+}
+
+// One deposit's conversion, recorded with its attestation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Converted {
+    pub received: Piconero,
+    pub received_xmr: String,
+    pub fee: Piconero,
+    pub fee_xmr: String,
+    // `LockProof.amount`, in base units of a token with `wfoid_decimals`
+    #[serde(with = "crate::attestation::u128_string")]
+    #[schema(value_type = String)]
+    pub wfoid: u128,
+    pub wfoid_decimals: u8,
+}
+
+impl Default for WfoidConversion {
+    fn default() -> Self {
+        Self::new(&BridgeConfig::default()).expect("default bridge config is valid")
+    }
+}
+
+impl WfoidConversion {
+    pub fn new(config: &BridgeConfig) -> Result<Self> {
+        let rate_scale = config.wfoid_per_xmr.split_once('.').map_or(0, |(_, fraction)| fraction.len() as u32);
+        let (units, fraction) =
+            parse_decimal(&config.wfoid_per_xmr, rate_scale).context("bridge.wfoid_per_xmr")?;
+        let rate = 10u128
+            .checked_pow(rate_scale)
+            .and_then(|scale| units.checked_mul(scale))
+            .and_then(|units| units.checked_add(fraction))
+            .ok_or_else(|| anyhow!("bridge.wfoid_per_xmr is too large"))?;
+        if rate == 0 {
+            bail!("bridge.wfoid_per_xmr must be greater than zero");
+        }
+        Ok(Self {
+            decimals: config.wfoid_decimals,
+            rate,
+            rate_scale,
+        })
+    }
+
+    // Converts what is left of `received` after the bridge `fee`
+    pub fn convert(&self, received: Piconero, fee: Piconero) -> Result<Converted> {
+        let net = received.checked_sub(fee).unwrap_or(Piconero::ZERO);
+
+        // net · rate · 10^decimals / (10^12 · 10^rate_scale), scaled in one step
+        let exponent = i64::from(self.decimals) - i64::from(XMR_DECIMALS) - i64::from(self.rate_scale);
+        let scaled = u128::from(net.0).checked_mul(self.rate).and_then(|value| {
+            let power = 10u128.checked_pow(exponent.unsigned_abs() as u32)?;
+            if exponent >= 0 { value.checked_mul(power) } else { Some(value / power) }
+        });
+        let wfoid = scaled.ok_or_else(|| anyhow!("{} XMR overflows the wFOID amount", received))?;
+        if wfoid == 0 {
+            bail!("{} XMR is worth no wFOID after a {} XMR fee", received, fee);
+        }
+        Ok(Converted {
+            received,
+            received_xmr: received.xmr(),
+            fee,
+            fee_xmr: fee.xmr(),
+            wfoid,
+            wfoid_decimals: self.decimals,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_and_parses_xmr() {
+        assert_eq!(Piconero(1_250_000_000_000).to_string(), "1.250000000000");
+        assert_eq!(Piconero(1).xmr(), "0.000000000001");
+        assert_eq!("1.25".parse::<Piconero>().unwrap(), Piconero(1_250_000_000_000));
+        assert_eq!("18446744.073709551615".parse::<Piconero>().unwrap(), Piconero(u64::MAX));
+        for invalid in ["", ".5", "1.", "-1", "1.0000000000001", "18446744.073709551616", "1e3"] {
+            assert!(invalid.parse::<Piconero>().is_err(), "{:?}", invalid);
+        }
+        assert_eq!(Piconero::checked_sum([Piconero(u64::MAX), Piconero(1)]), None);
+    }
+
+    #[test]
+    fn converts_to_wfoid_after_the_fee() {
+        // 1:1 at 18 decimals: 1 piconero is 10^6 base units
+        let conversion = WfoidConversion::default();
+        assert_eq!(conversion.convert(Piconero(1), Piconero::ZERO).unwrap().wfoid, 1_000_000);
+
+        let conversion = WfoidConversion::new(&BridgeConfig {
+            wfoid_decimals: 18,
+            wfoid_per_xmr: "2.5".to_string(),
+            ..BridgeConfig::default()
+        })
+        .unwrap();
+        let converted = conversion.convert("1".parse().unwrap(), "0.003".parse().unwrap()).unwrap();
+        assert_eq!(converted.fee_xmr, "0.003000000000");
+        assert_eq!(converted.wfoid, 2_492_500_000_000_000_000);
+
+        // Fewer decimals than XMR round down, and a dust deposit mints nothing
+        let conversion = WfoidConversion::new(&BridgeConfig {
+            wfoid_decimals: 6,
+            ..BridgeConfig::default()
+        })
+        .unwrap();
+        assert_eq!(conversion.convert(Piconero(1_999_999), Piconero::ZERO).unwrap().wfoid, 1);
+        assert!(conversion.convert(Piconero(999_999), Piconero::ZERO).is_err());
+    }
+}
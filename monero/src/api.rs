@@ -0,0 +1,318 @@
+// Request and response types of the HTTP API, shared by the server and `client`.
+// The `/v1` shapes are frozen; richer responses go into the `...V2` types.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::amount::Piconero;
+use crate::attestation::{Attestation, LockProof};
+use crate::events::unix_now;
+use crate::finality::BlockRef;
+use crate::health::Checks;
+use crate::network::Network;
+use crate::policy::PolicyDecision;
+use crate::pool::EndpointStatus;
+use crate::privacy::{Commitment, ProofPreimage};
+use crate::reconcile::{MintFlag, MintRecord};
+use crate::scanner::MatchedOutput;
+use crate::validation::FieldError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofStatus {
+    #[default]
+    Created,
+    Validated,
+    Attested,
+    Invalidated,
+    // BridgeRouter consumed the attestation's lockId
+    Minted,
+    // A tombstone left by `DELETE /proof/{id}` or the retention purge
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TransactionProof {
+    pub tx_id: String,
+    // Main key with any additional keys appended, see `TxKey`
+    pub tx_key: String,
+    pub recipient_address: String,
+    pub amount: Option<Piconero>,
+    pub proof_id: String,
+    #[serde(default)]
+    pub status: ProofStatus,
+    // When `status` last changed, unix seconds; 0 on proofs stored before
+    // it was recorded
+    #[serde(default)]
+    pub updated_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<Attestation>,
+    // The latest attestation attempt's fee and limit checks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<PolicyDecision>,
+    // The `Minted` event of the attestation, once the reconciler saw it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<MintRecord>,
+    // What the reconciler found wrong with the attestation on-chain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<MintFlag>,
+    // In privacy mode, stands in for `tx_id`, `tx_key` and
+    // `recipient_address`, which are then stored empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<Commitment>,
+    // The block the deposit was mined in, recorded when it validated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<BlockRef>,
+}
+
+impl TransactionProof {
+    pub fn set_status(&mut self, status: ProofStatus) {
+        self.status = status;
+        self.updated_at = unix_now();
+    }
+}
+
+// The tx private key plus, for transactions with subaddress outputs, one
+// additional key per output. `get_tx_key` and `check_tx_key` use them
+// concatenated into a single hex string, main key first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxKey {
+    pub main: String,
+    pub additional: Vec<String>,
+}
+
+impl TxKey {
+    pub fn parse(concatenated: &str) -> anyhow::Result<Self> {
+        let hex = concatenated.trim().to_ascii_lowercase();
+        if hex.is_empty() || !hex.len().is_multiple_of(64) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            anyhow::bail!("tx key must be one or more 64-character hex keys");
+        }
+        let mut keys = hex.as_bytes().chunks(64).map(|key| String::from_utf8_lossy(key).into_owned());
+        Ok(Self {
+            main: keys.next().unwrap_or_default(),
+            additional: keys.collect(),
+        })
+    }
+
+    // The single-string form `check_tx_key` expects
+    pub fn concatenated(&self) -> String {
+        std::iter::once(&self.main).chain(&self.additional).map(String::as_str).collect()
+    }
+}
+
+impl std::fmt::Display for TxKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.concatenated())
+    }
+}
+
+// `tx_key` may already carry the additional keys appended, as returned by
+// `POST /v1/proof`; `additional_tx_keys` are appended after it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ValidationRequest {
+    pub tx_id: String,
+    pub tx_key: String,
+    pub recipient_address: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_tx_keys: Vec<String>,
+}
+
+impl ValidationRequest {
+    pub fn tx_keys(&self) -> anyhow::Result<TxKey> {
+        let mut keys = TxKey::parse(&self.tx_key)?;
+        for key in &self.additional_tx_keys {
+            let key = TxKey::parse(key)?;
+            keys.additional.push(key.main);
+            keys.additional.extend(key.additional);
+        }
+        Ok(keys)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ValidationResponse {
+    pub valid: bool,
+    pub amount: Option<Piconero>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProofRequest {
+    pub tx_id: String,
+    pub recipient_address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProofResponse {
+    pub proof_id: String,
+    pub tx_key: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AttestRequest {
+    pub dest: String,
+    // The deposit data returned when the proof was created. Required: only
+    // its creator holds the tx key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preimage: Option<ProofPreimage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AttestResponse {
+    pub proof_id: String,
+    #[serde(flatten)]
+    pub attestation: Attestation,
+}
+
+// A proof as returned by the `/v1` proof endpoints: the fields `/v1` has always
+// had, nothing the later requests added
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ProofV1 {
+    pub tx_id: String,
+    pub tx_key: String,
+    pub recipient_address: String,
+    pub amount: Option<Piconero>,
+    pub proof_id: String,
+}
+
+impl From<TransactionProof> for ProofV1 {
+    fn from(proof: TransactionProof) -> Self {
+        Self {
+            tx_id: proof.tx_id,
+            tx_key: proof.tx_key,
+            recipient_address: proof.recipient_address,
+            amount: proof.amount,
+            proof_id: proof.proof_id,
+        }
+    }
+}
+
+// A proof as returned by every `/v2` proof endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProofV2 {
+    pub proof_id: String,
+    pub tx_id: String,
+    // The main tx key; subaddress outputs add one key each
+    pub tx_key: String,
+    pub additional_tx_keys: Vec<String>,
+    pub recipient_address: String,
+    pub amount: Option<Piconero>,
+    pub amount_xmr: Option<String>,
+    pub status: ProofStatus,
+    pub updated_at: u64,
+    // Blocks on top of the transaction's block, 0 while it is in the pool,
+    // null when the daemons could not be asked
+    pub confirmations: Option<u64>,
+    pub attestation: Option<Attestation>,
+    pub policy: Option<PolicyDecision>,
+    pub mint: Option<MintRecord>,
+    pub flags: Vec<MintFlag>,
+    // Set on proofs stored as a commitment, see `POST /v2/proof/{id}/open`
+    pub commitment: Option<Commitment>,
+    pub block: Option<BlockRef>,
+}
+
+impl ProofV2 {
+    pub fn new(proof: TransactionProof, confirmations: Option<u64>) -> Self {
+        let tx_key = TxKey::parse(&proof.tx_key).unwrap_or_else(|_| TxKey {
+            main: proof.tx_key.clone(),
+            additional: Vec::new(),
+        });
+        Self {
+            proof_id: proof.proof_id,
+            tx_id: proof.tx_id,
+            tx_key: tx_key.main,
+            additional_tx_keys: tx_key.additional,
+            recipient_address: proof.recipient_address,
+            amount: proof.amount,
+            amount_xmr: proof.amount.map(Piconero::xmr),
+            status: proof.status,
+            updated_at: proof.updated_at,
+            confirmations,
+            attestation: proof.attestation,
+            policy: proof.policy,
+            mint: proof.mint,
+            flags: proof.flags,
+            commitment: proof.commitment,
+            block: proof.block,
+        }
+    }
+}
+
+// An issued attestation that can still be minted, see `GET /v2/attestations`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OutstandingAttestation {
+    pub proof_id: String,
+    pub lock_proof: LockProof,
+    pub issued_at: u64,
+    pub expires_in_secs: u64,
+}
+
+// `POST /v2/validate`; `amount` is the total of `outputs`, every output of
+// the transaction that pays the recipient, and null when there is none.
+// `proof` is the stored proof for this tx key, if any.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ValidationResponseV2 {
+    pub valid: bool,
+    pub amount: Option<Piconero>,
+    pub amount_xmr: Option<String>,
+    pub outputs: Vec<MatchedOutput>,
+    pub confirmations: Option<u64>,
+    pub proof: Option<ProofV2>,
+    // The network the transaction was looked up on
+    pub network: Network,
+}
+
+// `GET /health/live`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct LivenessResponse {
+    pub status: String,
+    pub version: String,
+}
+
+// `GET /health`: "healthy", "degraded" or "unhealthy"
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthResponse {
+    pub status: String,
+    pub service: String,
+    pub version: String,
+    // `monero.network`; each daemon reports its own `nettype`
+    pub network: Network,
+    pub daemons: Vec<EndpointStatus>,
+    pub wallet: Option<EndpointStatus>,
+}
+
+// `GET /health/ready`: "ready" or "not_ready"
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: String,
+    pub version: String,
+    pub checks: Checks,
+}
+
+// `DELETE /webhooks/{id}`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeletedResponse {
+    pub deleted: String,
+}
+
+// `POST /webhooks/dead-letters/{id}/replay`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReplayResponse {
+    pub replayed: String,
+    pub event_id: String,
+}
+
+// Body of every non-2xx response; `fields` lists what was wrong with an
+// invalid request body
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
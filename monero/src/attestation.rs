@@ -0,0 +1,391 @@
+use anyhow::{Context, Result, anyhow};
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::amount::{Converted, WfoidConversion};
+use crate::config::AttestorConfig;
+use crate::coordination::Coordinator;
+use crate::network::Network;
+use crate::signer::{self, LocalSigner, Signer};
+
+// Mirrors BridgeRouter.MINT_TYPEHASH
+const MINT_TYPE: &str = "BridgeMint(bytes32,bytes32,address,uint256,uint256,address,uint256)";
+
+// Default `attestor.expiry_secs`, same as scripts/mint_with_attestation.sh
+pub const DEFAULT_EXPIRY_SECS: u64 = 3600;
+
+// Solidity `BridgeRouter.LockProof`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct LockProof {
+    #[serde(with = "hex_array")]
+    #[schema(value_type = String)]
+    pub lock_id: [u8; 32],
+    #[serde(with = "hex_array")]
+    #[schema(value_type = String)]
+    pub monero_tx: [u8; 32],
+    #[serde(with = "hex_array")]
+    #[schema(value_type = String)]
+    pub dest: [u8; 20],
+    // uint256 on-chain, kept as a decimal string in JSON
+    #[serde(with = "u128_string")]
+    #[schema(value_type = String)]
+    pub amount: u128,
+    pub expiry: u64,
+}
+
+// A signed LockProof ready to be passed to `mintWithAttestation`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Attestation {
+    pub lock_proof: LockProof,
+    pub signature: String,
+    pub attestor: String,
+    pub issued_at: u64,
+    // How the deposit became `lock_proof.amount`; absent on attestations
+    // issued before conversions were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversion: Option<Converted>,
+    // Every collected signature over `lock_proof`, `signature` included:
+    // what `mintWithAttestation` takes, at least the registry's threshold
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<PartialSignature>,
+    // During a key rotation, the same LockProof signed by the previous key,
+    // for routers that do not recognize the new one yet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_signature: Option<PreviousSignature>,
+    // The Monero network of the deposit. Not part of the signed LockProof;
+    // absent on attestations issued before networks were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<Network>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PreviousSignature {
+    pub attestor: String,
+    pub signature: String,
+    // End of the previous key's grace window
+    pub valid_until: u64,
+}
+
+// One attestor's signature over a LockProof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PartialSignature {
+    pub attestor: String,
+    pub signature: String,
+}
+
+impl LockProof {
+    pub fn new(monero_tx: [u8; 32], dest: [u8; 20], amount: u128, expiry: u64) -> Self {
+        Self {
+            lock_id: lock_id(&monero_tx),
+            monero_tx,
+            dest,
+            amount,
+            expiry,
+        }
+    }
+
+    // keccak256(abi.encode(MINT_TYPEHASH, lockId, moneroTx, dest, amount, expiry, router, chainId))
+    pub fn struct_hash(&self, router: &[u8; 20], chain_id: u64) -> [u8; 32] {
+        let mut encoded = Vec::with_capacity(32 * 8);
+        encoded.extend_from_slice(&keccak256(MINT_TYPE.as_bytes()));
+        encoded.extend_from_slice(&self.lock_id);
+        encoded.extend_from_slice(&self.monero_tx);
+        encoded.extend_from_slice(&abi_word(&self.dest));
+        encoded.extend_from_slice(&abi_word(&self.amount.to_be_bytes()));
+        encoded.extend_from_slice(&abi_word(&self.expiry.to_be_bytes()));
+        encoded.extend_from_slice(&abi_word(router));
+        encoded.extend_from_slice(&abi_word(&chain_id.to_be_bytes()));
+        keccak256(&encoded)
+    }
+
+    // What the router recovers the signer from: `_hash(p).toEthSignedMessageHash()`
+    pub fn digest(&self, router: &[u8; 20], chain_id: u64) -> [u8; 32] {
+        eth_signed_message_hash(&self.struct_hash(router, chain_id))
+    }
+}
+
+// Derived from the Monero transaction alone: the router consumes each lockId
+// once, so a deposit mints at most once whatever `dest` it is attested to
+pub fn lock_id(monero_tx: &[u8; 32]) -> [u8; 32] {
+    keccak256(monero_tx)
+}
+
+// Signs LockProofs for the BridgeRouter with the attestor key, converting
+// deposits with `conversion`. With a `coordinator`, peer validators co-sign.
+pub struct Attestor {
+    signer: Arc<dyn Signer>,
+    previous: Option<PreviousKey>,
+    pub router: [u8; 20],
+    pub chain_id: u64,
+    pub conversion: WfoidConversion,
+    pub coordinator: Option<Coordinator>,
+    // How long each LockProof stays mintable
+    pub expiry_secs: u64,
+    // Only deposits to addresses of this network are signed
+    pub network: Network,
+    // `bridge.deposit_address`; deposits paying any other address are not
+    // signed, and none are while it is unset
+    pub deposit_address: Option<String>,
+}
+
+// A rotated-out key that still signs until `valid_until`
+pub struct PreviousKey {
+    pub signer: Arc<dyn Signer>,
+    pub valid_until: u64,
+}
+
+impl Attestor {
+    pub fn new(private_key: &str, router: &str, chain_id: u64) -> Result<Self> {
+        Self::with_signer(Arc::new(LocalSigner::from_hex(private_key)?), router, chain_id)
+    }
+
+    pub fn with_signer(signer: Arc<dyn Signer>, router: &str, chain_id: u64) -> Result<Self> {
+        Ok(Self {
+            signer,
+            previous: None,
+            router: parse_address(router)?,
+            chain_id,
+            conversion: WfoidConversion::default(),
+            coordinator: None,
+            expiry_secs: DEFAULT_EXPIRY_SECS,
+            network: Network::Mainnet,
+            deposit_address: None,
+        })
+    }
+
+    pub fn with_conversion(self, conversion: WfoidConversion) -> Self {
+        Self { conversion, ..self }
+    }
+
+    pub fn with_coordinator(self, coordinator: Option<Coordinator>) -> Self {
+        Self { coordinator, ..self }
+    }
+
+    pub fn with_previous(self, previous: Option<PreviousKey>) -> Self {
+        Self { previous, ..self }
+    }
+
+    pub fn with_network(self, network: Network) -> Self {
+        Self { network, ..self }
+    }
+
+    pub fn with_deposit_address(self, deposit_address: Option<String>) -> Self {
+        Self { deposit_address, ..self }
+    }
+
+    // Loads the keys `config` describes. `ROUTER` and `CHAIN_ID` come from
+    // the same environment as conditional_mint_fluent/.env.
+    pub fn load(config: &AttestorConfig) -> Result<Option<Self>> {
+        let Some(signer) = signer::load(&config.key)? else {
+            return Ok(None);
+        };
+        let router = std::env::var("ROUTER").context("ROUTER must be set when an attestor key is configured")?;
+        let chain_id = match std::env::var("CHAIN_ID") {
+            Ok(id) => id.parse().context("CHAIN_ID must be a number")?,
+            Err(_) => 20994,
+        };
+        let previous = match &config.previous {
+            Some(previous) => Some(PreviousKey {
+                signer: signer::load(&previous.key)?.context("attestor.previous.key is not set")?,
+                valid_until: previous.valid_until,
+            }),
+            None => None,
+        };
+        let attestor = Self::with_signer(signer, &router, chain_id)?.with_previous(previous);
+        Ok(Some(Self {
+            expiry_secs: config.expiry_secs,
+            ..attestor
+        }))
+    }
+
+    pub fn address(&self) -> [u8; 20] {
+        self.signer.address()
+    }
+
+    // The previous key while its grace window is open
+    pub fn previous(&self, now: u64) -> Option<&PreviousKey> {
+        self.previous.as_ref().filter(|previous| now < previous.valid_until)
+    }
+
+    // 65-byte r || s || v signature, v in {27, 28} as expected by OZ ECDSA.recover
+    pub async fn sign(&self, lock_proof: &LockProof) -> Result<String> {
+        sign_with(self.signer.as_ref(), &lock_proof.digest(&self.router, self.chain_id)).await
+    }
+
+    // `lock_proof` signed by the previous key, if it is still in its grace window
+    pub async fn sign_previous(&self, lock_proof: &LockProof, now: u64) -> Result<Option<PreviousSignature>> {
+        let Some(previous) = self.previous(now) else {
+            return Ok(None);
+        };
+        let digest = lock_proof.digest(&self.router, self.chain_id);
+        Ok(Some(PreviousSignature {
+            attestor: format_hex(&previous.signer.address()),
+            signature: sign_with(previous.signer.as_ref(), &digest).await?,
+            valid_until: previous.valid_until,
+        }))
+    }
+
+    // Who signed `lock_proof` for this attestor's router and chain, as
+    // `ECDSA.recover` would see it
+    pub fn recover_signer(&self, lock_proof: &LockProof, signature: &str) -> Result<[u8; 20]> {
+        let bytes = hex::decode(strip_0x(signature)).context("signature is not hex")?;
+        recover_digest(&lock_proof.digest(&self.router, self.chain_id), &bytes)
+    }
+}
+
+async fn sign_with(signer: &dyn Signer, digest: &[u8; 32]) -> Result<String> {
+    Ok(format_hex(&signer.sign_digest(*digest).await?))
+}
+
+// The address behind a 65-byte r || s || v signature over `digest`
+pub(crate) fn recover_digest(digest: &[u8; 32], signature: &[u8]) -> Result<[u8; 20]> {
+    if signature.len() != 65 || signature[64] < 27 {
+        return Err(anyhow!("expected a 65-byte r || s || v signature"));
+    }
+    let recovery_id = RecoveryId::from_byte(signature[64] - 27).ok_or_else(|| anyhow!("invalid recovery id"))?;
+    let signature = Signature::from_slice(&signature[..64])?;
+    let key = VerifyingKey::recover_from_prehash(digest, &signature, recovery_id)?;
+    Ok(verifying_key_address(&key))
+}
+
+// Fresh random attestor key, 0x-prefixed hex, with its EVM address
+pub fn generate_private_key() -> (String, [u8; 20]) {
+    let signing_key = SigningKey::random(&mut rand::rngs::OsRng);
+    let private_key = format!("0x{}", hex::encode(signing_key.to_bytes()));
+    (private_key, key_address(&signing_key))
+}
+
+pub(crate) fn key_address(signing_key: &SigningKey) -> [u8; 20] {
+    verifying_key_address(signing_key.verifying_key())
+}
+
+fn verifying_key_address(key: &VerifyingKey) -> [u8; 20] {
+    let point = key.to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+pub fn eth_signed_message_hash(hash: &[u8; 32]) -> [u8; 32] {
+    let mut message = b"\x19Ethereum Signed Message:\n32".to_vec();
+    message.extend_from_slice(hash);
+    keccak256(&message)
+}
+
+pub fn parse_address(value: &str) -> Result<[u8; 20]> {
+    parse_hex_array(value).with_context(|| format!("invalid EVM address: {}", value))
+}
+
+pub fn parse_hex32(value: &str) -> Result<[u8; 32]> {
+    parse_hex_array(value).with_context(|| format!("invalid 32-byte hex value: {}", value))
+}
+
+pub fn format_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn parse_hex_array<const N: usize>(value: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(strip_0x(value))?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| anyhow!("expected {} bytes, got {}", N, bytes.len()))
+}
+
+fn strip_0x(value: &str) -> &str {
+    value.strip_prefix("0x").unwrap_or(value)
+}
+
+// Left-pads a big-endian value to a 32-byte ABI word
+pub(crate) fn abi_word(bytes: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(bytes);
+    word
+}
+
+mod hex_array {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::format_hex(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+        let value = String::deserialize(deserializer)?;
+        super::parse_hex_array(&value).map_err(D::Error::custom)
+    }
+}
+
+pub(crate) mod u128_string {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const ROUTER: &str = "0x1111111111111111111111111111111111111111";
+
+    #[tokio::test]
+    async fn signature_recovers_to_attestor() {
+        let attestor = Attestor::new(KEY, ROUTER, 20994).unwrap();
+        assert_eq!(format_hex(&attestor.address()), "0x2c7536e3605d9c16a7a3d7b1898e529396a65c23");
+
+        let proof = LockProof::new([7u8; 32], [9u8; 20], 1_000_000, 1_700_000_000);
+        let signature = hex::decode(strip_0x(&attestor.sign(&proof).await.unwrap())).unwrap();
+        assert_eq!(signature.len(), 65);
+
+        let digest = proof.digest(&attestor.router, attestor.chain_id);
+        let recovered = VerifyingKey::recover_from_prehash(
+            &digest,
+            &Signature::from_slice(&signature[..64]).unwrap(),
+            RecoveryId::from_byte(signature[64] - 27).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(verifying_key_address(&recovered), attestor.address());
+        let signature = attestor.sign(&proof).await.unwrap();
+        assert_eq!(attestor.recover_signer(&proof, &signature).unwrap(), attestor.address());
+    }
+
+    #[tokio::test]
+    async fn previous_key_signs_until_its_grace_window_closes() {
+        let (old_key, old_address) = generate_private_key();
+        let previous = PreviousKey {
+            signer: Arc::new(LocalSigner::from_hex(&old_key).unwrap()),
+            valid_until: 1_000,
+        };
+        let attestor = Attestor::new(KEY, ROUTER, 20994).unwrap().with_previous(Some(previous));
+        let proof = LockProof::new([7u8; 32], [9u8; 20], 1_000_000, 1_700_000_000);
+
+        let signed = attestor.sign_previous(&proof, 999).await.unwrap().unwrap();
+        assert_eq!(signed.attestor, format_hex(&old_address));
+        assert_eq!(attestor.recover_signer(&proof, &signed.signature).unwrap(), old_address);
+        assert!(attestor.sign_previous(&proof, 1_000).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn lock_proof_json_uses_hex_and_decimal_strings() {
+        let proof = LockProof::new([1u8; 32], [2u8; 20], u128::MAX, 42);
+        let json = serde_json::to_value(&proof).unwrap();
+        assert_eq!(json["dest"], format_hex(&[2u8; 20]));
+        assert_eq!(json["amount"], u128::MAX.to_string());
+        let back: LockProof = serde_json::from_value(json).unwrap();
+        assert_eq!(back, proof);
+    }
+}
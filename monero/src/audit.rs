@@ -0,0 +1,325 @@
+// Append-only, hash-chained record of every validation and attestation
+// decision, one JSON object per line. Each entry carries the SHA-256 of its
+// predecessor, so edits, deletions and reordering break the chain and
+// `monero verify-audit` reports where.
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::attestation::{self, Attestor};
+use crate::events::unix_now;
+use crate::export::ImportResponse;
+
+// `prev_hash` of the first entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    Validation,
+    Attestation,
+    AttestationRefused,
+    Cosign,
+    CosignRefused,
+    // A proof replaced by its tombstone
+    Deletion,
+    // A validated deposit's block was orphaned
+    Reorg,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: u64,
+    pub kind: AuditKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_id: Option<String>,
+    pub data: Value,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+// Everything but `hash`, in the order it is hashed
+#[derive(Serialize)]
+struct HashedFields<'a> {
+    seq: u64,
+    timestamp: u64,
+    kind: AuditKind,
+    proof_id: &'a Option<String>,
+    data: &'a Value,
+    prev_hash: &'a str,
+}
+
+impl AuditEntry {
+    pub fn compute_hash(&self) -> String {
+        let fields = HashedFields {
+            seq: self.seq,
+            timestamp: self.timestamp,
+            kind: self.kind,
+            proof_id: &self.proof_id,
+            data: &self.data,
+            prev_hash: &self.prev_hash,
+        };
+        let bytes = serde_json::to_vec(&fields).expect("audit entries serialize");
+        hex::encode(Sha256::digest(bytes))
+    }
+}
+
+// The chain head after the last entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainHead {
+    pub entries: u64,
+    pub last_hash: String,
+}
+
+impl Default for ChainHead {
+    fn default() -> Self {
+        Self {
+            entries: 0,
+            last_hash: GENESIS_HASH.to_string(),
+        }
+    }
+}
+
+// Checks every entry of the log at `path`; the error names the first broken line
+pub fn verify(path: &Path) -> Result<ChainHead> {
+    let file = std::fs::File::open(path).with_context(|| format!("could not open {}", path.display()))?;
+    let mut head = ChainHead::default();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.with_context(|| format!("could not read {}", path.display()))?;
+        let entry: AuditEntry = serde_json::from_str(&line)
+            .with_context(|| format!("line {}: not an audit entry (truncated or edited)", line_number))?;
+        if entry.seq != head.entries + 1 {
+            bail!("line {}: seq {} follows {}, entries are missing or reordered", line_number, entry.seq, head.entries);
+        }
+        if entry.prev_hash != head.last_hash {
+            bail!("line {}: prev_hash does not match the previous entry's hash", line_number);
+        }
+        if entry.compute_hash() != entry.hash {
+            bail!("line {}: hash does not match the entry's contents", line_number);
+        }
+        head = ChainHead {
+            entries: entry.seq,
+            last_hash: entry.hash,
+        };
+    }
+    Ok(head)
+}
+
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    head: Mutex<ChainHead>,
+}
+
+impl AuditLog {
+    // Records nothing
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            head: Mutex::new(ChainHead::default()),
+        }
+    }
+
+    // Continues the chain in `path`. Refuses a log that no longer verifies,
+    // so new entries are never chained onto tampered ones.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+        }
+        let head = if path.exists() {
+            verify(path).with_context(|| format!("audit log {} does not verify", path.display()))?
+        } else {
+            ChainHead::default()
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            head: Mutex::new(head),
+        })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    // Appends and syncs one entry. Callers that sign treat a failure as
+    // fatal, so nothing is released without its record.
+    pub async fn record(&self, kind: AuditKind, proof_id: Option<&str>, data: Value) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut head = self.head.lock().await;
+        let mut entry = AuditEntry {
+            seq: head.entries + 1,
+            timestamp: unix_now(),
+            kind,
+            proof_id: proof_id.map(str::to_string),
+            data,
+            prev_hash: head.last_hash.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("could not open audit log {}", path.display()))?;
+        file.write_all(&line)
+            .await
+            .and(file.sync_data().await)
+            .map_err(|e| anyhow!("could not append to audit log {}: {}", path.display(), e))?;
+        *head = ChainHead {
+            entries: entry.seq,
+            last_hash: entry.hash,
+        };
+        Ok(())
+    }
+    // Adds the entries of another copy of this log. Entries already present
+    // with the same hash are skipped, the rest must continue the chain, so
+    // importing the same export twice changes nothing and a diverging log is
+    // refused before anything is written.
+    pub async fn import(&self, mut entries: Vec<AuditEntry>) -> Result<ImportResponse> {
+        let Some(path) = &self.path else {
+            bail!("the audit log is disabled");
+        };
+        let mut head = self.head.lock().await;
+        let mut existing = Vec::new();
+        if path.exists() {
+            let file = std::fs::File::open(path).with_context(|| format!("could not open {}", path.display()))?;
+            for line in BufReader::new(file).lines() {
+                existing.push(serde_json::from_str::<AuditEntry>(&line?)?.hash);
+            }
+        }
+        entries.sort_by_key(|entry| entry.seq);
+        let mut response = ImportResponse::default();
+        let mut lines = Vec::new();
+        let mut next = head.clone();
+        for entry in entries {
+            if entry.compute_hash() != entry.hash {
+                bail!("entry {}: hash does not match the entry's contents", entry.seq);
+            }
+            if entry.seq >= 1 && entry.seq <= next.entries {
+                // `existing` covers the file, `lines` what this import appends
+                let index = (entry.seq - 1) as usize;
+                let known = existing.get(index).cloned().or_else(|| {
+                    let appended: &AuditEntry = lines.get(index - existing.len())?;
+                    Some(appended.hash.clone())
+                });
+                if known.as_deref() != Some(entry.hash.as_str()) {
+                    bail!("entry {}: differs from the entry already in the log", entry.seq);
+                }
+                response.skipped += 1;
+                continue;
+            }
+            if entry.seq != next.entries + 1 || entry.prev_hash != next.last_hash {
+                bail!("entry {}: does not continue the chain after entry {}", entry.seq, next.entries);
+            }
+            next = ChainHead {
+                entries: entry.seq,
+                last_hash: entry.hash.clone(),
+            };
+            lines.push(entry);
+            response.imported += 1;
+        }
+        if lines.is_empty() {
+            return Ok(response);
+        }
+        let mut bytes = Vec::new();
+        for entry in &lines {
+            bytes.extend(serde_json::to_vec(entry)?);
+            bytes.push(b'\n');
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("could not open audit log {}", path.display()))?;
+        file.write_all(&bytes)
+            .await
+            .and(file.sync_data().await)
+            .map_err(|e| anyhow!("could not append to audit log {}: {}", path.display(), e))?;
+        *head = next;
+        Ok(response)
+    }
+}
+
+// Who signed, for the `signer` field of attestation entries
+pub fn signer(attestor: &Attestor) -> Value {
+    serde_json::json!({
+        "attestor": attestation::format_hex(&attestor.address()),
+        "router": attestation::format_hex(&attestor.router),
+        "chain_id": attestor.chain_id,
+        "network": attestor.network,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn verification_detects_edits_and_gaps() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = AuditLog::open(&path).unwrap();
+        for amount in [1, 2, 3] {
+            log.record(AuditKind::Validation, Some("p1"), json!({ "amount": amount })).await.unwrap();
+        }
+        // Reopening continues the chain
+        let log = AuditLog::open(&path).unwrap();
+        log.record(AuditKind::Attestation, Some("p1"), json!({ "signature": "0x01" })).await.unwrap();
+        assert_eq!(verify(&path).unwrap().entries, 4);
+
+        let original = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = original.lines().collect();
+
+        let edited = original.replacen("\"amount\":2", "\"amount\":20", 1);
+        std::fs::write(&path, edited).unwrap();
+        assert!(verify(&path).unwrap_err().to_string().contains("line 2: hash"));
+        assert!(AuditLog::open(&path).is_err());
+
+        let gap = [lines[0], lines[2], lines[3]].join("\n");
+        std::fs::write(&path, gap).unwrap();
+        assert!(verify(&path).unwrap_err().to_string().contains("line 2: seq 3 follows 1"));
+    }
+
+    #[tokio::test]
+    async fn import_skips_known_entries_and_refuses_forks() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_path = dir.path().join("source.jsonl");
+        let source = AuditLog::open(&source_path).unwrap();
+        for amount in [1, 2, 3] {
+            source.record(AuditKind::Validation, Some("p1"), json!({ "amount": amount })).await.unwrap();
+        }
+        let text = std::fs::read_to_string(&source_path).unwrap();
+        let entries: Vec<AuditEntry> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+        let target_path = dir.path().join("target.jsonl");
+        let target = AuditLog::open(&target_path).unwrap();
+        let first = target.import(entries[..2].to_vec()).await.unwrap();
+        assert_eq!((first.imported, first.skipped), (2, 0));
+        let all = target.import(entries.clone()).await.unwrap();
+        assert_eq!((all.imported, all.skipped), (1, 2));
+        assert_eq!(std::fs::read_to_string(&target_path).unwrap(), text);
+
+        // A log that went its own way after entry 2
+        let fork_path = dir.path().join("fork.jsonl");
+        std::fs::write(&fork_path, format!("{}\n", text.lines().take(2).collect::<Vec<_>>().join("\n"))).unwrap();
+        let fork = AuditLog::open(&fork_path).unwrap();
+        fork.record(AuditKind::Deletion, Some("p1"), json!({})).await.unwrap();
+        let error = fork.import(entries).await.unwrap_err();
+        assert!(error.to_string().contains("entry 3: differs"), "{}", error);
+        assert_eq!(verify(&fork_path).unwrap().entries, 3);
+    }
+}
@@ -0,0 +1,479 @@
+// Operator commands. Everything except `serve` runs in-process against the
+// configured RPC endpoints and proof store, without a running server.
+use anyhow::{Context, Result, anyhow};
+use clap::{Args, Parser, Subcommand};
+use k256::ecdsa::SigningKey;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::amount::WfoidConversion;
+use crate::api::{AttestRequest, ProofRequest, TransactionProof, ValidationRequest};
+use crate::attestation::{self, Attestor};
+use crate::audit::{self, AuditEntry, AuditLog};
+use crate::config::Config;
+use crate::coordination::Coordinator;
+use crate::events::{EventBus, unix_now};
+use crate::export::{self, Encoder, Format, Record};
+use crate::keystore::{Keystore, STANDARD_SCRYPT};
+use crate::policy::Policy;
+use crate::privacy::ProofPreimage;
+use crate::proofs;
+use crate::rpc::MoneroService;
+use crate::server;
+use crate::storage::ProofStore;
+use crate::validation::Validate;
+
+#[derive(Debug, Parser)]
+#[command(name = "monero", version, about = "Monero transaction validation server and operator tools")]
+pub struct Cli {
+    /// Config file (default: $MONERO_CONFIG, then ./monero.toml)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the HTTP server (the default)
+    Serve,
+    /// Create or inspect stored proofs
+    #[command(subcommand)]
+    Proof(ProofCommand),
+    /// Check a tx key against the daemons and record the result
+    Validate(ValidateArgs),
+    /// Sign a bridge attestation for a validated proof
+    Attest {
+        proof_id: String,
+        /// EVM address that receives the minted wFOID
+        #[arg(long)]
+        dest: String,
+        /// With --tx-key and --recipient, the preimage of a proof stored as a commitment
+        #[arg(long, requires_all = ["tx_key", "recipient"])]
+        tx_id: Option<String>,
+        #[arg(long, requires_all = ["tx_id", "recipient"])]
+        tx_key: Option<String>,
+        #[arg(long, requires_all = ["tx_id", "tx_key"])]
+        recipient: Option<String>,
+    },
+    /// Back up or restore the proof store
+    #[command(subcommand)]
+    Storage(StorageCommand),
+    /// Manage the attestor key
+    #[command(subcommand)]
+    Keys(KeysCommand),
+    /// Inspect the configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Check that the audit log is complete and unmodified
+    VerifyAudit {
+        /// Log to check (default: the configured audit log)
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProofCommand {
+    /// Fetch the tx key from the wallet and store a new proof
+    Create {
+        #[arg(long)]
+        tx_id: String,
+        #[arg(long)]
+        recipient: String,
+    },
+    /// Print a stored proof
+    Get { proof_id: String },
+}
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    #[arg(long)]
+    pub tx_id: String,
+    #[arg(long)]
+    pub tx_key: String,
+    #[arg(long)]
+    pub recipient: String,
+    /// Additional tx key of a subaddress output; repeat once per key
+    #[arg(long = "additional-tx-key", value_name = "KEY")]
+    pub additional_tx_keys: Vec<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum StorageCommand {
+    /// Write every proof, or the audit log, as JSON, JSON Lines or CSV
+    Export {
+        /// Output file (default: stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Default: from the --out extension, else json
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+        /// Export the audit log instead of the proofs
+        #[arg(long)]
+        audit: bool,
+    },
+    /// Load proofs or audit entries written by `storage export`; importing
+    /// the same file again changes nothing
+    Import {
+        file: PathBuf,
+        /// Default: from the file extension, else json
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+        /// Append audit entries that continue the configured audit log
+        #[arg(long, conflicts_with = "overwrite")]
+        audit: bool,
+        /// Replace proofs that already exist
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeysCommand {
+    /// Generate a new attestor key and write it as an env file or keystore
+    Rotate {
+        /// Where to write `ATTESTOR_PRIVKEY=...` (created with mode 0600)
+        #[arg(long)]
+        out: PathBuf,
+        #[arg(long)]
+        force: bool,
+        /// Write an encrypted JSON keystore, locked with $ATTESTOR_KEYSTORE_PASSWORD
+        #[arg(long)]
+        keystore: bool,
+        /// How long the current key keeps co-signing after the switch
+        #[arg(long, default_value_t = 24)]
+        grace_hours: u64,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Validate the configuration and print it with defaults filled in
+    Check {
+        /// Also query every daemon and the wallet
+        #[arg(long)]
+        probe: bool,
+    },
+}
+
+pub async fn run(cli: Cli) -> Result<()> {
+    let config = Config::load(cli.config.as_deref())?;
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => {
+            println!("🚀 Starting Monero Transaction Validation Server...");
+            if let Some(path) = Config::resolve_path(cli.config.as_deref()) {
+                println!("⚙️  Loaded config from {}", path.display());
+            }
+            server::run(config, Config::resolve_path(cli.config.as_deref())).await
+        }
+        Command::Proof(ProofCommand::Create { tx_id, recipient }) => {
+            let storage = open_store(&config)?;
+            let monero_service = MoneroService::new(&config.monero)?;
+            let request = ProofRequest {
+                tx_id,
+                recipient_address: recipient,
+            };
+            request.validate()?;
+            let proof = proofs::create_proof(&monero_service, &storage, &EventBus::new(1), &request).await?;
+            storage.flush().await?;
+            print_json(&proof)
+        }
+        Command::Proof(ProofCommand::Get { proof_id }) => {
+            let storage = open_store(&config)?;
+            let proofs = storage.read().await;
+            let proof = proofs.get(&proof_id).ok_or_else(|| anyhow!("proof {} not found", proof_id))?;
+            print_json(proof)
+        }
+        Command::Validate(args) => {
+            // Without a data dir there is nothing to record, but the check still runs
+            let storage = ProofStore::open(config.storage.data_dir.as_deref())?.with_privacy(config.storage.privacy)?;
+            let monero_service = MoneroService::new(&config.monero)?;
+            let request = ValidationRequest {
+                tx_id: args.tx_id,
+                tx_key: args.tx_key,
+                recipient_address: args.recipient,
+                additional_tx_keys: args.additional_tx_keys,
+            };
+            request.validate()?;
+            let audit = open_audit(&config)?;
+            let validation = proofs::validate(&monero_service, &storage, &EventBus::new(1), &audit, &request).await?;
+            storage.flush().await?;
+            print_json(&validation.response())
+        }
+        Command::Attest { proof_id, dest, tx_id, tx_key, recipient } => {
+            let storage = open_store(&config)?;
+            // The operator reads the store directly; only committed proofs
+            // need their deposit data on the command line
+            let preimage = match (tx_id, tx_key, recipient) {
+                (Some(tx_id), Some(tx_key), Some(recipient_address)) => {
+                    Some(ProofPreimage { tx_id, tx_key, recipient_address })
+                }
+                _ => storage
+                    .read()
+                    .await
+                    .get(&proof_id)
+                    .filter(|proof| proof.commitment.is_none())
+                    .map(ProofPreimage::of),
+            };
+            let request = AttestRequest { dest, preimage };
+            request.validate()?;
+            let conversion = WfoidConversion::new(&config.bridge)?;
+            let coordinator = Coordinator::from_env(&config.coordination)?;
+            let attestor = Attestor::load(&config.attestor)?.map(|attestor| {
+                attestor
+                    .with_network(config.monero.network)
+                    .with_deposit_address(config.bridge.deposit_address.clone())
+                    .with_conversion(conversion)
+                    .with_coordinator(coordinator)
+            });
+            let policy = Policy::new(&config.policy)?;
+            let audit = open_audit(&config)?;
+            let attestation = proofs::attest(
+                attestor.as_ref(),
+                &policy,
+                &storage,
+                &EventBus::new(1),
+                &audit,
+                &proof_id,
+                &request,
+            )
+            .await?;
+            storage.flush().await?;
+            print_json(&attestation)
+        }
+        Command::Storage(StorageCommand::Export { out, format, audit }) => {
+            let format = format.or(out.as_deref().map(Format::from_path)).unwrap_or(Format::Json);
+            let writer: Box<dyn Write> = match &out {
+                Some(path) => {
+                    let file = std::fs::File::create(path).with_context(|| format!("could not create {}", path.display()))?;
+                    Box::new(std::io::BufWriter::new(file))
+                }
+                None => Box::new(std::io::stdout().lock()),
+            };
+            let (exported, kind) = if audit {
+                let path = config.audit_path().ok_or_else(|| anyhow!("no audit log configured"))?;
+                (export_audit(&path, format, writer)?, "audit entries")
+            } else {
+                let storage = open_store(&config)?;
+                let mut proofs: Vec<TransactionProof> = storage.read().await.values().cloned().collect();
+                proofs.sort_by(|a, b| a.proof_id.cmp(&b.proof_id));
+                (export_records(&proofs, format, writer)?, "proofs")
+            };
+            if let Some(path) = out {
+                eprintln!("📦 Exported {} {} to {}", exported, kind, path.display());
+            }
+            Ok(())
+        }
+        Command::Storage(StorageCommand::Import { file, format, audit, overwrite }) => {
+            let format = format.unwrap_or_else(|| Format::from_path(&file));
+            let reader = std::fs::File::open(&file).with_context(|| format!("could not read {}", file.display()))?;
+            let reader = std::io::BufReader::new(reader);
+            let context = || format!("{} is not a {} export", file.display(), format.extension());
+            let (response, kind) = if audit {
+                let entries = export::decode(reader, format).with_context(context)?;
+                let log = open_audit(&config)?;
+                (log.import(entries).await?, "audit entries")
+            } else {
+                let proofs = export::decode(reader, format).with_context(context)?;
+                let storage = open_store(&config)?;
+                let private = storage.is_private();
+                let response = export::import_proofs(&mut *storage.write().await, proofs, overwrite, private)?;
+                storage.flush().await?;
+                (response, "proofs")
+            };
+            eprintln!("📥 Imported {} {}, skipped {} existing", response.imported, kind, response.skipped);
+            Ok(())
+        }
+        Command::Keys(KeysCommand::Rotate { out, force, keystore, grace_hours }) => {
+            rotate_key(&config, &out, force, keystore, grace_hours)
+        }
+        Command::Config(ConfigCommand::Check { probe }) => check_config(&config, cli.config.as_deref(), probe).await,
+        Command::VerifyAudit { path } => {
+            let path = path
+                .or_else(|| config.audit_path())
+                .ok_or_else(|| anyhow!("no audit log configured, pass its path"))?;
+            let head = audit::verify(&path).with_context(|| format!("{} failed verification", path.display()))?;
+            println!("✅ {} entries verified in {}", head.entries, path.display());
+            println!("🔗 Last hash: {}", head.last_hash);
+            Ok(())
+        }
+    }
+}
+
+// Offline commands need somewhere to read and write proofs. Stop the server
+// first: it would overwrite the store with its own copy on the next flush.
+fn open_store(config: &Config) -> Result<ProofStore> {
+    let data_dir = config
+        .storage
+        .data_dir
+        .as_deref()
+        .ok_or_else(|| anyhow!("storage.data_dir is not set, proofs only exist inside a running server"))?;
+    ProofStore::open(Some(data_dir))?.with_privacy(config.storage.privacy)
+}
+
+// Offline decisions are audited to the same log as the server's, which
+// also has to be stopped first so both don't extend the chain at once
+fn open_audit(config: &Config) -> Result<AuditLog> {
+    match config.audit_path() {
+        Some(path) => AuditLog::open(&path),
+        None => Ok(AuditLog::disabled()),
+    }
+}
+
+fn export_records<R: Record>(records: &[R], format: Format, mut writer: impl Write) -> Result<usize> {
+    let mut encoder = Encoder::new(format);
+    writer.write_all(&encoder.begin::<R>()?)?;
+    for record in records {
+        writer.write_all(&encoder.record(record)?)?;
+    }
+    writer.write_all(&encoder.finish())?;
+    writer.flush()?;
+    Ok(records.len())
+}
+
+// Streams the log entry by entry rather than loading it whole
+fn export_audit(path: &Path, format: Format, mut writer: impl Write) -> Result<usize> {
+    // A log nothing was recorded to yet is empty
+    let lines = match std::fs::File::open(path) {
+        Ok(file) => Some(std::io::BufReader::new(file).lines()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e).with_context(|| format!("could not open {}", path.display())),
+    };
+    let mut encoder = Encoder::new(format);
+    writer.write_all(&encoder.begin::<AuditEntry>()?)?;
+    let mut exported = 0;
+    for (index, line) in lines.into_iter().flatten().enumerate() {
+        let entry: AuditEntry = serde_json::from_str(&line?).with_context(|| format!("line {}", index + 1))?;
+        writer.write_all(&encoder.record(&entry)?)?;
+        exported += 1;
+    }
+    writer.write_all(&encoder.finish())?;
+    writer.flush()?;
+    Ok(exported)
+}
+
+fn rotate_key(config: &Config, out: &Path, force: bool, keystore: bool, grace_hours: u64) -> Result<()> {
+    if out.exists() && !force {
+        return Err(anyhow!("{} already exists, pass --force to replace it", out.display()));
+    }
+    let password = if keystore {
+        Some(std::env::var("ATTESTOR_KEYSTORE_PASSWORD").context("set ATTESTOR_KEYSTORE_PASSWORD to encrypt the keystore")?)
+    } else {
+        None
+    };
+    let current = Attestor::load(&config.attestor).ok().flatten();
+    let signing_key = SigningKey::random(&mut rand::rngs::OsRng);
+    let address = attestation::key_address(&signing_key);
+    let contents = match &password {
+        Some(password) => serde_json::to_string_pretty(&Keystore::encrypt(&signing_key, password, STANDARD_SCRYPT)?)?,
+        None => format!("ATTESTOR_PRIVKEY={}", attestation::format_hex(&signing_key.to_bytes())),
+    };
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(out).with_context(|| format!("could not create {}", out.display()))?;
+    writeln!(file, "{}", contents)?;
+
+    if let Some(current) = current {
+        println!("🔑 Current attestor: {}", attestation::format_hex(&current.address()));
+    }
+    println!("🔑 New attestor:     {}", attestation::format_hex(&address));
+    println!("📝 Key written to {}", out.display());
+    println!("   1. Register the new address in the AttestorRegistry");
+    if keystore {
+        println!("   2. Point [attestor.key] at it and move the current key to [attestor.previous]:");
+        println!("        [attestor.key]");
+        println!("        backend = \"keystore\"");
+        println!("        path = \"{}\"", out.display());
+    } else {
+        println!("   2. Set ATTESTOR_PRIVKEY from {} and move the current key to [attestor.previous]:", out.display());
+    }
+    println!("        [attestor.previous]");
+    println!("        valid_until = {}", unix_now() + grace_hours * 3600);
+    println!("        [attestor.previous.key]");
+    println!("        # the current [attestor.key] settings");
+    println!("   3. Restart the server; until valid_until attestations also carry the previous key's signature");
+    println!("   4. Remove the old attestor from the registry once pending attestations have been minted");
+    Ok(())
+}
+
+async fn check_config(config: &Config, path: Option<&Path>, probe: bool) -> Result<()> {
+    match Config::resolve_path(path) {
+        Some(path) => println!("⚙️  Config file: {}", path.display()),
+        None => println!("⚙️  No config file found, using defaults"),
+    }
+    print!("{}", toml::to_string_pretty(config)?);
+
+    let mut ok = true;
+    let storage = ProofStore::open(config.storage.data_dir.as_deref())?;
+    match storage.check_writable().await {
+        Ok(()) => println!("✅ Storage: {}", storage.data_dir().map_or("in-memory".into(), |d| d.display().to_string())),
+        Err(e) => {
+            ok = false;
+            println!("❌ Storage: {:#}", e);
+        }
+    }
+    match Attestor::load(&config.attestor) {
+        Ok(Some(attestor)) => {
+            println!("✅ Attestor: {}", attestation::format_hex(&attestor.address()));
+            if let Some(previous) = attestor.previous(unix_now()) {
+                let address = attestation::format_hex(&previous.signer.address());
+                println!("✅ Previous attestor: {} until {}", address, previous.valid_until);
+            }
+        }
+        Ok(None) => println!("⚠️  Attestor: no key configured"),
+        Err(e) => {
+            ok = false;
+            println!("❌ Attestor: {:#}", e);
+        }
+    }
+    if probe {
+        let monero_service = MoneroService::new(&config.monero)?;
+        monero_service.pool.check_health().await;
+        for daemon in monero_service.pool.status() {
+            match daemon.last_error {
+                None => println!("✅ Daemon {} at height {}", daemon.url, daemon.height.unwrap_or_default()),
+                Some(e) => {
+                    ok = false;
+                    println!("❌ Daemon {}: {}", daemon.url, e);
+                }
+            }
+        }
+        if monero_service.wallet.is_some() {
+            match monero_service.get_wallet_version().await {
+                Ok(version) => println!("✅ Wallet: version {}", version),
+                Err(e) => {
+                    ok = false;
+                    println!("❌ Wallet: {:#}", e);
+                }
+            }
+        }
+    }
+    if ok { Ok(()) } else { Err(anyhow!("configuration check failed")) }
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn parses_nested_subcommands() {
+        Cli::command().debug_assert();
+        let cli = Cli::parse_from(["monero", "--config", "x.toml", "storage", "import", "dump.csv", "--overwrite"]);
+        assert_eq!(cli.config.as_deref(), Some(Path::new("x.toml")));
+        assert!(matches!(
+            cli.command,
+            Some(Command::Storage(StorageCommand::Import { overwrite: true, .. }))
+        ));
+        assert!(Cli::parse_from(["monero"]).command.is_none());
+    }
+}
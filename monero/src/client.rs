@@ -0,0 +1,351 @@
+use anyhow::{Context, Result};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::api::{
+    AttestRequest, AttestResponse, DeletedResponse, ErrorResponse, HealthResponse, LivenessResponse, ProofRequest,
+    ProofResponse, ProofV1, ProofV2, ReadinessResponse, ReplayResponse, ValidationRequest,
+    ValidationResponse, ValidationResponseV2,
+};
+use crate::export::{ExportQuery, Format, ImportQuery, ImportResponse};
+use crate::privacy::ProofPreimage;
+use crate::validation::FieldError;
+use crate::webhooks::{DeadLetter, RegisterWebhookRequest, WebhookEndpoint};
+
+// A non-2xx answer from the server, with its JSON error envelope
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub error: String,
+    pub message: String,
+    // Per-field problems when the server rejected the request body
+    pub fields: Vec<FieldError>,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.error, self.status, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+// Typed async client for the validation server's HTTP API, `/v1` unless the
+// method name says `_v2`. Failed requests return an `ApiError` inside the
+// `anyhow::Error` when the server answered.
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    admin_token: Option<String>,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            admin_token: None,
+        }
+    }
+
+    // Bearer token for the admin (webhook) endpoints
+    pub fn with_admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin_token = Some(token.into());
+        self
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub async fn health(&self) -> Result<HealthResponse> {
+        self.send_any_status(self.request(Method::GET, "/health")).await
+    }
+
+    pub async fn liveness(&self) -> Result<LivenessResponse> {
+        self.send(self.request(Method::GET, "/health/live")).await
+    }
+
+    // Also returns the body when the server is not ready (503)
+    pub async fn readiness(&self) -> Result<ReadinessResponse> {
+        self.send_any_status(self.request(Method::GET, "/health/ready")).await
+    }
+
+    pub async fn generate_proof(&self, request: &ProofRequest) -> Result<ProofResponse> {
+        self.send(self.request(Method::POST, "/v1/proof").json(request)).await
+    }
+
+    pub async fn validate(&self, request: &ValidationRequest) -> Result<ValidationResponse> {
+        self.send(self.request(Method::POST, "/v1/validate").json(request)).await
+    }
+
+    pub async fn proofs(&self) -> Result<Vec<ProofV1>> {
+        self.send(self.request(Method::GET, "/v1/proofs")).await
+    }
+
+    // `None` when the server doesn't know the proof
+    pub async fn proof(&self, proof_id: &str) -> Result<Option<ProofV1>> {
+        match self.send(self.request(Method::GET, &format!("/v1/proof/{}", proof_id))).await {
+            Ok(proof) => Ok(Some(proof)),
+            Err(e) if status_of(&e) == Some(StatusCode::NOT_FOUND) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Only the proof's owner knows `preimage`
+    pub async fn delete_proof(&self, proof_id: &str, preimage: &ProofPreimage) -> Result<DeletedResponse> {
+        self.send(self.request(Method::DELETE, &format!("/v1/proof/{}", proof_id)).json(preimage))
+            .await
+    }
+
+    pub async fn attest(&self, proof_id: &str, request: &AttestRequest) -> Result<AttestResponse> {
+        self.send(self.request(Method::POST, &format!("/v1/proof/{}/attest", proof_id)).json(request))
+            .await
+    }
+
+    pub async fn generate_proof_v2(&self, request: &ProofRequest) -> Result<ProofV2> {
+        self.send(self.request(Method::POST, "/v2/proof").json(request)).await
+    }
+
+    pub async fn validate_v2(&self, request: &ValidationRequest) -> Result<ValidationResponseV2> {
+        self.send(self.request(Method::POST, "/v2/validate").json(request)).await
+    }
+
+    pub async fn proofs_v2(&self) -> Result<Vec<ProofV2>> {
+        self.send(self.request(Method::GET, "/v2/proofs")).await
+    }
+
+    // `None` when the server doesn't know the proof
+    pub async fn proof_v2(&self, proof_id: &str) -> Result<Option<ProofV2>> {
+        match self.send(self.request(Method::GET, &format!("/v2/proof/{}", proof_id))).await {
+            Ok(proof) => Ok(Some(proof)),
+            Err(e) if status_of(&e) == Some(StatusCode::NOT_FOUND) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn attest_v2(&self, proof_id: &str, request: &AttestRequest) -> Result<ProofV2> {
+        self.send(self.request(Method::POST, &format!("/v2/proof/{}/attest", proof_id)).json(request))
+            .await
+    }
+
+    pub async fn register_webhook(&self, request: &RegisterWebhookRequest) -> Result<WebhookEndpoint> {
+        self.send(self.request(Method::POST, "/v1/webhooks").json(request)).await
+    }
+
+    pub async fn webhooks(&self) -> Result<Vec<WebhookEndpoint>> {
+        self.send(self.request(Method::GET, "/v1/webhooks")).await
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: &str) -> Result<DeletedResponse> {
+        self.send(self.request(Method::DELETE, &format!("/v1/webhooks/{}", webhook_id)))
+            .await
+    }
+
+    pub async fn dead_letters(&self) -> Result<Vec<DeadLetter>> {
+        self.send(self.request(Method::GET, "/v1/webhooks/dead-letters")).await
+    }
+
+    pub async fn replay_dead_letter(&self, dead_letter_id: &str) -> Result<ReplayResponse> {
+        self.send(self.request(Method::POST, &format!("/v1/webhooks/dead-letters/{}/replay", dead_letter_id)))
+            .await
+    }
+
+    // Every proof, encoded as `format`
+    pub async fn export_proofs(&self, format: Format) -> Result<Vec<u8>> {
+        self.send_bytes(self.request(Method::GET, "/v1/export/proofs").query(&ExportQuery { format: Some(format) }))
+            .await
+    }
+
+    pub async fn export_audit(&self, format: Format) -> Result<Vec<u8>> {
+        self.send_bytes(self.request(Method::GET, "/v1/export/audit").query(&ExportQuery { format: Some(format) }))
+            .await
+    }
+
+    // `body` as written by `export_proofs` or `monero storage export`
+    pub async fn import_proofs(&self, body: Vec<u8>, format: Format, overwrite: bool) -> Result<ImportResponse> {
+        let query = ImportQuery { format: Some(format), overwrite };
+        self.send(self.request(Method::POST, "/v1/import/proofs").query(&query).body(body))
+            .await
+    }
+
+    pub async fn import_audit(&self, body: Vec<u8>, format: Format) -> Result<ImportResponse> {
+        let query = ImportQuery { format: Some(format), overwrite: false };
+        self.send(self.request(Method::POST, "/v1/import/audit").query(&query).body(body))
+            .await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self.http.request(method, format!("{}{}", self.base_url, path));
+        match &self.admin_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let response = self.send_checked(request).await?;
+        response.json().await.context("invalid response from validation server")
+    }
+
+    async fn send_bytes(&self, request: RequestBuilder) -> Result<Vec<u8>> {
+        let response = self.send_checked(request).await?;
+        let body = response.bytes().await.context("invalid response from validation server")?;
+        Ok(body.to_vec())
+    }
+
+    // The response if it was a success, its `ApiError` otherwise
+    async fn send_checked(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        let response = request.send().await.context("request to validation server failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body: Option<ErrorResponse> = response.json().await.ok();
+            let (error, message, fields) = body
+                .map(|body| (body.error, body.message, body.fields))
+                .unwrap_or_else(|| (status.canonical_reason().unwrap_or("Error").to_string(), String::new(), Vec::new()));
+            return Err(ApiError { status, error, message, fields }.into());
+        }
+        Ok(response)
+    }
+
+    // For endpoints whose error statuses carry the regular body
+    async fn send_any_status<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let response = request.send().await.context("request to validation server failed")?;
+        response.json().await.context("invalid response from validation server")
+    }
+}
+
+// The HTTP status of a failed call, if the server answered at all
+pub fn status_of(error: &anyhow::Error) -> Option<StatusCode> {
+    error.downcast_ref::<ApiError>().map(|e| e.status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Piconero;
+    use crate::server::routes;
+    use crate::testing::{ADDRESS, StubDaemon, server_state};
+
+    fn spawn_server(daemon: String) -> String {
+        let (addr, server) = warp::serve(routes(server_state(daemon))).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn generates_validates_and_fetches_proofs() {
+        let client = Client::new(spawn_server(StubDaemon::default().spawn()));
+        let tx_id = "ab".repeat(32);
+
+        let created = client
+            .generate_proof(&ProofRequest {
+                tx_id: tx_id.clone(),
+                recipient_address: ADDRESS.to_string(),
+            })
+            .await
+            .unwrap();
+        let validated = client
+            .validate(&ValidationRequest {
+                tx_id,
+                tx_key: created.tx_key.clone(),
+                recipient_address: ADDRESS.to_string(),
+                additional_tx_keys: Vec::new(),
+            })
+            .await
+            .unwrap();
+        assert!(validated.valid);
+        assert_eq!(validated.amount, Some(Piconero(1_000_000)));
+
+        let proof = client.proof(&created.proof_id).await.unwrap().unwrap();
+        assert_eq!(proof.amount, Some(Piconero(1_000_000)));
+        assert_eq!(client.proofs().await.unwrap().len(), 1);
+        assert!(client.proof("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn v2_returns_full_proofs_with_confirmations() {
+        let client = Client::new(spawn_server(StubDaemon::default().spawn()));
+        let request = ProofRequest {
+            tx_id: "cd".repeat(32),
+            recipient_address: ADDRESS.to_string(),
+        };
+
+        let created = client.generate_proof_v2(&request).await.unwrap();
+        assert_eq!(created.status, crate::api::ProofStatus::Created);
+        // The stub mines the transaction 10 blocks below its tip
+        assert_eq!(created.confirmations, Some(10));
+
+        let validated = client
+            .validate_v2(&ValidationRequest {
+                tx_id: request.tx_id,
+                tx_key: created.tx_key.clone(),
+                recipient_address: request.recipient_address,
+                additional_tx_keys: Vec::new(),
+            })
+            .await
+            .unwrap();
+        let proof = validated.proof.unwrap();
+        assert_eq!(proof.proof_id, created.proof_id);
+        assert_eq!(proof.status, crate::api::ProofStatus::Validated);
+        assert_eq!(client.proofs_v2().await.unwrap()[0].confirmations, Some(10));
+    }
+
+    #[tokio::test]
+    async fn surfaces_api_errors() {
+        let base_url = spawn_server(StubDaemon::default().spawn());
+
+        // No attestor key is loaded
+        let error = Client::new(&base_url)
+            .attest("missing", &AttestRequest { dest: "0x0000000000000000000000000000000000000001".to_string(), preimage: None })
+            .await
+            .unwrap_err();
+        assert_eq!(status_of(&error), Some(StatusCode::SERVICE_UNAVAILABLE));
+
+        // Malformed bodies are rejected field by field before any RPC call
+        let error = Client::new(&base_url)
+            .validate(&ValidationRequest {
+                tx_id: "abc123def456".to_string(),
+                tx_key: "ab".repeat(32),
+                recipient_address: ADDRESS.to_string(),
+                additional_tx_keys: Vec::new(),
+            })
+            .await
+            .unwrap_err();
+        let api_error = error.downcast_ref::<ApiError>().unwrap();
+        assert_eq!(api_error.status, StatusCode::BAD_REQUEST);
+        assert_eq!(api_error.fields.len(), 1);
+        assert_eq!(api_error.fields[0].field, "tx_id");
+
+        let error = Client::new(&base_url).webhooks().await.unwrap_err();
+        assert_eq!(status_of(&error), Some(StatusCode::UNAUTHORIZED));
+        assert!(Client::new(&base_url).with_admin_token("admin").webhooks().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn exports_import_into_another_server_once() {
+        let daemon = StubDaemon::default().spawn();
+        let source = Client::new(spawn_server(daemon.clone())).with_admin_token("admin");
+        let target = Client::new(spawn_server(daemon)).with_admin_token("admin");
+        for tx_id in ["ab", "cd"] {
+            let request = ProofRequest { tx_id: tx_id.repeat(32), recipient_address: ADDRESS.to_string() };
+            source.generate_proof(&request).await.unwrap();
+        }
+
+        let csv = source.export_proofs(Format::Csv).await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&csv).lines().count(), 3);
+        let imported = target.import_proofs(csv.clone(), Format::Csv, false).await.unwrap();
+        assert_eq!((imported.imported, imported.skipped), (2, 0));
+        let again = target.import_proofs(csv, Format::Csv, false).await.unwrap();
+        assert_eq!((again.imported, again.skipped), (0, 2));
+        assert_eq!(target.export_proofs(Format::Jsonl).await.unwrap(), source.export_proofs(Format::Jsonl).await.unwrap());
+
+        let error = Client::new(&source.base_url).export_proofs(Format::Jsonl).await.unwrap_err();
+        assert_eq!(status_of(&error), Some(StatusCode::UNAUTHORIZED));
+        // The test servers keep no audit log
+        let error = source.export_audit(Format::Jsonl).await.unwrap_err();
+        assert_eq!(status_of(&error), Some(StatusCode::NOT_FOUND));
+    }
+}
@@ -0,0 +1,542 @@
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::amount::WfoidConversion;
+use crate::attestation;
+use crate::network::Network;
+use crate::policy::Policy;
+use crate::scanner;
+
+pub const DEFAULT_CONFIG_FILE: &str = "monero.toml";
+const AUDIT_FILE: &str = "audit.jsonl";
+
+// Server configuration, read from `$MONERO_CONFIG` or `./monero.toml`.
+// Every field has a default so the server also starts without a file.
+// Secrets (attestor key, admin token) stay in the environment or in files
+// `[attestor]` points at.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub monero: MoneroConfig,
+    pub storage: StorageConfig,
+    pub bridge: BridgeConfig,
+    pub policy: PolicyConfig,
+    pub coordination: CoordinationConfig,
+    pub attestor: AttestorConfig,
+    pub audit: AuditConfig,
+    pub fluent: FluentConfig,
+    pub retention: RetentionConfig,
+    pub finality: FinalityConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: SocketAddr,
+    // How long in-flight requests and background tasks get to finish on shutdown
+    pub shutdown_timeout_secs: u64,
+    // HTTP-date sent as `Sunset` on the deprecated unversioned routes,
+    // e.g. "Wed, 30 Jun 2027 00:00:00 GMT"
+    pub legacy_sunset: Option<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::from(([127, 0, 0, 1], 3030)),
+            shutdown_timeout_secs: 30,
+            legacy_sunset: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MoneroConfig {
+    // Daemons must report this nettype and addresses must belong to it
+    pub network: Network,
+    // monerod endpoints, tried in order of measured latency
+    pub daemons: Vec<String>,
+    pub wallet: Option<String>,
+    pub health_check_interval_secs: u64,
+    // Number of daemons that must return identical results; 1 disables quorum mode
+    pub quorum: usize,
+    // Readiness fails while the daemon is further behind its target height
+    pub max_sync_lag_blocks: u64,
+    pub rpc: RpcPolicyConfig,
+}
+
+impl Default for MoneroConfig {
+    fn default() -> Self {
+        Self {
+            network: Network::Mainnet,
+            daemons: vec!["http://127.0.0.1:18081".to_string()],
+            wallet: Some("http://127.0.0.1:18083".to_string()),
+            health_check_interval_secs: 15,
+            quorum: 1,
+            max_sync_lag_blocks: 2,
+            rpc: RpcPolicyConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    // Proofs are kept in memory only when unset
+    pub data_dir: Option<PathBuf>,
+    pub flush_interval_secs: u64,
+    // Keep salted commitments instead of tx ids, tx keys and recipients
+    pub privacy: bool,
+    // Seal minted proofs this long after their attestation; never when unset
+    pub purge_after_mint_secs: Option<u64>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: None,
+            flush_interval_secs: 1,
+            privacy: false,
+            purge_after_mint_secs: None,
+        }
+    }
+}
+
+// How deposits convert into wFOID when attested, see `amount::WfoidConversion`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BridgeConfig {
+    pub wfoid_decimals: u8,
+    // A decimal string so the rate is exact, e.g. "1" or "0.25"
+    pub wfoid_per_xmr: String,
+    // The bridge's Monero address. Only deposits paying it are attested;
+    // nothing is while it is unset.
+    pub deposit_address: Option<String>,
+    // Its private view key, checked against the address so deposits never
+    // go to a wallet the bridge cannot see into
+    pub view_key: Option<String>,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            wfoid_decimals: 18,
+            wfoid_per_xmr: "1".to_string(),
+            deposit_address: None,
+            view_key: None,
+        }
+    }
+}
+
+// Deposit limits and fees, see `policy::Policy`. XMR amounts are decimal
+// strings; unset limits don't apply. Reloaded while the server runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyConfig {
+    pub min_deposit_xmr: Option<String>,
+    // Per transaction
+    pub max_deposit_xmr: Option<String>,
+    // Attested per UTC day, for each destination and for the whole bridge
+    pub daily_cap_per_dest_xmr: Option<String>,
+    pub daily_cap_xmr: Option<String>,
+    // Percentage fee in basis points, added to the flat fee
+    pub fee_bps: u32,
+    pub flat_fee_xmr: Option<String>,
+}
+
+// Threshold attestation with peer validators, see `coordination`.
+// `threshold` must match `AttestorRegistry.threshold`. Enabled by setting
+// `GOSSIP_SECRET`, shared by every node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CoordinationConfig {
+    // Signatures an attestation needs, this node's included
+    pub threshold: usize,
+    pub peers: Vec<PeerConfig>,
+    pub peer_timeout_secs: u64,
+}
+
+impl Default for CoordinationConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 1,
+            peers: Vec::new(),
+            peer_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PeerConfig {
+    // Base URL of the peer's server
+    pub url: String,
+    // The peer's attestor address; its signatures must recover to it
+    pub attestor: String,
+}
+
+// Hash-chained decision log, see `audit`. Defaults to
+// `<storage.data_dir>/audit.jsonl`; without either, nothing is recorded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    pub path: Option<PathBuf>,
+}
+
+// The chain BridgeRouter (`ROUTER`) is deployed on, read to reconcile
+// attestations with mints, see `reconcile`. Off while `rpc_url` is unset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FluentConfig {
+    pub rpc_url: Option<String>,
+    pub poll_interval_secs: u64,
+    // First block searched for `Minted` logs, e.g. the router's deployment block
+    pub start_block: u64,
+    // Blocks per `eth_getLogs` request
+    pub max_block_range: u64,
+    pub timeout_secs: u64,
+}
+
+impl Default for FluentConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: None,
+            poll_interval_secs: 30,
+            start_block: 0,
+            max_block_range: 2000,
+            timeout_secs: 10,
+        }
+    }
+}
+
+// How long a proof may stay in each status before the purge job deletes it,
+// counted from its last status change. Unset windows keep proofs forever.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    pub created_secs: Option<u64>,
+    pub validated_secs: Option<u64>,
+    pub invalidated_secs: Option<u64>,
+    pub attested_secs: Option<u64>,
+    pub minted_secs: Option<u64>,
+    pub purge_interval_secs: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            created_secs: None,
+            validated_secs: None,
+            invalidated_secs: None,
+            attested_secs: None,
+            minted_secs: None,
+            purge_interval_secs: 3600,
+        }
+    }
+}
+
+// How validated deposits are followed until their block is final, see
+// `finality`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FinalityConfig {
+    // Confirmations after which a deposit's block is no longer re-checked
+    pub depth: u64,
+    pub check_interval_secs: u64,
+    // What happens to a validated, not yet attested proof whose block was
+    // orphaned. Attested and minted proofs are always flagged.
+    pub on_orphan: OrphanAction,
+}
+
+impl Default for FinalityConfig {
+    fn default() -> Self {
+        Self {
+            depth: 10,
+            check_interval_secs: 60,
+            on_orphan: OrphanAction::Revert,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanAction {
+    // Back to `created`, so it has to be validated again
+    Revert,
+    // Keep the status, flag it `orphaned` and refuse to attest it
+    Flag,
+}
+
+// Where the attestor key comes from, see `signer`. `ROUTER` and `CHAIN_ID`
+// are still read from the environment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AttestorConfig {
+    pub key: KeyConfig,
+    // The key being rotated out; it keeps signing alongside `key` until
+    // `valid_until`
+    pub previous: Option<PreviousKeyConfig>,
+    // Lifetime of an attestation, the `expiry` of its LockProof
+    pub expiry_secs: u64,
+}
+
+impl Default for AttestorConfig {
+    fn default() -> Self {
+        Self {
+            key: KeyConfig::default(),
+            previous: None,
+            expiry_secs: attestation::DEFAULT_EXPIRY_SECS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case", deny_unknown_fields)]
+pub enum KeyConfig {
+    // Hex key in an environment variable
+    Env {
+        #[serde(default = "default_key_var")]
+        var: String,
+    },
+    // Hex key in a file readable only by its owner
+    File { path: PathBuf },
+    // Ethereum V3 keystore, unlocked with `password_file` or
+    // `$ATTESTOR_KEYSTORE_PASSWORD`
+    Keystore {
+        path: PathBuf,
+        #[serde(default)]
+        password_file: Option<PathBuf>,
+    },
+    // Signing service holding the key for `address`
+    Remote {
+        url: String,
+        address: String,
+        #[serde(default = "default_signer_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+fn default_key_var() -> String {
+    "ATTESTOR_PRIVKEY".to_string()
+}
+
+fn default_signer_timeout_secs() -> u64 {
+    10
+}
+
+impl Default for KeyConfig {
+    fn default() -> Self {
+        KeyConfig::Env { var: default_key_var() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreviousKeyConfig {
+    pub key: KeyConfig,
+    // Unix time the grace window ends
+    pub valid_until: u64,
+}
+
+// Timeouts, retries and circuit breaking for daemon and wallet RPC calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RpcPolicyConfig {
+    pub default_timeout_ms: u64,
+    // Per-method overrides, e.g. `get_transactions = 20000`
+    pub timeouts_ms: HashMap<String, u64>,
+    // Extra attempts for idempotent methods only
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+    pub retry_max_delay_ms: u64,
+    // Consecutive transport failures before an endpoint's breaker opens
+    pub breaker_failure_threshold: u32,
+    pub breaker_cooldown_secs: u64,
+}
+
+impl Default for RpcPolicyConfig {
+    fn default() -> Self {
+        Self {
+            default_timeout_ms: 10_000,
+            timeouts_ms: HashMap::new(),
+            max_retries: 2,
+            retry_base_delay_ms: 200,
+            retry_max_delay_ms: 5_000,
+            breaker_failure_threshold: 5,
+            breaker_cooldown_secs: 30,
+        }
+    }
+}
+
+impl RpcPolicyConfig {
+    pub fn timeout(&self, method: &str) -> Duration {
+        Duration::from_millis(*self.timeouts_ms.get(method).unwrap_or(&self.default_timeout_ms))
+    }
+}
+
+impl Config {
+    // Explicit path, then `$MONERO_CONFIG`, then `./monero.toml` if present
+    pub fn resolve_path(path: Option<&Path>) -> Option<PathBuf> {
+        path.map(Path::to_path_buf)
+            .or_else(|| std::env::var_os("MONERO_CONFIG").map(PathBuf::from))
+            .or_else(|| {
+                let default = PathBuf::from(DEFAULT_CONFIG_FILE);
+                default.exists().then_some(default)
+            })
+    }
+
+    // Defaults when `resolve_path` finds no file
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let config = match Self::resolve_path(path) {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("could not read {}", path.display()))?;
+                Self::parse(&text).with_context(|| format!("invalid config {}", path.display()))?
+            }
+            None => Self::default(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn audit_path(&self) -> Option<PathBuf> {
+        self.audit.path.clone().or_else(|| self.storage.data_dir.as_ref().map(|dir| dir.join(AUDIT_FILE)))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let monero = &self.monero;
+        if monero.daemons.is_empty() {
+            return Err(anyhow!("monero.daemons must list at least one daemon"));
+        }
+        if monero.quorum == 0 || monero.quorum > monero.daemons.len() {
+            return Err(anyhow!(
+                "monero.quorum must be between 1 and the number of daemons ({})",
+                monero.daemons.len()
+            ));
+        }
+        for url in monero.daemons.iter().chain(monero.wallet.iter()) {
+            reqwest::Url::parse(url).with_context(|| format!("invalid RPC url {}", url))?;
+        }
+        if let Some(sunset) = &self.server.legacy_sunset
+            && warp::http::HeaderValue::from_str(sunset).is_err()
+        {
+            return Err(anyhow!("server.legacy_sunset is not a valid header value: {:?}", sunset));
+        }
+        WfoidConversion::new(&self.bridge)?;
+        match (&self.bridge.deposit_address, &self.bridge.view_key) {
+            (Some(address), Some(view_key)) => {
+                let parsed = monero.network.check_address(address).context("invalid bridge.deposit_address")?;
+                if !scanner::is_view_key_of(view_key, &parsed)? {
+                    return Err(anyhow!("bridge.view_key is not the view key of bridge.deposit_address"));
+                }
+            }
+            (None, None) => {}
+            _ => return Err(anyhow!("bridge.deposit_address and bridge.view_key must be set together")),
+        }
+        Policy::new(&self.policy)?;
+        let coordination = &self.coordination;
+        if coordination.threshold == 0 || coordination.threshold > coordination.peers.len() + 1 {
+            return Err(anyhow!(
+                "coordination.threshold must be between 1 and the number of peers plus this node ({})",
+                coordination.peers.len() + 1
+            ));
+        }
+        for peer in &coordination.peers {
+            reqwest::Url::parse(&peer.url).with_context(|| format!("invalid peer url {}", peer.url))?;
+            attestation::parse_address(&peer.attestor)?;
+        }
+        let previous = self.attestor.previous.as_ref().map(|previous| &previous.key);
+        for key in std::iter::once(&self.attestor.key).chain(previous) {
+            if let KeyConfig::Remote { url, address, .. } = key {
+                reqwest::Url::parse(url).with_context(|| format!("invalid remote signer url {}", url))?;
+                attestation::parse_address(address)?;
+            }
+        }
+        if let Some(url) = &self.fluent.rpc_url {
+            reqwest::Url::parse(url).with_context(|| format!("invalid fluent.rpc_url {}", url))?;
+        }
+        if self.fluent.max_block_range == 0 {
+            return Err(anyhow!("fluent.max_block_range must be at least 1"));
+        }
+        if self.retention.purge_interval_secs == 0 {
+            return Err(anyhow!("retention.purge_interval_secs must be at least 1"));
+        }
+        if self.finality.depth == 0 || self.finality.check_interval_secs == 0 {
+            return Err(anyhow!("finality.depth and finality.check_interval_secs must be at least 1"));
+        }
+        if self.attestor.expiry_secs < 60 {
+            return Err(anyhow!("attestor.expiry_secs must be at least 60"));
+        }
+        if previous == Some(&self.attestor.key) {
+            return Err(anyhow!("attestor.previous.key is the same as attestor.key"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_file_falls_back_to_defaults() {
+        let config = Config::parse(
+            r#"
+            [monero]
+            daemons = ["http://node-a:18081", "http://node-b:18081", "http://node-c:18081"]
+            quorum = 2
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.monero.daemons.len(), 3);
+        assert_eq!(config.monero.wallet.as_deref(), Some("http://127.0.0.1:18083"));
+        assert_eq!(config.server.bind.port(), 3030);
+    }
+
+    #[test]
+    fn per_method_timeouts_override_the_default() {
+        let config = Config::parse(
+            "[monero.rpc]\ndefault_timeout_ms = 1000\ntimeouts_ms = { get_transactions = 5000 }\n",
+        )
+        .unwrap();
+        assert_eq!(config.monero.rpc.timeout("get_transactions"), Duration::from_secs(5));
+        assert_eq!(config.monero.rpc.timeout("get_info"), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn the_deposit_address_needs_its_own_view_key() {
+        let address = scanner::test_address(18, 7).encode();
+        let bridge = |view_key: Option<u64>| {
+            let view_key = view_key.map(|n| {
+                format!("view_key = \"{}\"\n", hex::encode(curve25519_dalek::scalar::Scalar::from(n).to_bytes()))
+            });
+            Config::parse(&format!("[bridge]\ndeposit_address = \"{}\"\n{}", address, view_key.unwrap_or_default()))
+                .unwrap()
+        };
+        // `test_address` derives the view key from seed + 1
+        bridge(Some(8)).validate().unwrap();
+        assert!(bridge(Some(7)).validate().is_err());
+        assert!(bridge(None).validate().is_err());
+        let mut stagenet = bridge(Some(8));
+        stagenet.monero.network = Network::Stagenet;
+        assert!(stagenet.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_impossible_quorum_and_unknown_keys() {
+        let config = Config::parse("[monero]\ndaemons = [\"http://a:18081\"]\nquorum = 2\n").unwrap();
+        assert!(config.validate().is_err());
+        assert!(Config::parse("[monero]\ndaemon = \"http://a:18081\"\n").is_err());
+    }
+}
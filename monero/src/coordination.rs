@@ -0,0 +1,517 @@
+// Threshold attestation across independent validator instances. The node
+// asked to attest signs the LockProof, then gossips it to its peers; each
+// peer verifies the deposit on its own daemons and returns its signature.
+// This node releases the attestation, with every signature, only once
+// `threshold` agree. The BridgeRouter mints only with signatures from
+// `AttestorRegistry.threshold` distinct operators, so no single key mints.
+use anyhow::{Result, anyhow, bail};
+use futures_util::StreamExt;
+use futures_util::stream::FuturesUnordered;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::api::{ErrorResponse, TxKey};
+use crate::attestation::{self, Attestor, LockProof, PartialSignature};
+use crate::audit::{self, AuditKind, AuditLog};
+use crate::config::CoordinationConfig;
+use crate::events::unix_now;
+use crate::policy::{DailyUsage, Policy};
+use crate::privacy::{self, ProofPreimage};
+use crate::proofs::EXPIRY_MARGIN_SECS;
+use crate::rpc::MoneroService;
+use crate::scanner;
+use crate::storage::ProofStore;
+use crate::webhooks::{SIGNATURE_HEADER, sign_payload, verify_payload};
+
+pub const GOSSIP_PATH: &str = "/v1/gossip/attestations";
+
+// Allowed difference between peers' clocks, for gossip signatures and expiries
+const MAX_CLOCK_SKEW_SECS: u64 = 300;
+
+// `POST /v1/gossip/attestations`: a LockProof to co-sign, and what a peer
+// needs to verify the deposit itself
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CosignRequest {
+    pub lock_proof: LockProof,
+    pub tx_id: String,
+    // Main key with any additional keys appended
+    pub tx_key: String,
+    pub recipient_address: String,
+}
+
+struct Peer {
+    url: String,
+    attestor: [u8; 20],
+}
+
+pub struct Coordinator {
+    peers: Vec<Peer>,
+    threshold: usize,
+    secret: String,
+    timeout: Duration,
+    http: reqwest::Client,
+    // LockProofs this node co-signed by lockId, until they expire; kept in
+    // memory only
+    cosigned: Mutex<HashMap<[u8; 32], LockProof>>,
+}
+
+impl Coordinator {
+    pub fn new(config: &CoordinationConfig, secret: String) -> Result<Self> {
+        if secret.is_empty() {
+            bail!("the gossip secret must not be empty");
+        }
+        let peers = config
+            .peers
+            .iter()
+            .map(|peer| {
+                Ok(Peer {
+                    url: peer.url.trim_end_matches('/').to_string(),
+                    attestor: attestation::parse_address(&peer.attestor)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            peers,
+            threshold: config.threshold,
+            secret,
+            timeout: Duration::from_secs(config.peer_timeout_secs),
+            http: reqwest::Client::new(),
+            cosigned: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // Coordination is on when `GOSSIP_SECRET` is set
+    pub fn from_env(config: &CoordinationConfig) -> Result<Option<Self>> {
+        match std::env::var("GOSSIP_SECRET") {
+            Ok(secret) => Self::new(config, secret).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    // Peers plus this node
+    pub fn nodes(&self) -> usize {
+        self.peers.len() + 1
+    }
+
+    // Whether a gossip request carries a valid `X-Foid-Signature`
+    pub fn authorizes(&self, header: Option<&str>, body: &[u8]) -> bool {
+        header.is_some_and(|header| verify_payload(&self.secret, header, body, unix_now(), MAX_CLOCK_SKEW_SECS))
+    }
+
+    // Claims `lock_proof.lock_id` unless another LockProof co-signed for it
+    // may still be minted, whose expiry is returned
+    fn claim(&self, lock_proof: &LockProof, now: u64) -> Result<(), u64> {
+        let mut cosigned = self.cosigned.lock().unwrap();
+        cosigned.retain(|_, signed| now <= signed.expiry.saturating_add(EXPIRY_MARGIN_SECS));
+        match cosigned.get(&lock_proof.lock_id) {
+            Some(signed) if signed != lock_proof => Err(signed.expiry),
+            _ => {
+                cosigned.insert(lock_proof.lock_id, lock_proof.clone());
+                Ok(())
+            }
+        }
+    }
+
+    fn release(&self, lock_id: &[u8; 32]) {
+        self.cosigned.lock().unwrap().remove(lock_id);
+    }
+
+    // Asks every peer at once and returns as soon as `threshold` distinct
+    // attestors, `own` included, have signed. Peers never see `own`: it only
+    // leaves this node inside an issued attestation.
+    pub async fn collect(
+        &self,
+        attestor: &Attestor,
+        own: PartialSignature,
+        request: &CosignRequest,
+    ) -> Result<Vec<PartialSignature>> {
+        let mut signatures = vec![own];
+        if signatures.len() >= self.threshold {
+            return Ok(signatures);
+        }
+        let body = serde_json::to_vec(request)?;
+        let mut pending: FuturesUnordered<_> = self.peers.iter().map(|peer| self.ask(peer, &body)).collect();
+        let mut failures = Vec::new();
+        while let Some((peer, result)) = pending.next().await {
+            let verified = result.and_then(|partial| {
+                let signer = attestor.recover_signer(&request.lock_proof, &partial.signature)?;
+                if signer != peer.attestor {
+                    bail!("signed by {} instead of {}", attestation::format_hex(&signer), attestation::format_hex(&peer.attestor));
+                }
+                Ok(PartialSignature {
+                    attestor: attestation::format_hex(&signer),
+                    signature: partial.signature,
+                })
+            });
+            match verified {
+                Ok(partial) if signatures.iter().any(|s| s.attestor == partial.attestor) => {}
+                Ok(partial) => {
+                    println!("🤝 {} co-signed lock {}", peer.url, attestation::format_hex(&request.lock_proof.lock_id));
+                    signatures.push(partial);
+                    if signatures.len() >= self.threshold {
+                        return Ok(signatures);
+                    }
+                }
+                Err(e) => failures.push(format!("{}: {:#}", peer.url, e)),
+            }
+        }
+        Err(anyhow!(
+            "only {} of {} required signatures ({})",
+            signatures.len(),
+            self.threshold,
+            failures.join("; ")
+        ))
+    }
+
+    async fn ask<'a>(&self, peer: &'a Peer, body: &[u8]) -> (&'a Peer, Result<PartialSignature>) {
+        let result = async {
+            let response = self
+                .http
+                .post(format!("{}{}", peer.url, GOSSIP_PATH))
+                .timeout(self.timeout)
+                .header("content-type", "application/json")
+                .header(SIGNATURE_HEADER, sign_payload(&self.secret, unix_now(), body))
+                .body(body.to_vec())
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                let message = match response.json::<ErrorResponse>().await {
+                    Ok(error) => format!("{}: {}", error.error, error.message),
+                    Err(_) => "no error body".to_string(),
+                };
+                bail!("{} ({})", status, message);
+            }
+            Ok(response.json().await?)
+        }
+        .await;
+        (peer, result)
+    }
+}
+
+#[derive(Debug)]
+pub enum CosignError {
+    NotConfigured,
+    Mismatch(String),
+    Policy(String),
+    Verification(anyhow::Error),
+    Signing(anyhow::Error),
+    Audit(anyhow::Error),
+}
+
+impl CosignError {
+    // Short title used as the `error` field of API responses
+    pub fn title(&self) -> &'static str {
+        match self {
+            CosignError::NotConfigured => "Coordination not enabled",
+            CosignError::Mismatch(_) => "LockProof does not match the deposit",
+            CosignError::Policy(_) => "Rejected by policy",
+            CosignError::Verification(_) => "Failed to verify deposit",
+            CosignError::Signing(_) => "Failed to sign attestation",
+            CosignError::Audit(_) => "Failed to record audit entry",
+        }
+    }
+}
+
+impl std::fmt::Display for CosignError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CosignError::NotConfigured => {
+                write!(f, "Set ATTESTOR_PRIVKEY, ROUTER, GOSSIP_SECRET and bridge.deposit_address to co-sign")
+            }
+            CosignError::Mismatch(reason) | CosignError::Policy(reason) => write!(f, "{}", reason),
+            CosignError::Verification(e) | CosignError::Signing(e) | CosignError::Audit(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+impl std::error::Error for CosignError {}
+
+// Verifies the deposit independently and signs `request.lock_proof` only if
+// it is exactly the LockProof this node would issue itself. Both outcomes
+// are audited.
+pub async fn cosign(
+    attestor: &Attestor,
+    policy: &Policy,
+    monero_service: &MoneroService,
+    storage: &ProofStore,
+    audit: &AuditLog,
+    request: &CosignRequest,
+) -> Result<PartialSignature, CosignError> {
+    // Private stores keep the tx key and recipient out of the audit log too
+    let mut data = if storage.is_private() {
+        let preimage = ProofPreimage {
+            tx_id: request.tx_id.clone(),
+            tx_key: request.tx_key.clone(),
+            recipient_address: request.recipient_address.clone(),
+        };
+        json!({ "request": {
+            "lock_proof": request.lock_proof,
+            "deposit": privacy::fingerprint(&preimage),
+        } })
+    } else {
+        json!({ "request": request })
+    };
+    let result = verify_and_sign(attestor, policy, monero_service, storage, request, &mut data).await;
+    let kind = match &result {
+        Ok(partial) => {
+            data["signer"] = audit::signer(attestor);
+            data["signature"] = json!(partial.signature);
+            AuditKind::Cosign
+        }
+        Err(e @ (CosignError::Mismatch(_) | CosignError::Policy(_))) => {
+            data["reason"] = json!(e.to_string());
+            AuditKind::CosignRefused
+        }
+        // Not a decision about the deposit
+        Err(_) => return result,
+    };
+    audit.record(kind, None, data).await.map_err(CosignError::Audit)?;
+    result
+}
+
+// Adds the daemon data and policy decision it relied on to `data`
+async fn verify_and_sign(
+    attestor: &Attestor,
+    policy: &Policy,
+    monero_service: &MoneroService,
+    storage: &ProofStore,
+    request: &CosignRequest,
+    data: &mut Value,
+) -> Result<PartialSignature, CosignError> {
+    let lock_proof = &request.lock_proof;
+    let now = unix_now();
+    let monero_tx = attestation::parse_hex32(&request.tx_id).map_err(|e| CosignError::Mismatch(format!("{:#}", e)))?;
+    if lock_proof.monero_tx != monero_tx || lock_proof.lock_id != attestation::lock_id(&monero_tx) {
+        return Err(CosignError::Mismatch("lock_id and monero_tx must derive from tx_id".to_string()));
+    }
+    if lock_proof.expiry <= now || lock_proof.expiry > now + attestor.expiry_secs + MAX_CLOCK_SKEW_SECS {
+        return Err(CosignError::Mismatch(format!("expiry {} is outside this node's window", lock_proof.expiry)));
+    }
+
+    let tx_key = TxKey::parse(&request.tx_key).map_err(|e| CosignError::Mismatch(e.to_string()))?;
+    // A peer on another network must not get this node's signature either
+    attestor
+        .network
+        .check_address(&request.recipient_address)
+        .map_err(|e| CosignError::Mismatch(e.to_string()))?;
+    // Nor one for a deposit to anyone but the bridge
+    let deposit_address = attestor.deposit_address.as_deref().ok_or(CosignError::NotConfigured)?;
+    if request.recipient_address != deposit_address {
+        return Err(CosignError::Mismatch("recipient_address is not the bridge's deposit address".to_string()));
+    }
+    let check = monero_service
+        .check_tx_key(&request.tx_id, &tx_key, &request.recipient_address)
+        .await
+        .map_err(CosignError::Verification)?;
+    data["rpc"] = json!({ "get_transactions": check.evidence });
+    let outputs = check.outputs;
+    if outputs.is_empty() {
+        return Err(CosignError::Mismatch("the tx key finds no output paying the recipient".to_string()));
+    }
+    let received = scanner::total(&outputs).map_err(CosignError::Verification)?;
+
+    let usage = DailyUsage::of(storage.read().await.values(), &lock_proof.dest, now);
+    let decision = policy.evaluate(received, usage, now);
+    data["policy"] = json!(decision);
+    if let Some(reason) = decision.reason {
+        return Err(CosignError::Policy(reason));
+    }
+    let converted = attestor
+        .conversion
+        .convert(decision.deposit, decision.fee)
+        .map_err(|e| CosignError::Mismatch(format!("{:#}", e)))?;
+    if converted.wfoid != lock_proof.amount {
+        return Err(CosignError::Mismatch(format!(
+            "amount {} differs from the {} this node computes",
+            lock_proof.amount, converted.wfoid
+        )));
+    }
+
+    // Like the issuing node, never sign a second LockProof for a live lockId
+    let coordinator = attestor.coordinator.as_ref().ok_or(CosignError::NotConfigured)?;
+    coordinator.claim(lock_proof, now).map_err(|expiry| {
+        CosignError::Mismatch(format!("lock_id already has a live attestation until {}", expiry))
+    })?;
+    let signature = match attestor.sign(lock_proof).await {
+        Ok(signature) => signature,
+        Err(e) => {
+            coordinator.release(&lock_proof.lock_id);
+            return Err(CosignError::Signing(e));
+        }
+    };
+    Ok(PartialSignature {
+        attestor: attestation::format_hex(&attestor.address()),
+        signature,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::{Piconero, WfoidConversion};
+    use crate::api::{AttestRequest, ProofRequest, ValidationRequest};
+    use crate::client::{self, Client};
+    use crate::config::{BridgeConfig, MoneroConfig, PeerConfig};
+    use crate::server::routes;
+    use crate::testing::{ADDRESS, StubDaemon, server_state};
+    use std::sync::Arc;
+
+    const SECRET: &str = "gossip";
+    const ROUTER: &str = "0x1111111111111111111111111111111111111111";
+
+    // A validator instance on localhost; returns its URL and attestor address
+    fn spawn_node(
+        daemon: &str,
+        key: u8,
+        conversion: WfoidConversion,
+        coordination: CoordinationConfig,
+        audit: AuditLog,
+    ) -> (String, String) {
+        let coordinator = Coordinator::new(&coordination, SECRET.to_string()).unwrap();
+        let attestor = Attestor::new(&format!("{:02x}", key).repeat(32), ROUTER, 20994)
+            .unwrap()
+            .with_deposit_address(Some(ADDRESS.to_string()))
+            .with_conversion(conversion)
+            .with_coordinator(Some(coordinator));
+        let address = attestation::format_hex(&attestor.address());
+        let mut state = server_state(daemon.to_string());
+        state.attestor = Some(Arc::new(attestor));
+        state.audit = Arc::new(audit);
+        let (addr, server) = warp::serve(routes(state)).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        (format!("http://{}", addr), address)
+    }
+
+    #[tokio::test]
+    async fn attestations_wait_for_the_threshold_of_agreeing_peers() {
+        let daemon = StubDaemon::default().spawn();
+        let solo = CoordinationConfig::default();
+        let (honest, honest_attestor) =
+            spawn_node(&daemon, 0x2b, WfoidConversion::default(), solo.clone(), AuditLog::disabled());
+        // A peer with another rate computes a different amount and refuses
+        let other_rate = WfoidConversion::new(&BridgeConfig {
+            wfoid_per_xmr: "2".to_string(),
+            ..BridgeConfig::default()
+        })
+        .unwrap();
+        let (dissenting, dissenting_attestor) = spawn_node(&daemon, 0x3c, other_rate, solo, AuditLog::disabled());
+        let peers = vec![
+            PeerConfig { url: honest, attestor: honest_attestor.clone() },
+            PeerConfig { url: dissenting, attestor: dissenting_attestor },
+        ];
+
+        let dir = tempfile::tempdir().unwrap();
+        let attest = |threshold: usize| {
+            let coordination = CoordinationConfig { threshold, peers: peers.clone(), ..CoordinationConfig::default() };
+            let audit = AuditLog::open(&dir.path().join(format!("audit-{}.jsonl", threshold))).unwrap();
+            let (url, _) = spawn_node(&daemon, 0x1a, WfoidConversion::default(), coordination, audit);
+            async move {
+                let client = Client::new(url);
+                let tx_id = "ab".repeat(32);
+                let proof = client
+                    .generate_proof(&ProofRequest { tx_id: tx_id.clone(), recipient_address: ADDRESS.to_string() })
+                    .await
+                    .unwrap();
+                client
+                    .validate(&ValidationRequest {
+                        tx_id,
+                        tx_key: proof.tx_key.clone(),
+                        recipient_address: ADDRESS.to_string(),
+                        additional_tx_keys: Vec::new(),
+                    })
+                    .await
+                    .unwrap();
+                let dest = "0x0000000000000000000000000000000000000002".to_string();
+                let preimage = ProofPreimage { tx_id: "ab".repeat(32), tx_key: proof.tx_key, recipient_address: ADDRESS.to_string() };
+                client.attest(&proof.proof_id, &AttestRequest { dest, preimage: Some(preimage) }).await
+            }
+        };
+
+        let attested = attest(2).await.unwrap().attestation;
+        let signers: Vec<&str> = attested.signatures.iter().map(|s| s.attestor.as_str()).collect();
+        assert_eq!(signers.len(), 2);
+        assert_eq!(signers[1], honest_attestor);
+
+        let error = attest(3).await.unwrap_err();
+        assert_eq!(client::status_of(&error), Some(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+        assert!(error.to_string().contains("differs"), "{}", error);
+        // The refusal keeps the lockId and why, never this node's signature
+        let log = std::fs::read_to_string(dir.path().join("audit-3.jsonl")).unwrap();
+        let refused: audit::AuditEntry = serde_json::from_str(log.lines().last().unwrap()).unwrap();
+        assert_eq!(refused.kind, AuditKind::AttestationRefused);
+        let mut fields: Vec<&str> = refused.data.as_object().unwrap().keys().map(String::as_str).collect();
+        fields.sort();
+        assert_eq!(fields, ["lock_id", "reason"]);
+    }
+
+    #[tokio::test]
+    async fn deposits_to_other_addresses_are_never_cosigned() {
+        // The tx key really does pay `other`, and the amount is right
+        let other = scanner::test_address(18, 7).encode();
+        let daemon = StubDaemon { recipient: other.clone(), ..StubDaemon::default() }.spawn();
+        let monero_service = MoneroService::new(&MoneroConfig {
+            daemons: vec![daemon.clone()],
+            wallet: Some(daemon),
+            ..MoneroConfig::default()
+        })
+        .unwrap();
+        let coordinator = Coordinator::new(&CoordinationConfig::default(), SECRET.to_string()).unwrap();
+        let attestor = Attestor::new(&"2b".repeat(32), ROUTER, 20994)
+            .unwrap()
+            .with_deposit_address(Some(ADDRESS.to_string()))
+            .with_coordinator(Some(coordinator));
+        let amount = WfoidConversion::default().convert(Piconero(1_000_000), Piconero::ZERO).unwrap().wfoid;
+        let request = CosignRequest {
+            lock_proof: LockProof::new([0xab; 32], [2; 20], amount, unix_now() + 600),
+            tx_id: "ab".repeat(32),
+            tx_key: "11".repeat(32),
+            recipient_address: other,
+        };
+
+        let refused = cosign(&attestor, &Policy::default(), &monero_service, &ProofStore::in_memory(), &AuditLog::disabled(), &request).await;
+        match refused {
+            Err(CosignError::Mismatch(reason)) => assert!(reason.contains("deposit address"), "{}", reason),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn gossip_requires_the_shared_secret() {
+        let daemon = StubDaemon::default().spawn();
+        let (url, _) =
+            spawn_node(&daemon, 0x2b, WfoidConversion::default(), CoordinationConfig::default(), AuditLog::disabled());
+        let body = b"{}".to_vec();
+        let response = reqwest::Client::new()
+            .post(format!("{}{}", url, GOSSIP_PATH))
+            .header(SIGNATURE_HEADER, sign_payload("wrong", unix_now(), &body))
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn a_live_lock_id_is_never_cosigned_twice() {
+        let coordinator = Coordinator::new(&CoordinationConfig::default(), SECRET.to_string()).unwrap();
+        let lock_proof = LockProof::new([1; 32], [2; 20], 500, 1_000);
+        let other = LockProof { amount: 600, ..lock_proof.clone() };
+
+        coordinator.claim(&lock_proof, 900).unwrap();
+        // Retries of the same LockProof are fine, another one is not
+        coordinator.claim(&lock_proof, 900).unwrap();
+        assert_eq!(coordinator.claim(&other, 900), Err(1_000));
+        // Until the first one has expired
+        coordinator.claim(&other, 1_000 + EXPIRY_MARGIN_SECS + 1).unwrap();
+    }
+}
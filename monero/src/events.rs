@@ -0,0 +1,264 @@
+use futures_util::{Stream, StreamExt, future::ready, stream};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::amount::Piconero;
+use crate::api::TransactionProof;
+
+// Lifecycle transitions a proof can go through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ProofEventKind {
+    #[serde(rename = "proof.created")]
+    Created,
+    #[serde(rename = "proof.validated")]
+    Validated,
+    #[serde(rename = "proof.attested")]
+    Attested,
+    #[serde(rename = "proof.invalidated")]
+    Invalidated,
+    #[serde(rename = "proof.minted")]
+    Minted,
+    // The reconciler or the finality watcher added a `MintFlag`
+    #[serde(rename = "proof.flagged")]
+    Flagged,
+    // Only the proof id is left, see `retention`
+    #[serde(rename = "proof.deleted")]
+    Deleted,
+    // The deposit's block was orphaned and the proof is `created` again
+    #[serde(rename = "proof.reorged")]
+    Reorged,
+}
+
+impl ProofEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofEventKind::Created => "proof.created",
+            ProofEventKind::Validated => "proof.validated",
+            ProofEventKind::Attested => "proof.attested",
+            ProofEventKind::Invalidated => "proof.invalidated",
+            ProofEventKind::Minted => "proof.minted",
+            ProofEventKind::Flagged => "proof.flagged",
+            ProofEventKind::Deleted => "proof.deleted",
+            ProofEventKind::Reorged => "proof.reorged",
+        }
+    }
+}
+
+// Proof snapshot carried by events. The tx key is deliberately left out.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProofEventData {
+    pub proof_id: String,
+    pub tx_id: String,
+    pub recipient_address: String,
+    pub amount: Option<Piconero>,
+    pub status: crate::api::ProofStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProofEvent {
+    pub id: String,
+    // Monotonic position in the bus, assigned on publish; used to resume streams
+    #[serde(default)]
+    pub seq: u64,
+    #[serde(rename = "type")]
+    pub kind: ProofEventKind,
+    pub created_at: u64,
+    pub data: ProofEventData,
+}
+
+impl ProofEvent {
+    pub fn new(kind: ProofEventKind, proof: &TransactionProof) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            seq: 0,
+            kind,
+            created_at: unix_now(),
+            data: ProofEventData {
+                proof_id: proof.proof_id.clone(),
+                tx_id: proof.tx_id.clone(),
+                recipient_address: proof.recipient_address.clone(),
+                amount: proof.amount,
+                status: proof.status,
+            },
+        }
+    }
+}
+
+// Subscription filter, e.g. `/events?recipient=...&proof_id=...`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventFilter {
+    pub recipient: Option<String>,
+    pub proof_id: Option<String>,
+    pub last_event_id: Option<u64>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &ProofEvent) -> bool {
+        self.recipient.as_ref().is_none_or(|r| *r == event.data.recipient_address)
+            && self.proof_id.as_ref().is_none_or(|p| *p == event.data.proof_id)
+    }
+}
+
+// Events replayed to a resuming subscriber, followed by the live feed
+pub struct Subscription {
+    pub backlog: Vec<ProofEvent>,
+    // The requested position fell out of the history buffer, some events were missed
+    pub truncated: bool,
+    pub receiver: broadcast::Receiver<ProofEvent>,
+}
+
+struct History {
+    next_seq: u64,
+    events: VecDeque<ProofEvent>,
+}
+
+// Fan-out of proof lifecycle events to in-process subscribers (webhooks,
+// SSE/WebSocket clients). The most recent events are kept for resumption.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ProofEvent>,
+    history: Arc<Mutex<History>>,
+    capacity: usize,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            history: Arc::new(Mutex::new(History {
+                next_seq: 1,
+                events: VecDeque::with_capacity(capacity),
+            })),
+            capacity,
+        }
+    }
+
+    pub fn publish(&self, mut event: ProofEvent) {
+        println!("📣 {:?} event for proof {}", event.kind, event.data.proof_id);
+        // Sequence, record and send under one lock so history and live feed agree
+        let mut history = self.history.lock().unwrap();
+        event.seq = history.next_seq;
+        history.next_seq += 1;
+        if history.events.len() == self.capacity {
+            history.events.pop_front();
+        }
+        history.events.push_back(event.clone());
+        // No subscribers is not an error, the event is simply dropped
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ProofEvent> {
+        self.sender.subscribe()
+    }
+
+    // Subscribes and returns every retained event after `last_event_id`
+    pub fn subscribe_from(&self, last_event_id: Option<u64>) -> Subscription {
+        let history = self.history.lock().unwrap();
+        let receiver = self.sender.subscribe();
+        let Some(last) = last_event_id else {
+            return Subscription {
+                backlog: Vec::new(),
+                truncated: false,
+                receiver,
+            };
+        };
+        let oldest = history.events.front().map_or(history.next_seq, |e| e.seq);
+        Subscription {
+            backlog: history.events.iter().filter(|e| e.seq > last).cloned().collect(),
+            truncated: last + 1 < oldest,
+            receiver,
+        }
+    }
+
+    // Backlog then live events matching `filter`. The stream ends if the
+    // subscriber lags behind the bus; clients reconnect with their last id.
+    pub fn stream(&self, filter: EventFilter) -> (bool, impl Stream<Item = ProofEvent> + Send + use<>) {
+        let subscription = self.subscribe_from(filter.last_event_id);
+        let live = BroadcastStream::new(subscription.receiver)
+            .take_while(|received| ready(received.is_ok()))
+            .filter_map(|received| ready(received.ok()));
+        let events = stream::iter(subscription.backlog)
+            .chain(live)
+            .filter(move |event| ready(filter.matches(event)));
+        (subscription.truncated, events)
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ProofStatus;
+
+    fn proof(proof_id: &str, recipient: &str) -> TransactionProof {
+        TransactionProof {
+            tx_id: "aa".repeat(32),
+            tx_key: "bb".repeat(32),
+            recipient_address: recipient.to_string(),
+            amount: Some(Piconero(1)),
+            proof_id: proof_id.to_string(),
+            status: ProofStatus::Created,
+            updated_at: 0,
+            attestation: None,
+            policy: None,
+            mint: None,
+            flags: Vec::new(),
+            commitment: None,
+            block: None,
+        }
+    }
+
+    #[test]
+    fn resume_replays_only_newer_events() {
+        let bus = EventBus::new(3);
+        for id in ["a", "b", "c", "d"] {
+            bus.publish(ProofEvent::new(ProofEventKind::Created, &proof(id, "r")));
+        }
+
+        let resumed = bus.subscribe_from(Some(2));
+        let seqs: Vec<u64> = resumed.backlog.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![3, 4]);
+        assert!(!resumed.truncated);
+
+        // Event 1 fell out of the three-event history
+        let stale = bus.subscribe_from(Some(0));
+        assert_eq!(stale.backlog.len(), 3);
+        assert!(stale.truncated);
+    }
+
+    #[tokio::test]
+    async fn stream_filters_backlog_and_live_events() {
+        let bus = EventBus::new(16);
+        bus.publish(ProofEvent::new(ProofEventKind::Created, &proof("a", "alice")));
+        bus.publish(ProofEvent::new(ProofEventKind::Created, &proof("b", "bob")));
+
+        let (_, events) = bus.stream(EventFilter {
+            recipient: Some("alice".to_string()),
+            proof_id: None,
+            last_event_id: Some(0),
+        });
+        bus.publish(ProofEvent::new(ProofEventKind::Validated, &proof("c", "bob")));
+        bus.publish(ProofEvent::new(ProofEventKind::Validated, &proof("a", "alice")));
+
+        let received: Vec<(String, u64)> = events
+            .take(2)
+            .map(|e| (e.data.proof_id, e.seq))
+            .collect()
+            .await;
+        assert_eq!(received, vec![("a".to_string(), 1), ("a".to_string(), 4)]);
+    }
+}
@@ -0,0 +1,370 @@
+// Export and import of proofs and audit entries as JSON Lines, CSV or a JSON
+// array, for accounting and for moving a store between servers. Records keep
+// their `TransactionProof` / `AuditEntry` serde shape; in CSV each top-level
+// field is a column and nested ones are JSON text.
+use anyhow::{Context, Result, anyhow, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Read};
+use std::path::Path;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api::TransactionProof;
+use crate::audit::AuditEntry;
+use crate::privacy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    // One JSON object per line
+    #[default]
+    Jsonl,
+    Csv,
+    // A single JSON array, as written by earlier `storage export`s
+    Json,
+}
+
+impl Format {
+    // From the file extension, JSON for anything unknown
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("jsonl" | "ndjson") => Format::Jsonl,
+            Some("csv") => Format::Csv,
+            _ => Format::Json,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Jsonl => "application/x-ndjson",
+            Format::Csv => "text/csv",
+            Format::Json => "application/json",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Jsonl => "jsonl",
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+}
+
+// How a CSV column holds its field
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    // A string; empty cells are empty strings
+    Text,
+    // An optional string; empty cells leave it out
+    Optional,
+    // Numbers, objects and arrays as JSON text; empty cells leave it out
+    Json,
+}
+
+pub struct Column {
+    pub name: &'static str,
+    pub cell: Cell,
+}
+
+const fn text(name: &'static str) -> Column {
+    Column { name, cell: Cell::Text }
+}
+
+const fn optional(name: &'static str) -> Column {
+    Column { name, cell: Cell::Optional }
+}
+
+const fn json(name: &'static str) -> Column {
+    Column { name, cell: Cell::Json }
+}
+
+pub trait Record: Serialize + DeserializeOwned {
+    const COLUMNS: &'static [Column];
+}
+
+impl Record for TransactionProof {
+    const COLUMNS: &'static [Column] = &[
+        text("proof_id"),
+        text("status"),
+        json("updated_at"),
+        text("tx_id"),
+        text("tx_key"),
+        text("recipient_address"),
+        json("amount"),
+        json("attestation"),
+        json("policy"),
+        json("mint"),
+        json("flags"),
+        json("commitment"),
+        json("block"),
+    ];
+}
+
+impl Record for AuditEntry {
+    const COLUMNS: &'static [Column] = &[
+        json("seq"),
+        json("timestamp"),
+        text("kind"),
+        optional("proof_id"),
+        json("data"),
+        text("prev_hash"),
+        text("hash"),
+    ];
+}
+
+// Serializes records one at a time, so exports can be streamed
+pub struct Encoder {
+    format: Format,
+    records: usize,
+}
+
+impl Encoder {
+    pub fn new(format: Format) -> Self {
+        Self { format, records: 0 }
+    }
+
+    // Written before the first record
+    pub fn begin<R: Record>(&self) -> Result<Vec<u8>> {
+        match self.format {
+            Format::Jsonl => Ok(Vec::new()),
+            Format::Csv => csv_line(R::COLUMNS.iter().map(|column| column.name)),
+            Format::Json => Ok(b"[".to_vec()),
+        }
+    }
+
+    pub fn record<R: Record>(&mut self, record: &R) -> Result<Vec<u8>> {
+        self.records += 1;
+        match self.format {
+            Format::Jsonl => {
+                let mut line = serde_json::to_vec(record)?;
+                line.push(b'\n');
+                Ok(line)
+            }
+            Format::Csv => {
+                let Value::Object(mut fields) = serde_json::to_value(record)? else {
+                    bail!("records serialize to JSON objects");
+                };
+                let cells: Vec<String> = R::COLUMNS
+                    .iter()
+                    .map(|column| match fields.remove(column.name) {
+                        None => String::new(),
+                        Some(Value::String(text)) => text,
+                        Some(value) => value.to_string(),
+                    })
+                    .collect();
+                csv_line(cells.iter().map(String::as_str))
+            }
+            Format::Json => {
+                let separator: &[u8] = if self.records == 1 { b"\n" } else { b",\n" };
+                let mut bytes = separator.to_vec();
+                bytes.extend(serde_json::to_vec_pretty(record)?);
+                Ok(bytes)
+            }
+        }
+    }
+
+    // Written after the last record
+    pub fn finish(&self) -> Vec<u8> {
+        match self.format {
+            Format::Json if self.records == 0 => b"]\n".to_vec(),
+            Format::Json => b"\n]\n".to_vec(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn csv_line<'a>(cells: impl IntoIterator<Item = &'a str>) -> Result<Vec<u8>> {
+    let mut writer = csv::WriterBuilder::new().from_writer(Vec::new());
+    writer.write_record(cells)?;
+    writer.into_inner().map_err(|e| anyhow!("{}", e.error()))
+}
+
+// Every record of `records`, encoded
+pub fn encode_all<R: Record>(records: &[R], format: Format) -> Result<Vec<u8>> {
+    let mut encoder = Encoder::new(format);
+    let mut bytes = encoder.begin::<R>()?;
+    for record in records {
+        bytes.extend(encoder.record(record)?);
+    }
+    bytes.extend(encoder.finish());
+    Ok(bytes)
+}
+
+// Parses what `Encoder` wrote. CSV columns may come in any order and unknown
+// ones are refused; errors name the line or row.
+pub fn decode<R: Record>(reader: impl BufRead, format: Format) -> Result<Vec<R>> {
+    match format {
+        Format::Json => serde_json::from_reader(reader).context("not a JSON array of records"),
+        Format::Jsonl => {
+            let mut records = Vec::new();
+            for (index, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                records.push(serde_json::from_str(&line).with_context(|| format!("line {}", index + 1))?);
+            }
+            Ok(records)
+        }
+        Format::Csv => decode_csv(reader),
+    }
+}
+
+fn decode_csv<R: Record>(reader: impl Read) -> Result<Vec<R>> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let columns: Vec<&Column> = headers
+        .iter()
+        .map(|header| {
+            R::COLUMNS
+                .iter()
+                .find(|column| column.name == header)
+                .ok_or_else(|| anyhow!("unknown column {:?}", header))
+        })
+        .collect::<Result<_>>()?;
+    let mut records = Vec::new();
+    for (index, row) in reader.records().enumerate() {
+        let row = row?;
+        let record = || -> Result<R> {
+            let mut fields = Map::new();
+            for (column, cell) in columns.iter().zip(row.iter()) {
+                // Empty cells take the field's default
+                if cell.is_empty() && column.cell != Cell::Text {
+                    continue;
+                }
+                let value = match column.cell {
+                    Cell::Json => {
+                        serde_json::from_str(cell).with_context(|| format!("{} is not JSON", column.name))?
+                    }
+                    _ => Value::String(cell.to_string()),
+                };
+                fields.insert(column.name.to_string(), value);
+            }
+            Ok(serde_json::from_value(Value::Object(fields))?)
+        };
+        // Row 1 is the header
+        records.push(record().with_context(|| format!("row {}", index + 2))?);
+    }
+    Ok(records)
+}
+
+// `/export/...?format=jsonl`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    // Default: jsonl
+    pub format: Option<Format>,
+}
+
+// `/import/...?format=csv&overwrite=true`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    // Default: jsonl
+    pub format: Option<Format>,
+    // Replace proofs that already exist
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+// How many records an import added and how many it left alone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+pub struct ImportResponse {
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+// Adds `proofs` to `store`. Existing proof ids are skipped unless
+// `overwrite`, so importing the same file twice changes nothing. A private
+// store conceals imported deposit data.
+pub fn import_proofs(
+    store: &mut HashMap<String, TransactionProof>,
+    proofs: Vec<TransactionProof>,
+    overwrite: bool,
+    private: bool,
+) -> Result<ImportResponse> {
+    let mut response = ImportResponse::default();
+    for mut proof in proofs {
+        if !overwrite && store.contains_key(&proof.proof_id) {
+            response.skipped += 1;
+            continue;
+        }
+        if private {
+            privacy::conceal(&mut proof).with_context(|| format!("proof {}", proof.proof_id))?;
+        }
+        store.insert(proof.proof_id.clone(), proof);
+        response.imported += 1;
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Piconero;
+    use crate::api::ProofStatus;
+    use crate::attestation::{Attestation, LockProof};
+
+    fn proof(id: &str, amount: u64) -> TransactionProof {
+        TransactionProof {
+            tx_id: "aa".repeat(32),
+            tx_key: "bb".repeat(32),
+            recipient_address: "addr".to_string(),
+            amount: Some(Piconero(amount)),
+            proof_id: id.to_string(),
+            status: ProofStatus::Validated,
+            updated_at: 0,
+            attestation: None,
+            policy: None,
+            mint: None,
+            flags: Vec::new(),
+            commitment: None,
+            block: None,
+        }
+    }
+
+    #[test]
+    fn every_format_round_trips_proofs() {
+        let mut attested = proof("b", 2);
+        attested.status = ProofStatus::Attested;
+        attested.attestation = Some(Attestation {
+            lock_proof: LockProof::new([0xaa; 32], [0x02; 20], 2_000_000, 1_700_000_000),
+            signature: "0x01".to_string(),
+            attestor: "0x03".to_string(),
+            issued_at: 1_699_999_000,
+            conversion: None,
+            signatures: Vec::new(),
+            previous_signature: None,
+            network: None,
+        });
+        let proofs = vec![proof("a", 1), attested];
+        for format in [Format::Jsonl, Format::Csv, Format::Json] {
+            let bytes = encode_all(&proofs, format).unwrap();
+            let decoded: Vec<TransactionProof> = decode(bytes.as_slice(), format).unwrap();
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&proofs).unwrap(), "{:?}", format);
+        }
+        let empty = encode_all::<TransactionProof>(&[], Format::Json).unwrap();
+        assert!(decode::<TransactionProof>(empty.as_slice(), Format::Json).unwrap().is_empty());
+
+        // Spreadsheets reorder and drop columns
+        let csv = "amount,proof_id,tx_id,tx_key,recipient_address\n7,c,cc,dd,addr\n";
+        let decoded: Vec<TransactionProof> = decode(csv.as_bytes(), Format::Csv).unwrap();
+        assert_eq!((decoded[0].amount, decoded[0].status), (Some(Piconero(7)), ProofStatus::Created));
+        let error = decode::<TransactionProof>("proof_id,amount\nc,seven\n".as_bytes(), Format::Csv).unwrap_err();
+        assert!(format!("{:#}", error).contains("row 2"), "{:#}", error);
+    }
+
+    #[test]
+    fn import_keeps_existing_proofs_unless_overwriting() {
+        let mut store = HashMap::from([("a".to_string(), proof("a", 1))]);
+        let imported = import_proofs(&mut store, vec![proof("a", 2), proof("b", 3)], false, false).unwrap();
+        assert_eq!((imported.imported, imported.skipped), (1, 1));
+        assert_eq!(store["a"].amount, Some(Piconero(1)));
+        let imported = import_proofs(&mut store, vec![proof("a", 2)], true, false).unwrap();
+        assert_eq!((imported.imported, imported.skipped), (1, 0));
+        assert_eq!(store["a"].amount, Some(Piconero(2)));
+    }
+}
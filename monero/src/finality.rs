@@ -0,0 +1,287 @@
+// Follows the block each validated deposit was mined in until it is
+// `finality.depth` blocks deep. A deposit whose block leaves the main chain
+// is either found again in its replacement, or, if the transaction went back
+// to the pool or vanished, treated as orphaned: a proof that was not attested
+// yet is reverted or flagged, an attested or minted one is always flagged.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::api::{ProofStatus, TransactionProof};
+use crate::audit::{AuditKind, AuditLog};
+use crate::config::{FinalityConfig, OrphanAction};
+use crate::events::{EventBus, ProofEvent, ProofEventKind};
+use crate::reconcile::MintFlag;
+use crate::rpc::MoneroService;
+use crate::storage::ProofStorage;
+
+// The block a deposit was mined in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct BlockRef {
+    pub height: u64,
+    pub hash: String,
+    // `finality.depth` blocks deep; no longer checked
+    #[serde(default)]
+    pub finalized: bool,
+}
+
+// Where a watched deposit is now
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    // Still in its recorded block
+    Kept,
+    // Mined in another block than the recorded one, or for the first time
+    Moved(BlockRef),
+    // Its block left the main chain and the transaction is in no other
+    Orphaned,
+    // Not mined yet and no block recorded
+    Pending,
+}
+
+// What one pass changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Checked {
+    pub finalized: usize,
+    pub moved: usize,
+    pub orphaned: usize,
+}
+
+// Whether `proof`'s deposit still needs checking. Committed proofs without a
+// block have no tx id to look up.
+pub fn watched(proof: &TransactionProof) -> bool {
+    let status = matches!(proof.status, ProofStatus::Validated | ProofStatus::Attested | ProofStatus::Minted);
+    match &proof.block {
+        Some(block) => status && !block.finalized,
+        None => status && !proof.tx_id.is_empty(),
+    }
+}
+
+#[derive(Clone)]
+pub struct Watcher {
+    monero_service: Arc<MoneroService>,
+    storage: ProofStorage,
+    events: EventBus,
+    audit: Arc<AuditLog>,
+    config: FinalityConfig,
+}
+
+impl Watcher {
+    pub fn new(
+        config: &FinalityConfig,
+        monero_service: Arc<MoneroService>,
+        storage: ProofStorage,
+        events: EventBus,
+        audit: Arc<AuditLog>,
+    ) -> Self {
+        Self {
+            monero_service,
+            storage,
+            events,
+            audit,
+            config: config.clone(),
+        }
+    }
+
+    pub async fn run(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match self.check().await {
+                Ok(checked) if checked.moved + checked.orphaned > 0 => println!(
+                    "⛓️  Deposit blocks checked: {} moved, {} orphaned, {} final",
+                    checked.moved, checked.orphaned, checked.finalized
+                ),
+                Ok(_) => {}
+                Err(e) => println!("⚠️  Deposit block check failed: {:#}", e),
+            }
+        }
+    }
+
+    pub async fn check(&self) -> Result<Checked> {
+        let tip = self.monero_service.get_info().await?.height;
+        let pending: Vec<(String, String, Option<BlockRef>)> = self
+            .storage
+            .read()
+            .await
+            .values()
+            .filter(|proof| watched(proof))
+            .map(|proof| (proof.proof_id.clone(), proof.tx_id.clone(), proof.block.clone()))
+            .collect();
+        let mut checked = Checked::default();
+        for (proof_id, tx_id, block) in pending {
+            let location = self.locate(&tx_id, block.as_ref(), tip).await?;
+            let mut storage = self.storage.write().await;
+            // Validated again or deleted meanwhile
+            let Some(proof) = storage.get_mut(&proof_id).filter(|proof| proof.block == block && watched(proof)) else {
+                continue;
+            };
+            match location {
+                Location::Kept | Location::Pending => {}
+                Location::Moved(moved) => {
+                    match &block {
+                        Some(old) => println!(
+                            "🔀 Deposit of proof {} moved from block {} to {}",
+                            proof_id, old.height, moved.height
+                        ),
+                        None => println!("⛏️  Deposit of proof {} mined in block {}", proof_id, moved.height),
+                    }
+                    proof.block = Some(moved);
+                    proof.flags.retain(|flag| *flag != MintFlag::Orphaned);
+                    checked.moved += 1;
+                }
+                Location::Orphaned => {
+                    self.orphan(proof).await?;
+                    checked.orphaned += 1;
+                    continue;
+                }
+            }
+            if let Some(block) = proof.block.as_mut()
+                && tip.saturating_sub(block.height) >= self.config.depth
+            {
+                block.finalized = true;
+                checked.finalized += 1;
+            }
+        }
+        Ok(checked)
+    }
+
+    async fn locate(&self, tx_id: &str, block: Option<&BlockRef>, tip: u64) -> Result<Location> {
+        if let Some(block) = block
+            && block.height < tip
+            && self.monero_service.block_hash(block.height).await? == block.hash
+        {
+            return Ok(Location::Kept);
+        }
+        let height = match tx_id {
+            "" => None,
+            tx_id => self.monero_service.tx_height(tx_id).await?,
+        };
+        Ok(match (height, block) {
+            (Some(height), _) => Location::Moved(BlockRef {
+                height,
+                hash: self.monero_service.block_hash(height).await?,
+                finalized: false,
+            }),
+            (None, Some(_)) => Location::Orphaned,
+            (None, None) => Location::Pending,
+        })
+    }
+
+    // Reverts or flags `proof`, audited first
+    async fn orphan(&self, proof: &mut TransactionProof) -> Result<()> {
+        let revert = proof.status == ProofStatus::Validated && self.config.on_orphan == OrphanAction::Revert;
+        let data = json!({
+            "block": proof.block,
+            "status": proof.status,
+            "action": if revert { "revert" } else { "flag" },
+        });
+        self.audit.record(AuditKind::Reorg, Some(&proof.proof_id), data).await?;
+        proof.block = None;
+        if revert {
+            println!("↩️  Deposit block of proof {} was orphaned, reverted to created", proof.proof_id);
+            proof.set_status(ProofStatus::Created);
+            self.events.publish(ProofEvent::new(ProofEventKind::Reorged, proof));
+        } else if !proof.flags.contains(&MintFlag::Orphaned) {
+            println!("🚩 Deposit block of proof {} was orphaned", proof.proof_id);
+            proof.flags.push(MintFlag::Orphaned);
+            self.events.publish(ProofEvent::new(ProofEventKind::Flagged, proof));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ProofRequest, TxKey, ValidationRequest};
+    use crate::config::MoneroConfig;
+    use crate::proofs;
+    use crate::storage::ProofStore;
+    use crate::testing::{ADDRESS, StubDaemon};
+    use std::sync::atomic::Ordering;
+
+    fn watcher(daemon: &StubDaemon, on_orphan: OrphanAction, depth: u64) -> Watcher {
+        let url = daemon.clone().spawn();
+        let monero_service = MoneroService::new(&MoneroConfig {
+            daemons: vec![url.clone()],
+            wallet: Some(url),
+            ..MoneroConfig::default()
+        })
+        .unwrap();
+        let config = FinalityConfig { depth, on_orphan, ..FinalityConfig::default() };
+        let storage: ProofStorage = Arc::new(ProofStore::in_memory());
+        Watcher::new(&config, Arc::new(monero_service), storage, EventBus::new(8), Arc::new(AuditLog::disabled()))
+    }
+
+    // Creates and validates a proof of the stub's transaction
+    async fn validated(watcher: &Watcher, tx_id: &str) -> TransactionProof {
+        let request = ProofRequest { tx_id: tx_id.repeat(32), recipient_address: ADDRESS.to_string() };
+        let (service, storage, events) = (&watcher.monero_service, &watcher.storage, &watcher.events);
+        let created = proofs::create_proof(service, storage, events, &request).await.unwrap();
+        let request = ValidationRequest {
+            tx_id: request.tx_id,
+            tx_key: TxKey::parse(&created.tx_key).unwrap().main,
+            recipient_address: ADDRESS.to_string(),
+            additional_tx_keys: Vec::new(),
+        };
+        let validation = proofs::validate(service, storage, events, &AuditLog::disabled(), &request).await.unwrap();
+        validation.proof.unwrap()
+    }
+
+    #[tokio::test]
+    async fn orphaned_deposits_are_reverted_unless_attested() {
+        let daemon = StubDaemon::default();
+        let watcher = watcher(&daemon, OrphanAction::Revert, 20);
+        let proof = validated(&watcher, "ab").await;
+        // The stub mines every transaction 10 blocks below its tip
+        let block = proof.block.clone().unwrap();
+        assert_eq!((block.height, block.hash.as_str(), block.finalized), (90, daemon.block_hash(90).as_str(), false));
+        let attested = validated(&watcher, "cd").await;
+        watcher.storage.write().await.get_mut(&attested.proof_id).unwrap().status = ProofStatus::Attested;
+        assert_eq!(watcher.check().await.unwrap(), Checked::default());
+
+        // Re-mined at the same height in the replacing block
+        daemon.reorgs.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(watcher.check().await.unwrap().moved, 2);
+        assert_eq!(watcher.storage.read().await[&proof.proof_id].block.as_ref().unwrap().hash, daemon.block_hash(90));
+
+        // Then dropped back into the pool
+        let mut events = watcher.events.subscribe();
+        daemon.reorgs.fetch_add(1, Ordering::SeqCst);
+        daemon.in_pool.store(true, Ordering::SeqCst);
+        assert_eq!(watcher.check().await.unwrap().orphaned, 2);
+        let proofs = watcher.storage.read().await.clone();
+        assert_eq!(proofs[&proof.proof_id].status, ProofStatus::Created);
+        assert!(proofs[&proof.proof_id].block.is_none());
+        assert_eq!(proofs[&attested.proof_id].status, ProofStatus::Attested);
+        assert_eq!(proofs[&attested.proof_id].flags, vec![MintFlag::Orphaned]);
+        let mut kinds = vec![events.recv().await.unwrap().kind, events.recv().await.unwrap().kind];
+        kinds.sort_by_key(|kind| kind.as_str());
+        assert_eq!(kinds, [ProofEventKind::Flagged, ProofEventKind::Reorged]);
+    }
+
+    #[tokio::test]
+    async fn flagged_deposits_recover_when_mined_again_and_finalize() {
+        let daemon = StubDaemon::default();
+        let watcher = watcher(&daemon, OrphanAction::Flag, 5);
+        let proof = validated(&watcher, "ab").await;
+
+        daemon.reorgs.fetch_add(1, Ordering::SeqCst);
+        daemon.in_pool.store(true, Ordering::SeqCst);
+        assert_eq!(watcher.check().await.unwrap().orphaned, 1);
+        let flagged = watcher.storage.read().await[&proof.proof_id].clone();
+        assert_eq!((flagged.status, flagged.flags), (ProofStatus::Validated, vec![MintFlag::Orphaned]));
+        // Still in the pool: nothing more to report
+        assert_eq!(watcher.check().await.unwrap(), Checked::default());
+
+        daemon.in_pool.store(false, Ordering::SeqCst);
+        let checked = watcher.check().await.unwrap();
+        assert_eq!((checked.moved, checked.finalized), (1, 1));
+        let recovered = watcher.storage.read().await[&proof.proof_id].clone();
+        assert!(recovered.flags.is_empty() && recovered.block.unwrap().finalized);
+        assert!(!watched(&watcher.storage.read().await[&proof.proof_id]));
+    }
+}
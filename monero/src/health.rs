@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::rpc::MoneroService;
+use crate::attestation::{self, Attestor};
+use crate::storage::ProofStore;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Check {
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn pass(detail: impl Into<String>) -> Self {
+        Self { ok: true, detail: detail.into() }
+    }
+
+    fn fail(detail: impl Into<String>) -> Self {
+        Self { ok: false, detail: detail.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Checks {
+    pub daemon: Check,
+    pub sync: Check,
+    pub wallet: Check,
+    pub storage: Check,
+    pub attestor: Check,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: Checks,
+}
+
+// Everything a request may depend on: a reachable, synced daemon, the wallet
+// (when configured), a writable data dir and a loaded attestor key
+pub async fn readiness(
+    monero_service: &MoneroService,
+    storage: &ProofStore,
+    attestor: Option<&Arc<Attestor>>,
+    max_sync_lag: u64,
+) -> Readiness {
+    let (info, wallet, writable) = tokio::join!(
+        monero_service.get_info(),
+        monero_service.get_wallet_version(),
+        storage.check_writable(),
+    );
+
+    let (daemon, sync) = match info {
+        Ok(info) => {
+            let lag = info.target_height.saturating_sub(info.height);
+            let sync = if !info.synchronized {
+                Check::fail(format!("daemon still syncing at height {} of {}", info.height, info.target_height))
+            } else if lag > max_sync_lag {
+                Check::fail(format!("daemon {} blocks behind target height {}", lag, info.target_height))
+            } else {
+                Check::pass(format!("synchronized at height {}", info.height))
+            };
+            (Check::pass(format!("{} reachable", info.nettype)), sync)
+        }
+        Err(e) => (
+            Check::fail(format!("{:#}", e)),
+            Check::fail("sync status unknown, no daemon reachable"),
+        ),
+    };
+
+    let wallet = match (monero_service.wallet.is_some(), wallet) {
+        (false, _) => Check::pass("not configured, proof generation disabled"),
+        (true, Ok(version)) => Check::pass(format!("wallet-rpc version {}", version)),
+        (true, Err(e)) => Check::fail(format!("{:#}", e)),
+    };
+
+    let storage = match (storage.data_dir(), writable) {
+        (None, _) => Check::pass("in-memory"),
+        (Some(dir), Ok(())) => Check::pass(format!("{} writable", dir.display())),
+        (Some(_), Err(e)) => Check::fail(format!("{:#}", e)),
+    };
+
+    let attestor = match attestor {
+        Some(attestor) => Check::pass(attestation::format_hex(&attestor.address())),
+        None => Check::fail("attestor key not loaded"),
+    };
+
+    let checks = Checks { daemon, sync, wallet, storage, attestor };
+    let ready = [&checks.daemon, &checks.sync, &checks.wallet, &checks.storage, &checks.attestor]
+        .iter()
+        .all(|check| check.ok);
+    Readiness { ready, checks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MoneroConfig;
+    use crate::testing::{StubDaemon, dead_url};
+
+    fn service(daemon: String, wallet: Option<String>) -> MoneroService {
+        MoneroService::new(&MoneroConfig {
+            daemons: vec![daemon],
+            wallet,
+            ..MoneroConfig::default()
+        })
+        .unwrap()
+    }
+
+    fn attestor() -> Arc<Attestor> {
+        Arc::new(Attestor::new(&"4c".repeat(32), "0x0000000000000000000000000000000000000001", 20994).unwrap())
+    }
+
+    #[tokio::test]
+    async fn ready_when_every_dependency_is_up() {
+        let daemon = StubDaemon::default().spawn();
+        let dir = tempfile::tempdir().unwrap();
+        let storage = ProofStore::open(Some(dir.path())).unwrap();
+        let service = service(daemon.clone(), Some(daemon));
+
+        let readiness = readiness(&service, &storage, Some(&attestor()), 2).await;
+        assert!(readiness.ready, "{:?}", readiness.checks);
+    }
+
+    #[tokio::test]
+    async fn lagging_daemon_dead_wallet_and_missing_key_are_not_ready() {
+        let daemon = StubDaemon {
+            height: 90,
+            target_height: 100,
+            synchronized: true,
+            ..StubDaemon::default()
+        }
+        .spawn();
+        let service = service(daemon, Some(dead_url()));
+
+        let readiness = readiness(&service, &ProofStore::in_memory(), None, 2).await;
+        assert!(!readiness.ready);
+        assert!(readiness.checks.daemon.ok);
+        assert!(!readiness.checks.sync.ok);
+        assert!(!readiness.checks.wallet.ok);
+        assert!(readiness.checks.storage.ok);
+        assert!(!readiness.checks.attestor.ok);
+    }
+}
@@ -0,0 +1,156 @@
+// Ethereum V3 JSON keystores (scrypt + aes-128-ctr), the format geth,
+// MetaMask and `cast wallet` import and export
+use aes::cipher::{KeyIvInit, StreamCipher};
+use anyhow::{Context, Result, anyhow, bail};
+use k256::ecdsa::SigningKey;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::attestation;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub id: String,
+    // Lowercase hex without 0x, as geth writes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    pub crypto: Crypto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Crypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub mac: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScryptParams {
+    pub n: u32,
+    pub r: u32,
+    pub p: u32,
+}
+
+// geth's "standard" cost; takes about a second to unlock
+pub const STANDARD_SCRYPT: ScryptParams = ScryptParams { n: 1 << 18, r: 8, p: 1 };
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub dklen: usize,
+    #[serde(flatten)]
+    pub scrypt: ScryptParams,
+    pub salt: String,
+}
+
+impl Keystore {
+    pub fn encrypt(signing_key: &SigningKey, password: &str, params: ScryptParams) -> Result<Self> {
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        rand::rngs::OsRng.fill_bytes(&mut iv);
+        let derived = derive_key(password, &salt, params, 32)?;
+
+        let mut ciphertext = signing_key.to_bytes().to_vec();
+        Aes128Ctr::new(derived[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+        let address = attestation::key_address(signing_key);
+        Ok(Self {
+            version: 3,
+            id: uuid::Uuid::new_v4().to_string(),
+            address: Some(hex::encode(address)),
+            crypto: Crypto {
+                cipher: "aes-128-ctr".to_string(),
+                cipherparams: CipherParams { iv: hex::encode(iv) },
+                mac: hex::encode(mac(&derived, &ciphertext)),
+                ciphertext: hex::encode(ciphertext),
+                kdf: "scrypt".to_string(),
+                kdfparams: KdfParams {
+                    dklen: 32,
+                    scrypt: params,
+                    salt: hex::encode(salt),
+                },
+            },
+        })
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<SigningKey> {
+        let crypto = &self.crypto;
+        if self.version != 3 {
+            bail!("unsupported keystore version {}", self.version);
+        }
+        if crypto.kdf != "scrypt" || crypto.cipher != "aes-128-ctr" {
+            bail!("unsupported keystore ({} / {}), expected scrypt / aes-128-ctr", crypto.kdf, crypto.cipher);
+        }
+        if crypto.kdfparams.dklen < 32 {
+            bail!("keystore dklen must be at least 32");
+        }
+        let salt = hex::decode(&crypto.kdfparams.salt).context("keystore salt is not hex")?;
+        let iv: [u8; 16] = hex::decode(&crypto.cipherparams.iv)
+            .ok()
+            .and_then(|iv| iv.try_into().ok())
+            .ok_or_else(|| anyhow!("keystore iv must be 16 hex bytes"))?;
+        let mut plaintext = hex::decode(&crypto.ciphertext).context("keystore ciphertext is not hex")?;
+        let derived = derive_key(password, &salt, crypto.kdfparams.scrypt, crypto.kdfparams.dklen)?;
+
+        let expected = hex::decode(&crypto.mac).context("keystore mac is not hex")?;
+        if mac(&derived, &plaintext).as_slice() != expected.as_slice() {
+            bail!("wrong keystore password");
+        }
+        Aes128Ctr::new(derived[..16].into(), &iv.into()).apply_keystream(&mut plaintext);
+        let signing_key = SigningKey::from_slice(&plaintext).context("keystore holds an invalid key")?;
+        if let Some(address) = &self.address
+            && attestation::parse_address(address)? != attestation::key_address(&signing_key)
+        {
+            bail!("keystore address does not match its key");
+        }
+        Ok(signing_key)
+    }
+}
+
+fn derive_key(password: &str, salt: &[u8], params: ScryptParams, dklen: usize) -> Result<Vec<u8>> {
+    if !params.n.is_power_of_two() || params.n < 2 {
+        bail!("keystore scrypt n must be a power of two");
+    }
+    let params = scrypt::Params::new(params.n.trailing_zeros() as u8, params.r, params.p, dklen)
+        .map_err(|e| anyhow!("invalid keystore scrypt parameters: {}", e))?;
+    let mut derived = vec![0u8; dklen];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut derived)
+        .map_err(|e| anyhow!("scrypt failed: {}", e))?;
+    Ok(derived)
+}
+
+// keccak256(derived[16..32] || ciphertext)
+fn mac(derived: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    attestation::keccak256(&[&derived[16..32], ciphertext].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap enough for debug builds
+    const TEST_SCRYPT: ScryptParams = ScryptParams { n: 1 << 10, r: 8, p: 1 };
+
+    #[test]
+    fn round_trips_and_rejects_a_wrong_password() {
+        let signing_key = SigningKey::random(&mut rand::rngs::OsRng);
+        let keystore = Keystore::encrypt(&signing_key, "correct horse", TEST_SCRYPT).unwrap();
+        let json = serde_json::to_string(&keystore).unwrap();
+        assert!(json.contains("\"n\":1024"));
+
+        let keystore: Keystore = serde_json::from_str(&json).unwrap();
+        assert_eq!(keystore.decrypt("correct horse").unwrap(), signing_key);
+        let error = keystore.decrypt("battery staple").unwrap_err();
+        assert!(error.to_string().contains("wrong keystore password"));
+    }
+}
@@ -0,0 +1,35 @@
+// Monero transaction validation: proof generation and validation against
+// monerod / monero-wallet-rpc, bridge attestations and the HTTP server.
+// `client` is a typed client for that server's API.
+pub mod amount;
+pub mod api;
+pub mod attestation;
+pub mod audit;
+pub mod cli;
+pub mod client;
+pub mod config;
+pub mod coordination;
+pub mod events;
+pub mod export;
+pub mod finality;
+pub mod health;
+pub mod keystore;
+pub mod network;
+pub mod openapi;
+pub mod policy;
+pub mod pool;
+pub mod privacy;
+pub mod proofs;
+pub mod reconcile;
+pub mod resilience;
+pub mod retention;
+pub mod rpc;
+pub mod scanner;
+pub mod server;
+pub mod signer;
+pub mod storage;
+pub mod supervisor;
+#[cfg(test)]
+mod testing;
+pub mod validation;
+pub mod webhooks;
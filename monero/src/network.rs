@@ -0,0 +1,115 @@
+// The Monero network a server runs on. monerod reports it as `nettype` in
+// `get_info` and every address carries it in its first varint, so a server
+// configured for one network refuses daemons and addresses of the others.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::scanner::Address;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+    Stagenet,
+}
+
+impl Network {
+    pub const ALL: [Network; 3] = [Network::Mainnet, Network::Testnet, Network::Stagenet];
+
+    // As monerod's `nettype`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Stagenet => "stagenet",
+        }
+    }
+
+    pub fn from_nettype(nettype: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|network| network.as_str() == nettype)
+    }
+
+    // Standard, integrated and subaddress prefixes
+    pub fn address_bytes(&self) -> [u64; 3] {
+        match self {
+            Network::Mainnet => [18, 19, 42],
+            Network::Testnet => [53, 54, 63],
+            Network::Stagenet => [24, 25, 36],
+        }
+    }
+
+    pub fn of_address(address: &Address) -> Option<Self> {
+        Self::ALL.into_iter().find(|network| network.address_bytes().contains(&address.network_byte))
+    }
+
+    // Parses `address` and refuses it unless it belongs to this network
+    pub fn check_address(&self, address: &str) -> Result<Address> {
+        let parsed = Address::parse(address)?;
+        match Self::of_address(&parsed) {
+            Some(network) if network == *self => Ok(parsed),
+            found => Err(WrongNetwork {
+                expected: *self,
+                found: found.map_or_else(|| format!("network byte {}", parsed.network_byte), |n| n.to_string()),
+            }
+            .into()),
+        }
+    }
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// An address of another network than the configured one
+#[derive(Debug, Clone)]
+pub struct WrongNetwork {
+    pub expected: Network,
+    pub found: String,
+}
+
+impl std::fmt::Display for WrongNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "address belongs to {}, this server runs on {}", self.found, self.expected)
+    }
+}
+
+impl std::error::Error for WrongNetwork {}
+
+// Whether `error` was caused by a `WrongNetwork`
+pub fn is_wrong_network(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<WrongNetwork>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::test_address;
+    use crate::testing::ADDRESS;
+
+    #[test]
+    fn addresses_are_checked_against_the_network() {
+        assert_eq!(Network::Mainnet.check_address(ADDRESS).unwrap().network_byte, 18);
+        for network in Network::ALL {
+            for byte in network.address_bytes() {
+                let address = test_address(byte, 1).encode();
+                assert_eq!(Network::of_address(&Address::parse(&address).unwrap()), Some(network));
+                assert!(network.check_address(&address).is_ok());
+            }
+        }
+
+        let error = Network::Testnet.check_address(ADDRESS).unwrap_err();
+        assert!(is_wrong_network(&error));
+        assert_eq!(error.to_string(), "address belongs to mainnet, this server runs on testnet");
+        let unknown = test_address(99, 1).encode();
+        assert!(Network::Mainnet.check_address(&unknown).unwrap_err().to_string().contains("network byte 99"));
+        // Not an address at all is not a network problem
+        assert!(!is_wrong_network(&Network::Mainnet.check_address("4abc").unwrap_err()));
+        assert_eq!(Network::from_nettype("stagenet"), Some(Network::Stagenet));
+        assert_eq!(Network::from_nettype("fakechain"), None);
+    }
+}
@@ -0,0 +1,217 @@
+// OpenAPI 3 document generated from the API types and the `#[utoipa::path]`
+// annotations on the handlers in `server`
+use std::sync::LazyLock;
+use utoipa::openapi::path::{Operation, PathItem};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::Deprecated;
+use utoipa::{Modify, OpenApi};
+
+use crate::server;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Monero Transaction Validation API"),
+    paths(
+        server::handle_health,
+        server::handle_health_live,
+        server::handle_health_ready,
+        server::handle_generate_proof,
+        server::handle_validate_transaction,
+        server::handle_get_proofs,
+        server::handle_get_proof,
+        server::handle_delete_proof,
+        server::handle_attest_proof,
+        server::handle_register_webhook,
+        server::handle_list_webhooks,
+        server::handle_delete_webhook,
+        server::handle_list_dead_letters,
+        server::handle_replay_dead_letter,
+        server::handle_gossip_attestation,
+        server::handle_events_sse,
+        server::handle_events_ws,
+        server::handle_export_proofs,
+        server::handle_export_audit,
+        server::handle_import_proofs,
+        server::handle_import_audit,
+        server::handle_generate_proof_v2,
+        server::handle_validate_transaction_v2,
+        server::handle_get_proofs_v2,
+        server::handle_get_proof_v2,
+        server::handle_attest_proof_v2,
+        server::handle_open_proof_v2,
+        server::handle_delete_proof_v2,
+        server::handle_get_attestations_v2,
+        handle_openapi,
+        handle_docs,
+    ),
+    modifiers(&AdminToken, &GossipSignature, &Versions),
+    tags(
+        (name = "proofs", description = "Generate, validate and attest Monero transaction proofs"),
+        (name = "events", description = "Live proof lifecycle events"),
+        (name = "webhooks", description = "Webhook management, requires the admin token"),
+        (name = "export", description = "Bulk export and import of proofs and the audit log, requires the admin token"),
+        (name = "coordination", description = "Threshold attestation between peer validators"),
+        (name = "health", description = "Health and readiness probes"),
+    )
+)]
+pub struct ApiDoc;
+
+// `MONERO_ADMIN_TOKEN`, sent as `Authorization: Bearer <token>`
+struct AdminToken;
+
+impl Modify for AdminToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "admin_token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+// `X-Foid-Signature` over the body, keyed with the shared `GOSSIP_SECRET`
+struct GossipSignature;
+
+impl Modify for GossipSignature {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "gossip_signature",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(crate::webhooks::SIGNATURE_HEADER))),
+        );
+    }
+}
+
+// Events, webhooks and exports are annotated once, under `/v1`; `/v2` serves them
+// unchanged. The unversioned aliases of `/v1` are documented as deprecated.
+struct Versions;
+
+impl Modify for Versions {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let paths = &mut openapi.paths.paths;
+        let v1: Vec<(String, PathItem)> = paths
+            .iter()
+            .filter(|(path, _)| path.starts_with("/v1/"))
+            .map(|(path, item)| (path.clone(), item.clone()))
+            .collect();
+        for (path, item) in v1 {
+            paths
+                .entry(path.replacen("/v1/", "/v2/", 1))
+                .or_insert_with(|| renamed(item.clone(), "v2_", false));
+            if let Some(legacy) = server::legacy_path(&path) {
+                paths.insert(legacy.to_string(), renamed(item, "legacy_", true));
+            }
+        }
+    }
+}
+
+// Operation ids must stay unique across the copies
+fn renamed(mut item: PathItem, prefix: &str, deprecated: bool) -> PathItem {
+    let operations: [&mut Option<Operation>; 5] =
+        [&mut item.get, &mut item.post, &mut item.put, &mut item.delete, &mut item.patch];
+    for operation in operations.into_iter().flatten() {
+        operation.operation_id = operation.operation_id.take().map(|id| format!("{}{}", prefix, id));
+        if deprecated {
+            operation.deprecated = Some(Deprecated::True);
+        }
+    }
+    item
+}
+
+static SPEC: LazyLock<String> =
+    LazyLock::new(|| ApiDoc::openapi().to_pretty_json().expect("OpenAPI document serializes"));
+
+const DOCS_HTML: &str = r##"<!doctype html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>Monero Transaction Validation API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
+
+#[utoipa::path(get, path = "/openapi.json", tag = "health",
+    responses((status = 200, description = "This document", content_type = "application/json")))]
+pub(crate) fn handle_openapi() -> impl warp::Reply {
+    warp::reply::with_header(SPEC.as_str(), "content-type", "application/json")
+}
+
+#[utoipa::path(get, path = "/docs", tag = "health",
+    responses((status = 200, description = "Swagger UI for `/openapi.json`", content_type = "text/html")))]
+pub(crate) fn handle_docs() -> impl warp::Reply {
+    warp::reply::html(DOCS_HTML)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{ROUTES, legacy_path, routes};
+    use crate::testing::{StubDaemon, server_state};
+    use std::collections::BTreeSet;
+
+    // `ROUTES` plus the deprecated unversioned aliases
+    fn table_routes() -> Vec<(String, String)> {
+        let mut routes = Vec::new();
+        for route in ROUTES {
+            for path in [Some(route.path), legacy_path(route.path)].into_iter().flatten() {
+                routes.push((route.method.to_string(), path.to_string()));
+            }
+        }
+        routes
+    }
+
+    fn spec_routes() -> BTreeSet<(String, String)> {
+        let mut documented = BTreeSet::new();
+        for (path, item) in ApiDoc::openapi().paths.paths {
+            let operations = [
+                ("GET", &item.get),
+                ("POST", &item.post),
+                ("PUT", &item.put),
+                ("DELETE", &item.delete),
+                ("PATCH", &item.patch),
+            ];
+            for (method, operation) in operations {
+                if operation.is_some() {
+                    documented.insert((method.to_string(), path.clone()));
+                }
+            }
+        }
+        documented
+    }
+
+    #[test]
+    fn spec_documents_exactly_the_route_table() {
+        let table: BTreeSet<(String, String)> = table_routes().into_iter().collect();
+        assert_eq!(spec_routes(), table);
+    }
+
+    #[tokio::test]
+    async fn every_route_in_the_table_is_served() {
+        let routes = routes(server_state(StubDaemon::default().spawn()));
+        for (method, path) in table_routes() {
+            let response = warp::test::request()
+                .method(&method)
+                .path(&path.replace("{id}", "unknown"))
+                .reply(&routes)
+                .await;
+            // Bad bodies and handler errors are fine; warp's own empty 404 or a
+            // 405 means no filter matched
+            let unrouted = response.status() == 405 || (response.status() == 404 && response.body().is_empty());
+            assert!(
+                !unrouted,
+                "{} {} is documented but not routed: {}",
+                method,
+                path,
+                response.status()
+            );
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// Deposit limits and bridge fees, checked when a proof is attested. The
+// server re-reads the `[policy]` config section while it runs.
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use utoipa::ToSchema;
+
+use crate::amount::Piconero;
+use crate::api::{ProofStatus, TransactionProof};
+use crate::config::{Config, PolicyConfig};
+use crate::proofs;
+use crate::reconcile::MintFlag;
+
+pub(crate) const SECS_PER_DAY: u64 = 86_400;
+
+// `PolicyConfig` with its XMR strings parsed; `None` means no limit
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Policy {
+    pub min_deposit: Option<Piconero>,
+    pub max_deposit: Option<Piconero>,
+    pub daily_cap_per_dest: Option<Piconero>,
+    pub daily_cap: Option<Piconero>,
+    pub fee_bps: u32,
+    pub flat_fee: Piconero,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyOutcome {
+    Accepted,
+    Rejected,
+}
+
+// Recorded on the proof at every attestation attempt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PolicyDecision {
+    pub outcome: PolicyOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub deposit: Piconero,
+    pub fee: Piconero,
+    // What is converted into wFOID
+    pub net: Piconero,
+    pub decided_at: u64,
+}
+
+// Deposits attested on the current UTC day whose attestation was minted or
+// still can be; one that expired unused no longer takes room
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DailyUsage {
+    pub dest: Piconero,
+    pub total: Piconero,
+}
+
+impl DailyUsage {
+    pub fn of<'a>(proofs: impl IntoIterator<Item = &'a TransactionProof>, dest: &[u8; 20], now: u64) -> Self {
+        let mut usage = Self::default();
+        for proof in proofs {
+            let Some(attestation) = &proof.attestation else { continue };
+            if attestation.issued_at / SECS_PER_DAY != now / SECS_PER_DAY {
+                continue;
+            }
+            let minted = proof.status == ProofStatus::Minted || proof.mint.is_some();
+            let expired = !proofs::is_live(attestation, now) || proof.flags.contains(&MintFlag::ExpiredUnused);
+            if expired && !minted {
+                continue;
+            }
+            let deposit = attestation.conversion.as_ref().map(|c| c.received).or(proof.amount).unwrap_or_default();
+            usage.total = usage.total.saturating_add(deposit);
+            if attestation.lock_proof.dest == *dest {
+                usage.dest = usage.dest.saturating_add(deposit);
+            }
+        }
+        usage
+    }
+}
+
+impl Policy {
+    pub fn new(config: &PolicyConfig) -> Result<Self> {
+        let parse = |field: &str, value: &Option<String>| -> Result<Option<Piconero>> {
+            value
+                .as_deref()
+                .map(|xmr| xmr.parse().map_err(|e: anyhow::Error| e.context(format!("policy.{}", field))))
+                .transpose()
+        };
+        if config.fee_bps >= 10_000 {
+            bail!("policy.fee_bps must be below 10000 (100%)");
+        }
+        let policy = Self {
+            min_deposit: parse("min_deposit_xmr", &config.min_deposit_xmr)?,
+            max_deposit: parse("max_deposit_xmr", &config.max_deposit_xmr)?,
+            daily_cap_per_dest: parse("daily_cap_per_dest_xmr", &config.daily_cap_per_dest_xmr)?,
+            daily_cap: parse("daily_cap_xmr", &config.daily_cap_xmr)?,
+            fee_bps: config.fee_bps,
+            flat_fee: parse("flat_fee_xmr", &config.flat_fee_xmr)?.unwrap_or_default(),
+        };
+        if let (Some(min), Some(max)) = (policy.min_deposit, policy.max_deposit)
+            && min > max
+        {
+            bail!("policy.min_deposit_xmr is above policy.max_deposit_xmr");
+        }
+        Ok(policy)
+    }
+
+    // Flat fee plus `fee_bps` of the deposit, rounded up
+    pub fn fee(&self, deposit: Piconero) -> Piconero {
+        let percentage = (u128::from(deposit.0) * u128::from(self.fee_bps)).div_ceil(10_000);
+        self.flat_fee.saturating_add(Piconero(percentage as u64))
+    }
+
+    pub fn evaluate(&self, deposit: Piconero, usage: DailyUsage, now: u64) -> PolicyDecision {
+        let fee = self.fee(deposit);
+        let exceeds = |used: Piconero, cap: Option<Piconero>| cap.filter(|cap| used.saturating_add(deposit) > *cap);
+        let reason = if let Some(min) = self.min_deposit.filter(|min| deposit < *min) {
+            Some(format!("deposit of {} XMR is below the {} XMR minimum", deposit, min))
+        } else if let Some(max) = self.max_deposit.filter(|max| deposit > *max) {
+            Some(format!("deposit of {} XMR is above the {} XMR per-transaction maximum", deposit, max))
+        } else if fee >= deposit {
+            Some(format!("deposit of {} XMR does not cover the {} XMR fee", deposit, fee))
+        } else if let Some(cap) = exceeds(usage.dest, self.daily_cap_per_dest) {
+            Some(format!("destination would exceed its {} XMR daily cap ({} XMR used)", cap, usage.dest))
+        } else {
+            exceeds(usage.total, self.daily_cap)
+                .map(|cap| format!("bridge would exceed its {} XMR daily cap ({} XMR used)", cap, usage.total))
+        };
+        let outcome = if reason.is_some() { PolicyOutcome::Rejected } else { PolicyOutcome::Accepted };
+        PolicyDecision {
+            outcome,
+            reason,
+            deposit,
+            fee,
+            net: deposit.checked_sub(fee).unwrap_or_default(),
+            decided_at: now,
+        }
+    }
+}
+
+// The live policy, shared by the handlers and swapped on reload
+#[derive(Clone, Default)]
+pub struct PolicyEngine(Arc<RwLock<Arc<Policy>>>);
+
+impl PolicyEngine {
+    pub fn new(policy: Policy) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(policy))))
+    }
+
+    pub fn current(&self) -> Arc<Policy> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    // Whether `path` held a different, valid policy
+    pub fn reload(&self, path: &Path) -> Result<bool> {
+        let policy = Policy::new(&Config::load(Some(path))?.policy)?;
+        if *self.current() == policy {
+            return Ok(false);
+        }
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(policy);
+        Ok(true)
+    }
+
+    // Reloads whenever the config file's modification time changes. An
+    // invalid file keeps the current policy.
+    pub async fn watch(self, path: PathBuf, interval: Duration) {
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let mut last: Option<SystemTime> = modified(&path);
+        loop {
+            tokio::time::sleep(interval).await;
+            let current = modified(&path);
+            if current == last {
+                continue;
+            }
+            last = current;
+            match self.reload(&path) {
+                Ok(true) => println!("🔄 Reloaded policy from {}", path.display()),
+                Ok(false) => {}
+                Err(e) => println!("⚠️  Keeping the current policy, {} is invalid: {:#}", path.display(), e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xmr(value: &str) -> Piconero {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn enforces_limits_caps_and_fees_in_order() {
+        let policy = Policy::new(&PolicyConfig {
+            min_deposit_xmr: Some("0.1".to_string()),
+            max_deposit_xmr: Some("10".to_string()),
+            daily_cap_per_dest_xmr: Some("15".to_string()),
+            daily_cap_xmr: Some("100".to_string()),
+            fee_bps: 50,
+            flat_fee_xmr: Some("0.01".to_string()),
+        })
+        .unwrap();
+        let usage = DailyUsage::default();
+
+        let accepted = policy.evaluate(xmr("2"), usage, 0);
+        assert_eq!(accepted.outcome, PolicyOutcome::Accepted);
+        assert_eq!(accepted.fee, xmr("0.02"));
+        assert_eq!(accepted.net, xmr("1.98"));
+
+        let reason = |deposit: &str, usage| policy.evaluate(xmr(deposit), usage, 0).reason.unwrap();
+        assert!(reason("0.05", usage).contains("minimum"));
+        assert!(reason("11", usage).contains("maximum"));
+        let busy_dest = DailyUsage { dest: xmr("10"), total: xmr("10") };
+        assert!(reason("6", busy_dest).contains("destination"));
+        let busy_bridge = DailyUsage { dest: Piconero::ZERO, total: xmr("95") };
+        assert!(reason("6", busy_bridge).contains("bridge"));
+    }
+
+    #[test]
+    fn reload_swaps_only_valid_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("monero.toml");
+        let engine = PolicyEngine::default();
+
+        std::fs::write(&path, "[policy]\nmin_deposit_xmr = \"0.5\"\n").unwrap();
+        assert!(engine.reload(&path).unwrap());
+        assert_eq!(engine.current().min_deposit, Some(xmr("0.5")));
+        assert!(!engine.reload(&path).unwrap());
+
+        std::fs::write(&path, "[policy]\nmin_deposit_xmr = \"half\"\n").unwrap();
+        assert!(engine.reload(&path).is_err());
+        assert_eq!(engine.current().min_deposit, Some(xmr("0.5")));
+    }
+}
@@ -0,0 +1,481 @@
+use anyhow::{Result, anyhow};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use crate::events::unix_now;
+use crate::network::Network;
+use crate::resilience::{
+    BreakerState, CircuitBreaker, CircuitOpen, is_idempotent, is_transport_error, jittered_backoff,
+};
+use crate::rpc::{DaemonInfo, RpcClient};
+
+// Weight of the newest sample in the latency moving average
+const LATENCY_ALPHA: f64 = 0.3;
+
+// Endpoints answered a quorum call with different results
+#[derive(Debug, Clone)]
+pub struct QuorumConflict {
+    pub method: String,
+}
+
+impl std::fmt::Display for QuorumConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} quorum failed: daemons returned conflicting results", self.method)
+    }
+}
+
+impl std::error::Error for QuorumConflict {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub healthy: bool,
+    pub latency_ms: Option<u64>,
+    pub height: Option<u64>,
+    // As reported by the last health check
+    pub nettype: Option<String>,
+    pub breaker: BreakerState,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_checked: Option<u64>,
+}
+
+struct Endpoint {
+    url: String,
+    healthy: bool,
+    latency_ms: Option<f64>,
+    height: Option<u64>,
+    nettype: Option<String>,
+    breaker: CircuitBreaker,
+    last_error: Option<String>,
+    last_checked: Option<u64>,
+}
+
+impl Endpoint {
+    fn record_success(&mut self, latency: Duration) {
+        let sample = latency.as_secs_f64() * 1000.0;
+        self.latency_ms = Some(match self.latency_ms {
+            Some(avg) => avg + LATENCY_ALPHA * (sample - avg),
+            None => sample,
+        });
+        self.healthy = true;
+        self.breaker.on_success();
+        self.last_error = None;
+    }
+
+    fn record_failure(&mut self, error: &anyhow::Error) {
+        // Only transport problems (unreachable, timeout, HTTP error) count
+        // against a node; an RPC-level error such as a tx this node has not
+        // seen still triggers failover but leaves the node healthy.
+        if is_transport_error(error) {
+            self.healthy = false;
+            self.breaker.on_failure();
+        }
+        self.last_error = Some(format!("{:#}", error));
+    }
+}
+
+// Pool of Monero RPC endpoints (monerod, or a single wallet-rpc) with health
+// checks, latency-ranked failover, per-endpoint circuit breakers, retries for
+// idempotent methods and an optional quorum mode in which N endpoints must
+// return identical results.
+#[derive(Clone)]
+pub struct RpcPool {
+    endpoints: Arc<Vec<Mutex<Endpoint>>>,
+    quorum: usize,
+    rpc: RpcClient,
+    // Endpoints reporting another nettype are never called
+    network: Option<Network>,
+}
+
+impl RpcPool {
+    pub fn new(urls: &[String], quorum: usize, rpc: RpcClient) -> Self {
+        let policy = rpc.policy();
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                Mutex::new(Endpoint {
+                    url: url.clone(),
+                    // Optimistic until the first health check says otherwise
+                    healthy: true,
+                    latency_ms: None,
+                    height: None,
+                    nettype: None,
+                    breaker: CircuitBreaker::new(
+                        policy.breaker_failure_threshold,
+                        Duration::from_secs(policy.breaker_cooldown_secs),
+                    ),
+                    last_error: None,
+                    last_checked: None,
+                })
+            })
+            .collect();
+        Self {
+            endpoints: Arc::new(endpoints),
+            quorum: quorum.max(1),
+            rpc,
+            network: None,
+        }
+    }
+
+    pub fn with_network(self, network: Network) -> Self {
+        Self {
+            network: Some(network),
+            ..self
+        }
+    }
+
+    pub fn network(&self) -> Option<Network> {
+        self.network
+    }
+
+    pub fn quorum(&self) -> usize {
+        self.quorum
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let endpoint = endpoint.lock().unwrap();
+                EndpointStatus {
+                    url: endpoint.url.clone(),
+                    healthy: endpoint.healthy,
+                    latency_ms: endpoint.latency_ms.map(|l| l.round() as u64),
+                    height: endpoint.height,
+                    nettype: endpoint.nettype.clone(),
+                    breaker: endpoint.breaker.state(),
+                    consecutive_failures: endpoint.breaker.consecutive_failures(),
+                    last_error: endpoint.last_error.clone(),
+                    last_checked: endpoint.last_checked,
+                }
+            })
+            .collect()
+    }
+
+    // True when every endpoint's breaker is open and calls would fast-fail
+    pub fn all_open(&self) -> bool {
+        self.endpoints
+            .iter()
+            .all(|endpoint| endpoint.lock().unwrap().breaker.state() == BreakerState::Open)
+    }
+
+    // Whether `endpoint` reported a nettype other than the pool's network.
+    // Unknown until its first health check.
+    fn wrong_network(&self, endpoint: &Endpoint) -> bool {
+        match (self.network, &endpoint.nettype) {
+            (Some(network), Some(nettype)) => nettype != network.as_str(),
+            _ => false,
+        }
+    }
+
+    // Probes every daemon with `get_info`. Probes bypass the breaker so a
+    // recovered node is noticed without waiting for real traffic.
+    pub async fn check_health(&self) {
+        let checks = (0..self.endpoints.len()).map(|index| async move {
+            let url = self.endpoints[index].lock().unwrap().url.clone();
+            let started = Instant::now();
+            let result = self.rpc.json_rpc::<DaemonInfo>(&url, "get_info", serde_json::json!({})).await;
+            let mut endpoint = self.endpoints[index].lock().unwrap();
+            endpoint.last_checked = Some(unix_now());
+            match result {
+                Ok(info) => {
+                    endpoint.record_success(started.elapsed());
+                    endpoint.height = Some(info.height);
+                    endpoint.nettype = Some(info.nettype);
+                    if self.wrong_network(&endpoint) {
+                        endpoint.healthy = false;
+                        endpoint.last_error = Some(format!(
+                            "daemon is on {}, expected {}",
+                            endpoint.nettype.as_deref().unwrap_or_default(),
+                            self.network.unwrap_or_default()
+                        ));
+                        println!("🚨 Daemon {} is on the wrong network and will not be used", endpoint.url);
+                    }
+                }
+                Err(e) => {
+                    endpoint.record_failure(&e);
+                    endpoint.healthy = false;
+                    println!("⚠️  Daemon {} failed health check: {:#}", endpoint.url, e);
+                }
+            }
+        });
+        join_all(checks).await;
+    }
+
+    pub async fn run_health_checks(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.check_health().await;
+        }
+    }
+
+    // Healthy endpoints by ascending latency (unmeasured last), then unhealthy
+    // ones as a last resort
+    fn ranked(&self) -> Vec<usize> {
+        let mut order: Vec<(usize, bool, f64)> = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                let endpoint = endpoint.lock().unwrap();
+                (index, endpoint.healthy, endpoint.latency_ms.unwrap_or(f64::MAX))
+            })
+            .collect();
+        order.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.total_cmp(&b.2)));
+        order.into_iter().map(|(index, _, _)| index).collect()
+    }
+
+    // Ranked endpoints whose breaker is not open, on the pool's network
+    fn available(&self) -> Vec<usize> {
+        self.ranked()
+            .into_iter()
+            .filter(|index| {
+                let endpoint = self.endpoints[*index].lock().unwrap();
+                endpoint.breaker.state() != BreakerState::Open && !self.wrong_network(&endpoint)
+            })
+            .collect()
+    }
+
+    async fn attempt<T, F, Fut>(&self, index: usize, call: &F) -> Result<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let url = {
+            let mut endpoint = self.endpoints[index].lock().unwrap();
+            // Half-open breakers only let a single trial call through
+            if !endpoint.breaker.allow() {
+                return Err(anyhow!("circuit breaker open for {}", endpoint.url));
+            }
+            endpoint.url.clone()
+        };
+        let started = Instant::now();
+        let result = call(url).await;
+        let mut endpoint = self.endpoints[index].lock().unwrap();
+        match &result {
+            Ok(_) => endpoint.record_success(started.elapsed()),
+            Err(e) => endpoint.record_failure(e),
+        }
+        result
+    }
+
+    // Idempotent methods get `max_retries` extra rounds with jittered backoff.
+    // An open breaker on every endpoint fails fast without retrying.
+    async fn with_retries<T, R, RFut>(&self, method: &str, round: R) -> Result<T>
+    where
+        R: Fn() -> RFut,
+        RFut: Future<Output = Result<T>>,
+    {
+        let policy = self.rpc.policy();
+        let attempts = if is_idempotent(method) { policy.max_retries + 1 } else { 1 };
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match round().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= attempts || e.is::<CircuitOpen>() || e.is::<QuorumConflict>() => {
+                    return Err(e);
+                }
+                Err(e) => {
+                    let delay = jittered_backoff(
+                        attempt,
+                        Duration::from_millis(policy.retry_base_delay_ms),
+                        Duration::from_millis(policy.retry_max_delay_ms),
+                    );
+                    println!("🔁 {} attempt {} failed ({:#}), retrying in {:?}", method, attempt, e, delay);
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    // Runs `call` against the best endpoint, failing over down the ranking
+    pub async fn call<T, F, Fut>(&self, method: &str, call: F) -> Result<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.with_retries(method, || async {
+            let candidates = self.available();
+            if candidates.is_empty() && !self.all_open() {
+                let network = self.network.unwrap_or_default();
+                return Err(anyhow!("{} failed: no endpoint is on {}", method, network));
+            }
+            if candidates.is_empty() {
+                return Err(CircuitOpen { method: method.to_string() }.into());
+            }
+            let mut last_error = None;
+            for index in candidates {
+                match self.attempt(index, &call).await {
+                    Ok(value) => return Ok(value),
+                    Err(e) => {
+                        println!("🔁 {} failed on an endpoint, failing over: {:#}", method, e);
+                        last_error = Some(e);
+                    }
+                }
+            }
+            Err(anyhow!(
+                "{} failed on all {} endpoints: {}",
+                method,
+                self.endpoints.len(),
+                last_error.map(|e| format!("{:#}", e)).unwrap_or_default()
+            ))
+        })
+        .await
+    }
+
+    // In quorum mode every available endpoint is queried and at least
+    // `quorum` of them must answer, all with the same result. Any
+    // disagreement fails the call and is not retried.
+    pub async fn quorum_call<T, F, Fut>(&self, method: &str, call: F) -> Result<T>
+    where
+        T: PartialEq + std::fmt::Debug,
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if self.quorum == 1 {
+            return self.call(method, call).await;
+        }
+        self.with_retries(method, || async {
+            let candidates = self.available();
+            if candidates.len() < self.quorum && self.all_open() {
+                return Err(CircuitOpen { method: method.to_string() }.into());
+            }
+            let results = join_all(candidates.into_iter().map(|index| self.attempt(index, &call))).await;
+            let mut answers: Vec<T> = results.into_iter().filter_map(Result::ok).collect();
+            if answers.len() < self.quorum {
+                return Err(anyhow!(
+                    "{} quorum not reached: {} of {} required endpoints answered",
+                    method,
+                    answers.len(),
+                    self.quorum
+                ));
+            }
+            if let Some(conflict) = answers.iter().find(|answer| **answer != answers[0]) {
+                println!("🚨 Daemons disagree on {}: {:?} vs {:?}", method, answers[0], conflict);
+                // Conflicts are a safety signal, not a transient failure
+                return Err(QuorumConflict { method: method.to_string() }.into());
+            }
+            Ok(answers.swap_remove(0))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RpcPolicyConfig;
+    use crate::resilience::is_circuit_open;
+    use crate::testing::{StubDaemon, dead_url};
+
+    fn policy() -> RpcPolicyConfig {
+        RpcPolicyConfig {
+            default_timeout_ms: 2_000,
+            max_retries: 0,
+            ..RpcPolicyConfig::default()
+        }
+    }
+
+    fn pool(urls: &[String], quorum: usize) -> RpcPool {
+        pool_with(urls, quorum, policy())
+    }
+
+    fn pool_with(urls: &[String], quorum: usize, policy: RpcPolicyConfig) -> RpcPool {
+        RpcPool::new(urls, quorum, RpcClient::new(policy))
+    }
+
+    async fn height(pool: &RpcPool) -> Result<u64> {
+        let rpc = pool.rpc.clone();
+        pool.quorum_call("get_info", |url| {
+            let rpc = rpc.clone();
+            async move {
+                let info: DaemonInfo = rpc.json_rpc(&url, "get_info", serde_json::json!({})).await?;
+                Ok(info.height)
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn fails_over_and_prefers_the_fastest_healthy_daemon() {
+        let slow = StubDaemon { delay_ms: 60, ..StubDaemon::default() }.spawn();
+        let fast = StubDaemon::default().spawn();
+        let pool = pool(&[dead_url(), slow.clone(), fast.clone()], 1);
+
+        // Before any health check the dead node is tried first and skipped
+        assert_eq!(height(&pool).await.unwrap(), 100);
+        pool.check_health().await;
+
+        let status = pool.status();
+        assert!(!status[0].healthy);
+        assert!(status[1].healthy && status[2].healthy);
+        assert_eq!(pool.ranked(), vec![2, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn quorum_requires_agreement_and_enough_answers() {
+        let a = StubDaemon::default().spawn();
+        let b = StubDaemon::default().spawn();
+        let forked = StubDaemon { height: 90, ..StubDaemon::default() }.spawn();
+
+        assert_eq!(height(&pool(&[a.clone(), b.clone()], 2)).await.unwrap(), 100);
+        assert!(height(&pool(&[a.clone(), forked], 2)).await.is_err());
+        assert!(height(&pool(&[a, dead_url()], 2)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn slow_calls_time_out_and_open_the_breaker() {
+        let slow = StubDaemon { delay_ms: 300, ..StubDaemon::default() }.spawn();
+        let pool = pool_with(
+            &[slow],
+            1,
+            RpcPolicyConfig {
+                timeouts_ms: [("get_info".to_string(), 50)].into(),
+                max_retries: 1,
+                retry_base_delay_ms: 1,
+                breaker_failure_threshold: 2,
+                ..RpcPolicyConfig::default()
+            },
+        );
+
+        // Two timed-out attempts (one retry) trip the breaker...
+        let started = Instant::now();
+        assert!(height(&pool).await.is_err());
+        assert!(started.elapsed() < Duration::from_millis(250));
+        assert_eq!(pool.status()[0].breaker, BreakerState::Open);
+        assert!(pool.all_open());
+
+        // ...after which calls fail fast without touching the node
+        let error = height(&pool).await.unwrap_err();
+        assert!(is_circuit_open(&error));
+    }
+
+    #[tokio::test]
+    async fn daemons_on_another_network_are_not_used() {
+        let testnet = StubDaemon { nettype: "testnet".to_string(), ..StubDaemon::default() }.spawn();
+        let mainnet = StubDaemon { height: 90, ..StubDaemon::default() }.spawn();
+        let mixed = pool(&[testnet.clone(), mainnet], 1).with_network(Network::Mainnet);
+        mixed.check_health().await;
+
+        let status = mixed.status();
+        assert_eq!(status[0].nettype.as_deref(), Some("testnet"));
+        assert!(!status[0].healthy && status[0].last_error.as_deref().unwrap().contains("expected mainnet"));
+        assert_eq!(height(&mixed).await.unwrap(), 90);
+
+        let stagenet = pool(&[testnet], 1).with_network(Network::Stagenet);
+        stagenet.check_health().await;
+        let error = height(&stagenet).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("no endpoint is on stagenet"), "{:#}", error);
+    }
+}
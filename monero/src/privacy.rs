@@ -0,0 +1,280 @@
+// Privacy mode (`storage.privacy`): stored proofs keep a salted commitment
+// instead of the tx id, tx key and recipient address, and are only shown to
+// whoever presents that preimage. Minted proofs can also be sealed
+// (`storage.purge_after_mint_secs`): the attestation and mint record, which
+// name the Monero tx and the EVM destination, give way to a commitment
+// linking the two.
+use anyhow::Result;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::api::{ProofStatus, TransactionProof, TxKey};
+use crate::attestation;
+use crate::events::unix_now;
+use crate::policy::SECS_PER_DAY;
+use crate::storage::ProofStorage;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Commitment {
+    // 16 random bytes, hex
+    pub salt: String,
+    // sha256(salt ‖ tx_id ‖ tx_key ‖ recipient_address), hex
+    pub deposit: String,
+    // sha256(salt ‖ tx_id ‖ dest), hex; set when the proof is sealed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+// What a user presents to open a committed proof: the deposit exactly as
+// returned when the proof was created
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProofPreimage {
+    pub tx_id: String,
+    // Main key with any additional keys appended
+    pub tx_key: String,
+    pub recipient_address: String,
+}
+
+impl ProofPreimage {
+    // The deposit data of a proof that is not committed
+    pub fn of(proof: &TransactionProof) -> Self {
+        Self {
+            tx_id: proof.tx_id.clone(),
+            tx_key: proof.tx_key.clone(),
+            recipient_address: proof.recipient_address.clone(),
+        }
+    }
+
+    // Hex is hashed lowercase, so either case opens a commitment
+    fn canonical(&self) -> Option<(String, String)> {
+        let tx_key = TxKey::parse(&self.tx_key).ok()?;
+        Some((self.tx_id.trim().to_ascii_lowercase(), tx_key.concatenated()))
+    }
+}
+
+impl Commitment {
+    pub fn new(preimage: &ProofPreimage) -> Result<Self> {
+        let (tx_id, tx_key) = preimage.canonical().ok_or_else(|| anyhow::anyhow!("invalid tx key"))?;
+        let mut salt = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        Ok(Self {
+            deposit: digest(&salt, &[&tx_id, &tx_key, &preimage.recipient_address]),
+            salt: hex::encode(salt),
+            link: None,
+        })
+    }
+
+    pub fn opens(&self, preimage: &ProofPreimage) -> bool {
+        let (Ok(salt), Some((tx_id, tx_key))) = (hex::decode(&self.salt), preimage.canonical()) else {
+            return false;
+        };
+        let expected = digest(&salt, &[&tx_id, &tx_key, &preimage.recipient_address]);
+        constant_time_eq(expected.as_bytes(), self.deposit.as_bytes())
+    }
+}
+
+fn digest(salt: &[u8], parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(parts.join("\n").as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+// Compares without stopping at the first difference; only the length leaks
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// Whether `preimage` is the deposit of `proof`, committed or not
+pub fn opens(proof: &TransactionProof, preimage: &ProofPreimage) -> bool {
+    match &proof.commitment {
+        Some(commitment) => commitment.opens(preimage),
+        None => {
+            proof.tx_id == preimage.tx_id
+                && TxKey::parse(&proof.tx_key).is_ok_and(|key| TxKey::parse(&preimage.tx_key).is_ok_and(|k| k == key))
+                && proof.recipient_address == preimage.recipient_address
+        }
+    }
+}
+
+// Replaces the deposit data of `proof` with a commitment to it. Tombstones
+// have nothing left to conceal.
+pub fn conceal(proof: &mut TransactionProof) -> Result<()> {
+    if proof.commitment.is_some() || proof.status == ProofStatus::Deleted {
+        return Ok(());
+    }
+    proof.commitment = Some(Commitment::new(&ProofPreimage::of(proof))?);
+    proof.tx_id.clear();
+    proof.tx_key.clear();
+    proof.recipient_address.clear();
+    Ok(())
+}
+
+// `proof` as `GET` shows it to anyone. Presenting the tx key proves
+// ownership (deletion, attestation), so it is left out, as in events; only
+// whoever created the proof got it.
+pub fn public(proof: &TransactionProof) -> TransactionProof {
+    TransactionProof {
+        tx_key: String::new(),
+        ..proof.clone()
+    }
+}
+
+// `proof` with its deposit data filled in, if `preimage` opens it
+pub fn reveal(proof: &TransactionProof, preimage: &ProofPreimage) -> Option<TransactionProof> {
+    if !opens(proof, preimage) {
+        return None;
+    }
+    let mut revealed = proof.clone();
+    if revealed.commitment.is_some() {
+        revealed.tx_id = preimage.tx_id.clone();
+        revealed.tx_key = preimage.tx_key.clone();
+        revealed.recipient_address = preimage.recipient_address.clone();
+    }
+    Some(revealed)
+}
+
+// An unsalted digest of a deposit, recorded by private stores in the audit
+// log in place of the deposit data. The tx key keeps it from being guessed.
+pub fn fingerprint(preimage: &ProofPreimage) -> Value {
+    match preimage.canonical() {
+        Some((tx_id, tx_key)) => json!(digest(&[], &[&tx_id, &tx_key, &preimage.recipient_address])),
+        None => Value::Null,
+    }
+}
+
+// A minted proof may be sealed once `after_secs` passed since its
+// attestation and the attestation's UTC day is over, so it no longer counts
+// towards the daily caps
+pub fn sealable(proof: &TransactionProof, now: u64, after_secs: u64) -> bool {
+    let Some(attestation) = &proof.attestation else {
+        return false;
+    };
+    proof.status == ProofStatus::Minted
+        && now >= attestation.issued_at.saturating_add(after_secs)
+        && now / SECS_PER_DAY > attestation.issued_at / SECS_PER_DAY
+}
+
+// Drops the attestation, mint record and block, keeping only the amount, status and
+// commitments. Uncommitted proofs are concealed first.
+pub fn seal(proof: &mut TransactionProof) -> Result<()> {
+    conceal(proof)?;
+    let (Some(attestation), Some(commitment)) = (proof.attestation.take(), proof.commitment.as_mut()) else {
+        return Ok(());
+    };
+    let salt = hex::decode(&commitment.salt)?;
+    let lock_proof = &attestation.lock_proof;
+    commitment.link = Some(digest(
+        &salt,
+        &[&hex::encode(lock_proof.monero_tx), &attestation::format_hex(&lock_proof.dest)],
+    ));
+    proof.mint = None;
+    proof.block = None;
+    Ok(())
+}
+
+// Seals minted proofs every `interval`
+pub async fn run_purger(storage: ProofStorage, after_secs: u64, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let now = unix_now();
+        let mut sealed = 0;
+        for proof in storage.write().await.values_mut() {
+            if !sealable(proof, now, after_secs) {
+                continue;
+            }
+            match seal(proof) {
+                Ok(()) => sealed += 1,
+                Err(e) => println!("❌ Failed to seal proof {}: {:#}", proof.proof_id, e),
+            }
+        }
+        if sealed > 0 {
+            println!("🔒 Sealed {} minted proofs", sealed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Piconero;
+    use crate::attestation::{Attestation, LockProof};
+
+    fn proof() -> TransactionProof {
+        TransactionProof {
+            tx_id: "ab".repeat(32),
+            tx_key: "cd".repeat(32),
+            recipient_address: crate::testing::ADDRESS.to_string(),
+            amount: Some(Piconero(5)),
+            proof_id: "p1".to_string(),
+            status: ProofStatus::Created,
+            updated_at: 0,
+            attestation: None,
+            policy: None,
+            mint: None,
+            flags: Vec::new(),
+            commitment: None,
+            block: None,
+        }
+    }
+
+    #[test]
+    fn only_the_preimage_opens_a_concealed_proof() {
+        let plain = proof();
+        let mut concealed = plain.clone();
+        conceal(&mut concealed).unwrap();
+        assert!(concealed.tx_id.is_empty() && concealed.tx_key.is_empty() && concealed.recipient_address.is_empty());
+        let json = serde_json::to_string(&concealed).unwrap();
+        assert!(!json.contains(&plain.tx_key) && !json.contains(&plain.recipient_address));
+
+        // Hex case does not matter, every field does
+        let preimage = ProofPreimage {
+            tx_id: plain.tx_id.to_uppercase(),
+            ..ProofPreimage::of(&plain)
+        };
+        let revealed = reveal(&concealed, &preimage).unwrap();
+        assert_eq!(revealed.tx_key, plain.tx_key);
+        let wrong_key = ProofPreimage { tx_key: "ce".repeat(32), ..preimage.clone() };
+        assert!(reveal(&concealed, &wrong_key).is_none());
+        let wrong_recipient = ProofPreimage { recipient_address: "4".to_string(), ..preimage };
+        assert!(reveal(&concealed, &wrong_recipient).is_none());
+    }
+
+    #[test]
+    fn minted_proofs_are_sealed_after_their_day() {
+        let mut minted = proof();
+        let monero_tx = [0xab; 32];
+        let dest = [0x02; 20];
+        let issued_at = 10 * SECS_PER_DAY + 100;
+        minted.status = ProofStatus::Minted;
+        minted.attestation = Some(Attestation {
+            lock_proof: LockProof::new(monero_tx, dest, 5, issued_at + 3600),
+            signature: "0x01".to_string(),
+            attestor: "0x03".to_string(),
+            issued_at,
+            conversion: None,
+            signatures: Vec::new(),
+            previous_signature: None,
+            network: None,
+        });
+        assert!(!sealable(&minted, issued_at + 3600, 0));
+        assert!(!sealable(&minted, 11 * SECS_PER_DAY, 2 * SECS_PER_DAY));
+        assert!(sealable(&minted, 11 * SECS_PER_DAY, 3600));
+
+        let preimage = ProofPreimage::of(&minted);
+        seal(&mut minted).unwrap();
+        assert!(minted.attestation.is_none() && minted.tx_id.is_empty());
+        assert_eq!(minted.status, ProofStatus::Minted);
+        let commitment = minted.commitment.clone().unwrap();
+        assert!(commitment.opens(&preimage));
+        let salt = hex::decode(&commitment.salt).unwrap();
+        let link = digest(&salt, &[&hex::encode(monero_tx), &attestation::format_hex(&dest)]);
+        assert_eq!(commitment.link, Some(link));
+    }
+}
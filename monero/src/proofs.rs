@@ -0,0 +1,1019 @@
+// Proof lifecycle shared by the HTTP handlers and the CLI: create from the
+// wallet, validate against the daemons, attest with the bridge key.
+use anyhow::Result;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::amount::Piconero;
+use crate::api::{
+    AttestRequest, OutstandingAttestation, ProofRequest, ProofStatus, TransactionProof, ValidationRequest,
+    ValidationResponse,
+};
+use crate::attestation::{self, Attestation, Attestor, LockProof, PartialSignature};
+use crate::audit::{self, AuditKind, AuditLog};
+use crate::coordination::CosignRequest;
+use crate::events::{EventBus, ProofEvent, ProofEventKind, unix_now};
+use crate::finality::BlockRef;
+use crate::network::Network;
+use crate::policy::{DailyUsage, Policy};
+use crate::privacy::{self, ProofPreimage};
+use crate::reconcile::MintFlag;
+use crate::rpc::MoneroService;
+use crate::scanner::{self, MatchedOutput};
+use crate::storage::ProofStore;
+
+// Fetches the tx key from the wallet and stores a new proof. A private store
+// keeps only a commitment, the caller gets the proof in full.
+pub async fn create_proof(
+    monero_service: &MoneroService,
+    storage: &ProofStore,
+    events: &EventBus,
+    request: &ProofRequest,
+) -> Result<TransactionProof> {
+    monero_service
+        .network
+        .check_address(&request.recipient_address)
+        .map_err(|e| e.context("Invalid recipient address"))?;
+    let tx_key = monero_service
+        .get_tx_key(&request.tx_id)
+        .await
+        .map_err(|e| e.context("Failed to get transaction key"))?;
+    // Only checks the daemons know the transaction: its amount is encrypted
+    // and known once validation decrypts the outputs paying the recipient
+    monero_service
+        .get_transaction(&request.tx_id)
+        .await
+        .map_err(|e| e.context("Failed to get transaction details"))?;
+
+    let proof = TransactionProof {
+        tx_id: request.tx_id.clone(),
+        tx_key: tx_key.concatenated(),
+        recipient_address: request.recipient_address.clone(),
+        amount: None,
+        proof_id: Uuid::new_v4().to_string(),
+        status: ProofStatus::Created,
+        updated_at: unix_now(),
+        attestation: None,
+        policy: None,
+        mint: None,
+        flags: Vec::new(),
+        commitment: None,
+        block: None,
+    };
+    let mut stored = proof.clone();
+    if storage.is_private() {
+        privacy::conceal(&mut stored)?;
+    }
+    let mut proofs = storage.write().await;
+    let deposit = ProofPreimage::of(&proof);
+    if let Some(existing) = proofs.values().find(|existing| claims_deposit(existing, &deposit)) {
+        return Err(DuplicateDeposit { proof_id: existing.proof_id.clone() }.into());
+    }
+    // Subscribers see the event only for a proof they can read back
+    proofs.insert(proof.proof_id.clone(), stored);
+    events.publish(ProofEvent::new(ProofEventKind::Created, &proofs[&proof.proof_id]));
+    Ok(proof)
+}
+
+// A second proof for a transaction that already has a live one
+#[derive(Debug, Clone)]
+pub struct DuplicateDeposit {
+    pub proof_id: String,
+}
+
+impl std::fmt::Display for DuplicateDeposit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "proof {} already claims this transaction", self.proof_id)
+    }
+}
+
+impl std::error::Error for DuplicateDeposit {}
+
+pub fn is_duplicate_deposit(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<DuplicateDeposit>())
+}
+
+// Whether `proof` is a live proof of `deposit`'s transaction. Committed
+// proofs hide their tx id, so they match by the preimage or, once attested,
+// by their LockProof.
+fn claims_deposit(proof: &TransactionProof, deposit: &ProofPreimage) -> bool {
+    let live = matches!(
+        proof.status,
+        ProofStatus::Created | ProofStatus::Validated | ProofStatus::Attested | ProofStatus::Minted
+    );
+    let tx_id = deposit.tx_id.trim();
+    let attested = |attestation: &Attestation| hex::encode(attestation.lock_proof.monero_tx).eq_ignore_ascii_case(tx_id);
+    live && ((!proof.tx_id.is_empty() && proof.tx_id.eq_ignore_ascii_case(tx_id))
+        || proof.attestation.as_ref().is_some_and(attested)
+        || (proof.commitment.is_some() && privacy::opens(proof, deposit)))
+}
+
+// Outcome of `validate`; `amount` is the total of `outputs` and `proof` the
+// matching stored proof after the update, revealed if it is committed
+#[derive(Debug, Clone)]
+pub struct Validation {
+    pub valid: bool,
+    pub amount: Option<Piconero>,
+    pub outputs: Vec<MatchedOutput>,
+    pub proof: Option<TransactionProof>,
+    pub network: Network,
+}
+
+impl Validation {
+    // The `/v1/validate` response
+    pub fn response(&self) -> ValidationResponse {
+        let message = if self.valid {
+            "Transaction validated successfully using Monero RPC"
+        } else {
+            "Transaction validation failed"
+        };
+        ValidationResponse {
+            valid: self.valid,
+            amount: self.amount,
+            message: message.to_string(),
+        }
+    }
+}
+
+// Checks the tx key against the daemons, audits the decision and records
+// it on the matching stored proof, if any
+pub async fn validate(
+    monero_service: &MoneroService,
+    storage: &ProofStore,
+    events: &EventBus,
+    audit: &AuditLog,
+    request: &ValidationRequest,
+) -> Result<Validation> {
+    let tx_key = request.tx_keys()?;
+    monero_service
+        .network
+        .check_address(&request.recipient_address)
+        .map_err(|e| e.context("Invalid recipient address"))?;
+    let check = monero_service
+        .check_tx_key(&request.tx_id, &tx_key, &request.recipient_address)
+        .await
+        .map_err(|e| e.context("Failed to validate transaction"))?;
+    let outputs = check.outputs;
+    let valid = !outputs.is_empty();
+    // Null rather than 0 when nothing paid the recipient, as `/v1` always answered
+    let amount = if valid { Some(scanner::total(&outputs)?) } else { None };
+    // Followed by `finality` until it is deep enough; unknown while in the pool
+    let block = match check.block_height.filter(|_| valid) {
+        Some(height) => Some(BlockRef {
+            height,
+            hash: monero_service
+                .block_hash(height)
+                .await
+                .map_err(|e| e.context("Failed to look up the deposit's block"))?,
+            finalized: false,
+        }),
+        None => None,
+    };
+
+    let preimage = ProofPreimage {
+        tx_id: request.tx_id.clone(),
+        tx_key: tx_key.concatenated(),
+        recipient_address: request.recipient_address.clone(),
+    };
+    // Private stores keep the tx key and recipient out of the audit log too
+    let logged_request = if storage.is_private() {
+        json!({ "deposit": privacy::fingerprint(&preimage) })
+    } else {
+        json!(request)
+    };
+    let local_proof = {
+        let mut proofs = storage.write().await;
+        let proof = proofs.values_mut().find(|proof| privacy::opens(proof, &preimage));
+        let data = json!({
+            "request": logged_request,
+            "rpc": { "get_transactions": check.evidence },
+            "valid": valid,
+            "amount": amount,
+            "outputs": outputs,
+            "block": block,
+        });
+        audit
+            .record(AuditKind::Validation, proof.as_ref().map(|proof| proof.proof_id.as_str()), data)
+            .await
+            .map_err(|e| e.context("Failed to record audit entry"))?;
+        proof.map(|proof| {
+            let (status, kind) = if valid {
+                (ProofStatus::Validated, ProofEventKind::Validated)
+            } else {
+                (ProofStatus::Invalidated, ProofEventKind::Invalidated)
+            };
+            // The sum of the matched outputs is what gets attested
+            if valid {
+                proof.amount = amount;
+                proof.flags.retain(|flag| *flag != MintFlag::Orphaned);
+            }
+            proof.block = block.clone();
+            // An attested or minted proof keeps its status unless it stops validating
+            let keep = matches!(proof.status, ProofStatus::Attested | ProofStatus::Minted) && valid;
+            if proof.status != status && !keep {
+                proof.set_status(status);
+                events.publish(ProofEvent::new(kind, proof));
+            }
+            privacy::reveal(proof, &preimage).unwrap_or_else(|| proof.clone())
+        })
+    };
+
+    Ok(Validation {
+        valid,
+        amount,
+        outputs,
+        proof: local_proof,
+        network: monero_service.network,
+    })
+}
+
+// Block timestamps on the router's chain may trail this clock, so an
+// attestation counts as live for this long past its expiry
+pub const EXPIRY_MARGIN_SECS: u64 = 60;
+
+// Whether `attestation` may still be minted at `now`
+pub fn is_live(attestation: &Attestation, now: u64) -> bool {
+    now <= attestation.lock_proof.expiry.saturating_add(EXPIRY_MARGIN_SECS)
+}
+
+// Attestations that may still be minted, the soonest to expire first
+pub fn outstanding<'a>(proofs: impl IntoIterator<Item = &'a TransactionProof>, now: u64) -> Vec<OutstandingAttestation> {
+    let mut outstanding: Vec<OutstandingAttestation> = proofs
+        .into_iter()
+        .filter(|proof| proof.status == ProofStatus::Attested)
+        .filter_map(|proof| {
+            let attestation = proof.attestation.as_ref().filter(|a| is_live(a, now))?;
+            Some(OutstandingAttestation {
+                proof_id: proof.proof_id.clone(),
+                lock_proof: attestation.lock_proof.clone(),
+                issued_at: attestation.issued_at,
+                expires_in_secs: attestation.lock_proof.expiry.saturating_sub(now),
+            })
+        })
+        .collect();
+    outstanding.sort_by_key(|a| a.lock_proof.expiry);
+    outstanding
+}
+
+#[derive(Debug)]
+pub enum AttestError {
+    NotConfigured,
+    InvalidDestination(anyhow::Error),
+    NotFound(String),
+    // Another proof of the same transaction was attested
+    DuplicateDeposit(String),
+    // The request did not prove it comes from the depositor
+    PreimageRequired,
+    PreimageMismatch,
+    AlreadyAttested,
+    // The deposit's block was orphaned; validate again once it is mined
+    Orphaned,
+    // The deposit pays an address of another network than the attestor's
+    WrongNetwork(anyhow::Error),
+    // The deposit pays another address than `bridge.deposit_address`
+    NotBridgeDeposit,
+    // The current attestation, live until this expiry
+    StillLive(u64),
+    // A re-issue asked for another destination than this one
+    ReissueDestination(String),
+    NotValidated,
+    UnknownAmount,
+    Policy(String),
+    Conversion(anyhow::Error),
+    InvalidTransactionId(anyhow::Error),
+    Signing(anyhow::Error),
+    Threshold(anyhow::Error),
+    Audit(anyhow::Error),
+}
+
+impl AttestError {
+    // Short title used as the `error` field of API responses
+    pub fn title(&self) -> &'static str {
+        match self {
+            AttestError::NotConfigured => "Attestor not configured",
+            AttestError::InvalidDestination(_) => "Invalid destination",
+            AttestError::NotFound(_) => "Proof not found",
+            AttestError::DuplicateDeposit(_) => "Deposit already attested",
+            AttestError::PreimageRequired => "Preimage required",
+            AttestError::PreimageMismatch => "Preimage mismatch",
+            AttestError::AlreadyAttested => "Proof already attested",
+            AttestError::Orphaned => "Deposit block orphaned",
+            AttestError::WrongNetwork(_) => "Wrong network",
+            AttestError::NotBridgeDeposit => "Not a bridge deposit",
+            AttestError::StillLive(_) => "Attestation still live",
+            AttestError::ReissueDestination(_) => "Destination differs from the expired attestation",
+            AttestError::NotValidated => "Proof not validated",
+            AttestError::UnknownAmount => "Unknown amount",
+            AttestError::Policy(_) => "Rejected by policy",
+            AttestError::Conversion(_) => "Amount not convertible",
+            AttestError::InvalidTransactionId(_) => "Invalid transaction id",
+            AttestError::Signing(_) => "Failed to sign attestation",
+            AttestError::Threshold(_) => "Attestation threshold not reached",
+            AttestError::Audit(_) => "Failed to record audit entry",
+        }
+    }
+}
+
+impl std::fmt::Display for AttestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttestError::NotConfigured => {
+                write!(f, "Set ATTESTOR_PRIVKEY, ROUTER and bridge.deposit_address to enable attestations")
+            }
+            AttestError::InvalidDestination(e)
+            | AttestError::WrongNetwork(e)
+            | AttestError::InvalidTransactionId(e)
+            | AttestError::Signing(e) => write!(f, "{}", e),
+            AttestError::Conversion(e) | AttestError::Threshold(e) | AttestError::Audit(e) => write!(f, "{:#}", e),
+            AttestError::Policy(reason) => write!(f, "{}", reason),
+            AttestError::NotFound(proof_id) => write!(f, "{}", proof_id),
+            AttestError::DuplicateDeposit(proof_id) => {
+                write!(f, "The transaction was attested through proof {}; a deposit mints once", proof_id)
+            }
+            AttestError::PreimageRequired => {
+                write!(f, "Send the tx_id, tx_key and recipient_address returned when the proof was created as preimage")
+            }
+            AttestError::PreimageMismatch => write!(f, "The preimage does not match this proof's deposit"),
+            AttestError::AlreadyAttested => write!(f, "An attestation has already been issued for this proof"),
+            AttestError::Orphaned => {
+                write!(f, "The deposit's block left the main chain; validate the proof again once it is mined")
+            }
+            AttestError::StillLive(expiry) => write!(
+                f,
+                "The current attestation expires at {}; a new one can be issued once it expired unused",
+                expiry
+            ),
+            AttestError::ReissueDestination(dest) => {
+                write!(f, "A re-issued attestation keeps the lockId, so it must pay {}", dest)
+            }
+            AttestError::NotValidated => write!(f, "Validate the transaction before requesting an attestation"),
+            AttestError::UnknownAmount => write!(f, "The proof has no received amount to attest"),
+            AttestError::NotBridgeDeposit => write!(f, "The transaction does not pay the bridge's deposit address"),
+        }
+    }
+}
+
+impl std::error::Error for AttestError {}
+
+// Signs a LockProof for a validated proof and marks it attested
+pub async fn attest(
+    attestor: Option<&Attestor>,
+    policy: &Policy,
+    storage: &ProofStore,
+    events: &EventBus,
+    audit: &AuditLog,
+    proof_id: &str,
+    request: &AttestRequest,
+) -> Result<Attestation, AttestError> {
+    let attestor = attestor.ok_or(AttestError::NotConfigured)?;
+    let deposit_address = attestor.deposit_address.as_deref().ok_or(AttestError::NotConfigured)?;
+    let dest = attestation::parse_address(&request.dest).map_err(AttestError::InvalidDestination)?;
+
+    // The LockProof is decided under the lock; peers are asked without it
+    let now = unix_now();
+    let (lock_proof, decision, converted, deposit, replaced) = {
+        let mut storage = storage.write().await;
+        // A re-issue replaces this proof's own attestation in the daily usage
+        let usage = DailyUsage::of(storage.values().filter(|proof| proof.proof_id != proof_id), &dest, now);
+        let proof = storage
+            .get(proof_id)
+            .ok_or_else(|| AttestError::NotFound(proof_id.to_string()))?;
+        // Only the creator got the tx key, so the preimage proves the deposit
+        // is theirs. Checked first so nothing is told to whoever lacks it.
+        let deposit = match &request.preimage {
+            None => return Err(AttestError::PreimageRequired),
+            Some(preimage) if !privacy::opens(proof, preimage) => return Err(AttestError::PreimageMismatch),
+            Some(preimage) if proof.commitment.is_some() => preimage.clone(),
+            Some(_) => ProofPreimage::of(proof),
+        };
+        // Imported proofs may come from a server on another network
+        attestor
+            .network
+            .check_address(&deposit.recipient_address)
+            .map_err(AttestError::WrongNetwork)?;
+        // Anything else the tx key proves was paid to someone else
+        if deposit.recipient_address != deposit_address {
+            return Err(AttestError::NotBridgeDeposit);
+        }
+        // Proofs imported from another store may share a transaction
+        let attested = storage.values().find(|other| {
+            other.proof_id != proof_id
+                && matches!(other.status, ProofStatus::Attested | ProofStatus::Minted)
+                && claims_deposit(other, &deposit)
+        });
+        if let Some(other) = attested {
+            return Err(AttestError::DuplicateDeposit(other.proof_id.clone()));
+        }
+        let proof = storage
+            .get_mut(proof_id)
+            .ok_or_else(|| AttestError::NotFound(proof_id.to_string()))?;
+        // An attestation that expired unused is replaced by a fresh one for
+        // the same lockId, which the router still accepts only once
+        let replaced = match (proof.status, &proof.attestation) {
+            (ProofStatus::Validated, _) => None,
+            (ProofStatus::Attested, Some(current)) if is_live(current, now) => {
+                return Err(AttestError::StillLive(current.lock_proof.expiry));
+            }
+            (ProofStatus::Attested, Some(current)) if current.lock_proof.dest != dest => {
+                return Err(AttestError::ReissueDestination(attestation::format_hex(&current.lock_proof.dest)));
+            }
+            (ProofStatus::Attested, Some(current)) => Some(current.clone()),
+            (ProofStatus::Attested | ProofStatus::Minted, _) => return Err(AttestError::AlreadyAttested),
+            (ProofStatus::Deleted, _) => return Err(AttestError::NotFound(proof_id.to_string())),
+            _ => return Err(AttestError::NotValidated),
+        };
+        if proof.flags.contains(&MintFlag::Orphaned) {
+            return Err(AttestError::Orphaned);
+        }
+        let amount = proof.amount.filter(|a| !a.is_zero()).ok_or(AttestError::UnknownAmount)?;
+
+        // Refusals are recorded too, so the proof shows why it was not attested
+        let decision = policy.evaluate(amount, usage, now);
+        proof.policy = Some(decision.clone());
+        let refusal = json!({ "dest": attestation::format_hex(&dest), "policy": decision });
+        if let Some(reason) = decision.reason.clone() {
+            return Err(refuse(audit, proof_id, refusal, AttestError::Policy(reason)).await);
+        }
+        let converted = match attestor.conversion.convert(decision.deposit, decision.fee) {
+            Ok(converted) => converted,
+            Err(e) => return Err(refuse(audit, proof_id, refusal, AttestError::Conversion(e)).await),
+        };
+        let monero_tx = attestation::parse_hex32(&deposit.tx_id).map_err(AttestError::InvalidTransactionId)?;
+        let lock_proof = LockProof::new(monero_tx, dest, converted.wfoid, now + attestor.expiry_secs);
+        (lock_proof, decision, converted, deposit, replaced)
+    };
+
+    let signature = attestor.sign(&lock_proof).await.map_err(AttestError::Signing)?;
+    let previous_signature = attestor.sign_previous(&lock_proof, now).await.map_err(AttestError::Signing)?;
+    let own = PartialSignature {
+        attestor: attestation::format_hex(&attestor.address()),
+        signature: signature.clone(),
+    };
+    let signatures = match &attestor.coordinator {
+        Some(coordinator) => {
+            let request = CosignRequest {
+                lock_proof: lock_proof.clone(),
+                tx_id: deposit.tx_id.clone(),
+                tx_key: deposit.tx_key.clone(),
+                recipient_address: deposit.recipient_address.clone(),
+            };
+            match coordinator.collect(attestor, own.clone(), &request).await {
+                Ok(signatures) => signatures,
+                Err(e) => {
+                    // The signature is dropped with the attestation: whoever reads the
+                    // audit log must not be able to mint with it
+                    let refusal = json!({ "lock_id": attestation::format_hex(&request.lock_proof.lock_id) });
+                    return Err(refuse(audit, proof_id, refusal, AttestError::Threshold(e)).await);
+                }
+            }
+        }
+        None => vec![own.clone()],
+    };
+    let attestation = Attestation {
+        lock_proof,
+        signature,
+        attestor: own.attestor,
+        issued_at: now,
+        conversion: Some(converted),
+        signatures,
+        previous_signature,
+        network: Some(attestor.network),
+    };
+
+    let mut storage = storage.write().await;
+    // Attestations committed while peers were signing count towards the caps
+    // and may claim the deposit, so both are decided again
+    let usage = DailyUsage::of(storage.values().filter(|proof| proof.proof_id != proof_id), &dest, now);
+    let attested = storage.values().find(|other| {
+        other.proof_id != proof_id
+            && matches!(other.status, ProofStatus::Attested | ProofStatus::Minted)
+            && claims_deposit(other, &deposit)
+    });
+    if let Some(other) = attested {
+        return Err(AttestError::DuplicateDeposit(other.proof_id.clone()));
+    }
+    let proof = storage
+        .get_mut(proof_id)
+        .ok_or_else(|| AttestError::NotFound(proof_id.to_string()))?;
+    // Another request may have attested while peers were signing
+    let current = proof.attestation.as_ref().map(|a| &a.signature);
+    let expected = replaced.as_ref().map(|a| &a.signature);
+    let expected_status = if replaced.is_some() { ProofStatus::Attested } else { ProofStatus::Validated };
+    if proof.status != expected_status || current != expected {
+        return Err(AttestError::AlreadyAttested);
+    }
+    let recheck = policy.evaluate(decision.deposit, usage, now);
+    if let Some(reason) = recheck.reason.clone() {
+        proof.policy = Some(recheck.clone());
+        let refusal = json!({ "dest": attestation::format_hex(&dest), "policy": recheck });
+        return Err(refuse(audit, proof_id, refusal, AttestError::Policy(reason)).await);
+    }
+    // Nothing is released without its audit entry
+    let data = json!({
+        "policy": decision,
+        "conversion": attestation.conversion,
+        "lock_proof": attestation.lock_proof,
+        "signer": audit::signer(attestor),
+        "signature": attestation.signature,
+        "signatures": attestation.signatures,
+        "previous_signature": attestation.previous_signature,
+        "replaces": replaced.map(|r| json!({ "signature": r.signature, "expiry": r.lock_proof.expiry })),
+    });
+    audit
+        .record(AuditKind::Attestation, Some(proof_id), data)
+        .await
+        .map_err(AttestError::Audit)?;
+    proof.set_status(ProofStatus::Attested);
+    proof.attestation = Some(attestation.clone());
+    proof.flags.retain(|flag| *flag != MintFlag::ExpiredUnused);
+    events.publish(ProofEvent::new(ProofEventKind::Attested, proof));
+    Ok(attestation)
+}
+
+// Audits a refused attestation and returns `error`, or the audit failure
+async fn refuse(audit: &AuditLog, proof_id: &str, mut data: Value, error: AttestError) -> AttestError {
+    data["reason"] = json!(error.to_string());
+    match audit.record(AuditKind::AttestationRefused, Some(proof_id), data).await {
+        Ok(()) => error,
+        Err(e) => AttestError::Audit(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::TxKey;
+    use crate::config::MoneroConfig;
+    use crate::testing::StubDaemon;
+
+    fn service(daemon: StubDaemon) -> MoneroService {
+        let daemon = daemon.spawn();
+        MoneroService::new(&MoneroConfig {
+            daemons: vec![daemon.clone()],
+            wallet: Some(daemon),
+            ..MoneroConfig::default()
+        })
+        .unwrap()
+    }
+
+    // Signs deposits to `testing::ADDRESS`, which `StubDaemon` pays
+    fn attestor() -> Attestor {
+        Attestor::new(&"4c".repeat(32), "0x0000000000000000000000000000000000000001", 20994)
+            .unwrap()
+            .with_deposit_address(Some(crate::testing::ADDRESS.to_string()))
+    }
+
+    #[tokio::test]
+    async fn subaddress_deposits_validate_with_their_additional_keys() {
+        let subaddress = scanner::test_address(42, 7).encode();
+        let monero_service = service(StubDaemon {
+            recipient: subaddress.clone(),
+            additional_keys: true,
+            ..StubDaemon::default()
+        });
+        let (storage, events) = (ProofStore::in_memory(), EventBus::new(4));
+        let request = ProofRequest {
+            tx_id: "ab".repeat(32),
+            recipient_address: subaddress.clone(),
+        };
+
+        let proof = create_proof(&monero_service, &storage, &events, &request).await.unwrap();
+        let tx_key = TxKey::parse(&proof.tx_key).unwrap();
+        // One per output: the change and the deposit
+        assert_eq!(tx_key.additional.len(), 2);
+
+        // The amount is only known once an output is decrypted
+        assert_eq!(proof.amount, None);
+
+        // The main key alone finds nothing for a subaddress, and no amount
+        let mut validation = ValidationRequest {
+            tx_id: request.tx_id,
+            tx_key: tx_key.main.clone(),
+            recipient_address: subaddress,
+            additional_tx_keys: Vec::new(),
+        };
+        let result = validate(&monero_service, &storage, &events, &AuditLog::disabled(), &validation).await.unwrap();
+        assert!(!result.valid);
+        assert_eq!(serde_json::to_value(result.response()).unwrap()["amount"], serde_json::Value::Null);
+
+        // Passed separately, the additional keys still match the stored proof
+        validation.additional_tx_keys = tx_key.additional;
+        let result = validate(&monero_service, &storage, &events, &AuditLog::disabled(), &validation).await.unwrap();
+        assert!(result.valid);
+        assert_eq!(result.proof.unwrap().status, ProofStatus::Validated);
+    }
+
+    #[tokio::test]
+    async fn multiple_outputs_to_the_recipient_are_listed_and_summed() {
+        let monero_service = service(StubDaemon {
+            outputs: vec![700_000, 300_000, 25],
+            ..StubDaemon::default()
+        });
+        let (storage, events) = (ProofStore::in_memory(), EventBus::new(4));
+        let request = ProofRequest {
+            tx_id: "ab".repeat(32),
+            recipient_address: crate::testing::ADDRESS.to_string(),
+        };
+        let proof = create_proof(&monero_service, &storage, &events, &request).await.unwrap();
+
+        let validation = ValidationRequest {
+            tx_id: request.tx_id,
+            tx_key: proof.tx_key,
+            recipient_address: request.recipient_address,
+            additional_tx_keys: Vec::new(),
+        };
+        let result = validate(&monero_service, &storage, &events, &AuditLog::disabled(), &validation).await.unwrap();
+        let outputs: Vec<(u64, u64)> = result.outputs.iter().map(|o| (o.index, o.amount.0)).collect();
+        // Index 0 is the sender's change
+        assert_eq!(outputs, [(1, 700_000), (2, 300_000), (3, 25)]);
+        assert_eq!(result.amount, Some(Piconero(1_000_025)));
+        // Attestations sign the stored amount
+        assert_eq!(result.proof.unwrap().amount, Some(Piconero(1_000_025)));
+    }
+
+    #[tokio::test]
+    async fn policy_decisions_are_recorded_and_fees_reduce_the_mint() {
+        let monero_service = service(StubDaemon::default());
+        let (storage, events) = (ProofStore::in_memory(), EventBus::new(4));
+        let request = ProofRequest {
+            tx_id: "ab".repeat(32),
+            recipient_address: crate::testing::ADDRESS.to_string(),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let audit_path = dir.path().join("audit.jsonl");
+        let audit = AuditLog::open(&audit_path).unwrap();
+        let proof = create_proof(&monero_service, &storage, &events, &request).await.unwrap();
+        let validation = ValidationRequest {
+            tx_id: request.tx_id,
+            tx_key: proof.tx_key.clone(),
+            recipient_address: request.recipient_address,
+            additional_tx_keys: Vec::new(),
+        };
+        validate(&monero_service, &storage, &events, &audit, &validation).await.unwrap();
+        let attestor = attestor();
+        let dest = AttestRequest {
+            dest: "0x0000000000000000000000000000000000000002".to_string(),
+            preimage: Some(ProofPreimage::of(&proof)),
+        };
+
+        // 1_000_000 piconero is below a 1 XMR minimum
+        let strict = Policy {
+            min_deposit: Some("1".parse().unwrap()),
+            ..Policy::default()
+        };
+        let refused = attest(Some(&attestor), &strict, &storage, &events, &audit, &proof.proof_id, &dest).await;
+        assert!(matches!(refused, Err(AttestError::Policy(_))));
+        let stored = storage.read().await[&proof.proof_id].clone();
+        assert_eq!(stored.policy.unwrap().outcome, crate::policy::PolicyOutcome::Rejected);
+        assert_eq!(stored.status, ProofStatus::Validated);
+
+        let with_fee = Policy {
+            flat_fee: Piconero(100_000),
+            ..Policy::default()
+        };
+        let attestation = attest(Some(&attestor), &with_fee, &storage, &events, &audit, &proof.proof_id, &dest)
+            .await
+            .unwrap();
+        // 900_000 piconero net, at 1:1 and 18 decimals
+        assert_eq!(attestation.lock_proof.amount, 900_000_000_000);
+        let stored = storage.read().await[&proof.proof_id].clone();
+        assert_eq!(stored.policy.unwrap().net, Piconero(900_000));
+
+        // Each decision is in the audit log, with the RPC evidence and signature
+        assert_eq!(audit::verify(&audit_path).unwrap().entries, 3);
+        let entries: Vec<audit::AuditEntry> = std::fs::read_to_string(&audit_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let kinds: Vec<AuditKind> = entries.iter().map(|entry| entry.kind).collect();
+        assert_eq!(kinds, [AuditKind::Validation, AuditKind::AttestationRefused, AuditKind::Attestation]);
+        assert!(entries[0].data["rpc"]["get_transactions"]["vout"].is_array());
+        assert!(entries[1].data["reason"].as_str().unwrap().contains("minimum"));
+        assert_eq!(entries[2].data["signature"], attestation.signature);
+        // Without peers the aggregate the router takes is this node's signature alone
+        assert_eq!(entries[2].data["signatures"], json!([{ "attestor": attestation.attestor, "signature": attestation.signature }]));
+    }
+
+    // Yields before signing, as a remote signer would
+    struct YieldingSigner(crate::signer::LocalSigner);
+
+    impl crate::signer::Signer for YieldingSigner {
+        fn address(&self) -> [u8; 20] {
+            self.0.address()
+        }
+
+        fn sign_digest(&self, digest: [u8; 32]) -> futures_util::future::BoxFuture<'_, anyhow::Result<[u8; 65]>> {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                self.0.sign_digest(digest).await
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_attestations_do_not_overrun_the_daily_caps() {
+        let monero_service = service(StubDaemon::default());
+        let (storage, events, audit) = (ProofStore::in_memory(), EventBus::new(8), AuditLog::disabled());
+        let mut requests = Vec::new();
+        for tx_id in ["ab".repeat(32), "cd".repeat(32)] {
+            let request = ProofRequest { tx_id, recipient_address: crate::testing::ADDRESS.to_string() };
+            let proof = create_proof(&monero_service, &storage, &events, &request).await.unwrap();
+            let validation = ValidationRequest {
+                tx_id: request.tx_id,
+                tx_key: proof.tx_key.clone(),
+                recipient_address: request.recipient_address,
+                additional_tx_keys: Vec::new(),
+            };
+            validate(&monero_service, &storage, &events, &audit, &validation).await.unwrap();
+            let dest = "0x0000000000000000000000000000000000000002".to_string();
+            requests.push((proof.proof_id.clone(), AttestRequest { dest, preimage: Some(ProofPreimage::of(&proof)) }));
+        }
+        let signer = crate::signer::LocalSigner::from_hex(&"4c".repeat(32)).unwrap();
+        let attestor = Attestor::with_signer(
+            std::sync::Arc::new(YieldingSigner(signer)),
+            "0x0000000000000000000000000000000000000001",
+            20994,
+        )
+        .unwrap()
+        .with_deposit_address(Some(crate::testing::ADDRESS.to_string()));
+        // Room for one 1_000_000 piconero deposit, not two
+        let policy = Policy { daily_cap_per_dest: Some("0.0000015".parse().unwrap()), ..Policy::default() };
+
+        // Both pass the first check before either is stored
+        let [(first_id, first), (second_id, second)] = &requests[..] else { unreachable!() };
+        let (first, second) = tokio::join!(
+            attest(Some(&attestor), &policy, &storage, &events, &audit, first_id, first),
+            attest(Some(&attestor), &policy, &storage, &events, &audit, second_id, second),
+        );
+        // Whichever is stored first takes the room
+        let (refused, refused_id) = match (first, second) {
+            (Ok(_), refused) => (refused, second_id),
+            (refused, Ok(_)) => (refused, first_id),
+            both => panic!("{:?}", both),
+        };
+        assert!(matches!(&refused, Err(AttestError::Policy(reason)) if reason.contains("daily cap")), "{:?}", refused);
+        let stored = storage.read().await[refused_id].clone();
+        assert_eq!(stored.status, ProofStatus::Validated);
+        assert_eq!(stored.policy.unwrap().outcome, crate::policy::PolicyOutcome::Rejected);
+    }
+
+    #[tokio::test]
+    async fn attestations_are_reissued_only_after_they_expire_unused() {
+        let monero_service = service(StubDaemon::default());
+        let (storage, events, audit) = (ProofStore::in_memory(), EventBus::new(8), AuditLog::disabled());
+        let request = ProofRequest {
+            tx_id: "cd".repeat(32),
+            recipient_address: crate::testing::ADDRESS.to_string(),
+        };
+        let proof = create_proof(&monero_service, &storage, &events, &request).await.unwrap();
+        let validation = ValidationRequest {
+            tx_id: request.tx_id,
+            tx_key: proof.tx_key.clone(),
+            recipient_address: request.recipient_address,
+            additional_tx_keys: Vec::new(),
+        };
+        validate(&monero_service, &storage, &events, &audit, &validation).await.unwrap();
+        let mut attestor = attestor();
+        attestor.expiry_secs = 600;
+        let (dest, other) = ("0x0000000000000000000000000000000000000002", "0x0000000000000000000000000000000000000003");
+        let policy = Policy::default();
+        let attest = |dest: &str| {
+            let request = AttestRequest { dest: dest.to_string(), preimage: Some(ProofPreimage::of(&proof)) };
+            let (attestor, policy, storage, events, audit) = (&attestor, &policy, &storage, &events, &audit);
+            let proof_id = &proof.proof_id;
+            async move { attest(Some(attestor), policy, storage, events, audit, proof_id, &request).await }
+        };
+
+        let first = attest(dest).await.unwrap();
+        assert_eq!(first.lock_proof.expiry, first.issued_at + 600);
+        assert!(matches!(attest(dest).await, Err(AttestError::StillLive(expiry)) if expiry == first.lock_proof.expiry));
+        let now = unix_now();
+        assert_eq!(outstanding(storage.read().await.values(), now).len(), 1);
+
+        // Past its expiry and the margin, as if the reconciler found it unused
+        {
+            let mut proofs = storage.write().await;
+            let stored = proofs.get_mut(&proof.proof_id).unwrap();
+            stored.attestation.as_mut().unwrap().lock_proof.expiry = now - EXPIRY_MARGIN_SECS - 1;
+            stored.flags.push(MintFlag::ExpiredUnused);
+        }
+        assert!(outstanding(storage.read().await.values(), now).is_empty());
+        assert!(matches!(attest(other).await, Err(AttestError::ReissueDestination(_))));
+        let reissued = attest(dest).await.unwrap();
+        assert_eq!(reissued.lock_proof.lock_id, first.lock_proof.lock_id);
+        assert!(reissued.lock_proof.expiry > now);
+        let stored = storage.read().await[&proof.proof_id].clone();
+        assert_eq!(stored.attestation.unwrap().signature, reissued.signature);
+        assert!(stored.flags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn attestations_that_expired_unused_leave_the_daily_caps() {
+        let monero_service = service(StubDaemon::default());
+        let (storage, events, audit) = (ProofStore::in_memory(), EventBus::new(8), AuditLog::disabled());
+        let mut proofs = Vec::new();
+        for tx_id in ["ab".repeat(32), "cd".repeat(32)] {
+            let request = ProofRequest { tx_id, recipient_address: crate::testing::ADDRESS.to_string() };
+            let proof = create_proof(&monero_service, &storage, &events, &request).await.unwrap();
+            let validation = ValidationRequest {
+                tx_id: request.tx_id,
+                tx_key: proof.tx_key.clone(),
+                recipient_address: request.recipient_address,
+                additional_tx_keys: Vec::new(),
+            };
+            validate(&monero_service, &storage, &events, &audit, &validation).await.unwrap();
+            proofs.push(proof);
+        }
+        let attestor = attestor();
+        // Room for one 1_000_000 piconero deposit, not two
+        let policy = Policy { daily_cap_per_dest: Some("0.0000015".parse().unwrap()), ..Policy::default() };
+        let attest = |proof: &TransactionProof| {
+            let dest = "0x0000000000000000000000000000000000000002".to_string();
+            let request = AttestRequest { dest, preimage: Some(ProofPreimage::of(proof)) };
+            let (attestor, policy, storage, events, audit) = (&attestor, &policy, &storage, &events, &audit);
+            let proof_id = proof.proof_id.clone();
+            async move { attest(Some(attestor), policy, storage, events, audit, &proof_id, &request).await }
+        };
+        let expire = |proof_id: &str, minted: bool| {
+            let (storage, proof_id) = (&storage, proof_id.to_string());
+            async move {
+                let mut proofs = storage.write().await;
+                let stored = proofs.get_mut(&proof_id).unwrap();
+                stored.attestation.as_mut().unwrap().lock_proof.expiry = unix_now() - EXPIRY_MARGIN_SECS - 1;
+                if minted {
+                    stored.set_status(ProofStatus::Minted);
+                }
+            }
+        };
+        let (first, second) = (&proofs[0], &proofs[1]);
+
+        attest(first).await.unwrap();
+        assert!(matches!(attest(second).await, Err(AttestError::Policy(_))));
+        // Expired unused, the first no longer counts
+        expire(&first.proof_id, false).await;
+        attest(second).await.unwrap();
+        // And its re-issue is held to the cap like any other attestation
+        assert!(matches!(attest(first).await, Err(AttestError::Policy(_))));
+        // Minted deposits count even once their LockProof expired
+        expire(&second.proof_id, true).await;
+        assert!(matches!(attest(first).await, Err(AttestError::Policy(_))));
+        {
+            let mut proofs = storage.write().await;
+            proofs.get_mut(&second.proof_id).unwrap().set_status(ProofStatus::Attested);
+        }
+        attest(first).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn private_stores_keep_only_commitments() {
+        let monero_service = service(StubDaemon::default());
+        let storage = ProofStore::in_memory().with_privacy(true).unwrap();
+        let (events, audit) = (EventBus::new(8), AuditLog::disabled());
+        let request = ProofRequest {
+            tx_id: "ef".repeat(32),
+            recipient_address: crate::testing::ADDRESS.to_string(),
+        };
+        // The caller gets the deposit data once, the store only its commitment
+        let proof = create_proof(&monero_service, &storage, &events, &request).await.unwrap();
+        let stored = storage.read().await[&proof.proof_id].clone();
+        assert!(stored.tx_id.is_empty() && stored.tx_key.is_empty() && stored.recipient_address.is_empty());
+        assert!(stored.commitment.is_some());
+
+        let validation = ValidationRequest {
+            tx_id: request.tx_id.clone(),
+            tx_key: proof.tx_key.clone(),
+            recipient_address: request.recipient_address.clone(),
+            additional_tx_keys: Vec::new(),
+        };
+        let result = validate(&monero_service, &storage, &events, &audit, &validation).await.unwrap();
+        let validated = result.proof.unwrap();
+        assert_eq!((validated.status, validated.tx_key), (ProofStatus::Validated, proof.tx_key.clone()));
+        assert!(storage.read().await[&proof.proof_id].tx_key.is_empty());
+
+        let attestor = attestor();
+        let mut attest_request = AttestRequest { dest: "0x0000000000000000000000000000000000000002".to_string(), preimage: None };
+        let policy = Policy::default();
+        let attest_with = |request: AttestRequest| {
+            let (attestor, policy, storage, events, audit) = (&attestor, &policy, &storage, &events, &audit);
+            let proof_id = &proof.proof_id;
+            async move { attest(Some(attestor), policy, storage, events, audit, proof_id, &request).await }
+        };
+        assert!(matches!(attest_with(attest_request.clone()).await, Err(AttestError::PreimageRequired)));
+        let preimage = ProofPreimage::of(&proof);
+        attest_request.preimage = Some(ProofPreimage { recipient_address: "4".to_string(), ..preimage.clone() });
+        assert!(matches!(attest_with(attest_request.clone()).await, Err(AttestError::PreimageMismatch)));
+        attest_request.preimage = Some(preimage);
+        let attestation = attest_with(attest_request.clone()).await.unwrap();
+        assert_eq!(hex::encode(attestation.lock_proof.monero_tx), request.tx_id);
+    }
+
+    #[tokio::test]
+    async fn addresses_and_attestors_of_another_network_are_refused() {
+        let recipient = scanner::test_address(53, 7).encode();
+        let daemon = StubDaemon {
+            nettype: "testnet".to_string(),
+            recipient: recipient.clone(),
+            ..StubDaemon::default()
+        }
+        .spawn();
+        let monero_service = MoneroService::new(&MoneroConfig {
+            network: Network::Testnet,
+            daemons: vec![daemon.clone()],
+            wallet: Some(daemon),
+            ..MoneroConfig::default()
+        })
+        .unwrap();
+        monero_service.pool.check_health().await;
+        monero_service.check_network().unwrap();
+        let (storage, events, audit) = (ProofStore::in_memory(), EventBus::new(8), AuditLog::disabled());
+
+        let mainnet = ProofRequest { tx_id: "ab".repeat(32), recipient_address: crate::testing::ADDRESS.to_string() };
+        let error = create_proof(&monero_service, &storage, &events, &mainnet).await.unwrap_err();
+        assert!(crate::network::is_wrong_network(&error), "{:#}", error);
+        assert!(storage.read().await.is_empty());
+
+        let request = ProofRequest { tx_id: "ab".repeat(32), recipient_address: recipient };
+        let proof = create_proof(&monero_service, &storage, &events, &request).await.unwrap();
+        let validation = ValidationRequest {
+            tx_id: request.tx_id,
+            tx_key: proof.tx_key.clone(),
+            recipient_address: request.recipient_address,
+            additional_tx_keys: Vec::new(),
+        };
+        let result = validate(&monero_service, &storage, &events, &audit, &validation).await.unwrap();
+        assert_eq!(result.network, Network::Testnet);
+
+        // A mainnet attestor never signs a testnet deposit
+        let attestor = attestor();
+        let request = AttestRequest {
+            dest: "0x0000000000000000000000000000000000000002".to_string(),
+            preimage: Some(ProofPreimage::of(&proof)),
+        };
+        let policy = Policy::default();
+        let refused = attest(Some(&attestor), &policy, &storage, &events, &audit, &proof.proof_id, &request).await;
+        assert!(matches!(refused, Err(AttestError::WrongNetwork(_))), "{:?}", refused);
+        let attestor = attestor.with_network(Network::Testnet);
+        // Nor a deposit to anyone but the bridge
+        let refused = attest(Some(&attestor), &policy, &storage, &events, &audit, &proof.proof_id, &request).await;
+        assert!(matches!(refused, Err(AttestError::NotBridgeDeposit)), "{:?}", refused);
+        let attestor = attestor.with_deposit_address(Some(proof.recipient_address.clone()));
+        let attestation = attest(Some(&attestor), &policy, &storage, &events, &audit, &proof.proof_id, &request)
+            .await
+            .unwrap();
+        assert_eq!(attestation.network, Some(Network::Testnet));
+    }
+
+    #[tokio::test]
+    async fn a_deposit_is_attested_to_one_destination_only() {
+        let monero_service = service(StubDaemon::default());
+        let (storage, events, audit) = (ProofStore::in_memory(), EventBus::new(8), AuditLog::disabled());
+        let request = ProofRequest {
+            tx_id: "ab".repeat(32),
+            recipient_address: crate::testing::ADDRESS.to_string(),
+        };
+        let mut created = events.subscribe();
+        let proof = create_proof(&monero_service, &storage, &events, &request).await.unwrap();
+        let error = create_proof(&monero_service, &storage, &events, &request).await.unwrap_err();
+        assert!(is_duplicate_deposit(&error), "{:#}", error);
+        // Only the stored proof was announced
+        assert_eq!(created.try_recv().unwrap().data.proof_id, proof.proof_id);
+        assert!(created.try_recv().is_err());
+        let validation = ValidationRequest {
+            tx_id: request.tx_id.clone(),
+            tx_key: proof.tx_key.clone(),
+            recipient_address: request.recipient_address.clone(),
+            additional_tx_keys: Vec::new(),
+        };
+        validate(&monero_service, &storage, &events, &audit, &validation).await.unwrap();
+
+        // A copy of the validated proof, as an import from another store would add
+        let mut copy = storage.read().await[&proof.proof_id].clone();
+        copy.proof_id = "copy".to_string();
+        storage.write().await.insert(copy.proof_id.clone(), copy);
+
+        let attestor = attestor();
+        let policy = Policy::default();
+        let attest_to = |proof_id: &str, dest: &str| {
+            let request = AttestRequest { dest: dest.to_string(), preimage: Some(ProofPreimage::of(&proof)) };
+            let (attestor, policy, storage, events, audit) = (&attestor, &policy, &storage, &events, &audit);
+            let proof_id = proof_id.to_string();
+            async move { attest(Some(attestor), policy, storage, events, audit, &proof_id, &request).await }
+        };
+        // Only the creator got the tx key; a listing of the proof is no preimage
+        let listed = privacy::public(&storage.read().await[&proof.proof_id]);
+        for preimage in [None, Some(ProofPreimage { tx_key: "cd".repeat(32), ..ProofPreimage::of(&listed) })] {
+            let request = AttestRequest { dest: "0x0000000000000000000000000000000000000003".to_string(), preimage };
+            let refused = attest(Some(&attestor), &policy, &storage, &events, &audit, &proof.proof_id, &request).await;
+            assert!(matches!(refused, Err(AttestError::PreimageRequired | AttestError::PreimageMismatch)), "{:?}", refused);
+        }
+
+        let first = attest_to(&proof.proof_id, "0x0000000000000000000000000000000000000002").await.unwrap();
+        let second = attest_to("copy", "0x0000000000000000000000000000000000000003").await;
+        assert!(matches!(&second, Err(AttestError::DuplicateDeposit(id)) if *id == proof.proof_id), "{:?}", second);
+
+        // Even signed, a LockProof to another destination reuses the lockId
+        let other = LockProof::new(first.lock_proof.monero_tx, [0x03; 20], 1, 1);
+        assert_eq!(other.lock_id, first.lock_proof.lock_id);
+    }
+}
@@ -0,0 +1,441 @@
+// Follows BridgeRouter on the Fluent chain to learn what became of each
+// attestation. `Minted` logs mark proofs minted; a mint for another amount
+// than the one attested, or an attestation that expired without
+// `consumed(lockId)` ever being set, is flagged on the proof.
+use anyhow::{Context, Result, anyhow, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use crate::api::ProofStatus;
+use crate::attestation::{self, u128_string};
+use crate::config::FluentConfig;
+use crate::events::{EventBus, ProofEvent, ProofEventKind};
+use crate::storage::ProofStorage;
+
+// event Minted(bytes32 lockId, address dest, uint256 amount, address attestor)
+const MINTED_EVENT: &str = "Minted(bytes32,address,uint256,address)";
+const CONSUMED_FUNCTION: &str = "consumed(bytes32)";
+const REGISTRY_FUNCTION: &str = "registry()";
+const THRESHOLD_FUNCTION: &str = "threshold()";
+
+// The `Minted` event that consumed a proof's lockId
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct MintRecord {
+    pub tx_hash: String,
+    pub block_number: u64,
+    #[serde(with = "u128_string")]
+    #[schema(value_type = String)]
+    pub amount: u128,
+    pub attestor: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MintFlag {
+    // Past `lock_proof.expiry` and never minted; it can no longer be
+    ExpiredUnused,
+    // Minted for another amount than `lock_proof.amount`
+    AmountMismatch,
+    // The deposit's block left the main chain, see `finality`
+    Orphaned,
+}
+
+// Minimal Ethereum JSON-RPC client for the Fluent endpoint
+#[derive(Clone)]
+pub struct FluentRpc {
+    url: String,
+    timeout: Duration,
+    http: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct EthEnvelope<T> {
+    result: Option<T>,
+    error: Option<EthError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EthError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub number: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintedLog {
+    pub lock_id: [u8; 32],
+    pub dest: [u8; 20],
+    pub amount: u128,
+    pub attestor: [u8; 20],
+    pub block_number: u64,
+    pub tx_hash: String,
+}
+
+impl FluentRpc {
+    pub fn new(url: &str, timeout: Duration) -> Self {
+        Self {
+            url: url.to_string(),
+            timeout,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let envelope: EthEnvelope<T> = self
+            .http
+            .post(&self.url)
+            .timeout(self.timeout)
+            .json(&body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("request to {} failed", self.url))?
+            .json()
+            .await
+            .with_context(|| format!("invalid response from {}", self.url))?;
+        if let Some(error) = envelope.error {
+            bail!("{} failed: {} (code {})", method, error.message, error.code);
+        }
+        envelope.result.ok_or_else(|| anyhow!("{} returned no result", method))
+    }
+
+    pub async fn latest_block(&self) -> Result<BlockHeader> {
+        #[derive(Deserialize)]
+        struct Block {
+            number: String,
+            timestamp: String,
+        }
+        let block: Block = self.call("eth_getBlockByNumber", json!(["latest", false])).await?;
+        Ok(BlockHeader {
+            number: quantity(&block.number)?,
+            timestamp: quantity(&block.timestamp)?,
+        })
+    }
+
+    // `Minted` logs of `router` in blocks `from..=to`
+    pub async fn minted_logs(&self, router: &[u8; 20], from: u64, to: u64) -> Result<Vec<MintedLog>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Log {
+            data: String,
+            block_number: String,
+            transaction_hash: String,
+            #[serde(default)]
+            removed: bool,
+        }
+        let filter = json!({
+            "address": attestation::format_hex(router),
+            "fromBlock": format!("0x{:x}", from),
+            "toBlock": format!("0x{:x}", to),
+            "topics": [attestation::format_hex(&attestation::keccak256(MINTED_EVENT.as_bytes()))],
+        });
+        let logs: Vec<Log> = self.call("eth_getLogs", json!([filter])).await?;
+        logs.into_iter()
+            .filter(|log| !log.removed)
+            .map(|log| {
+                let data = hex::decode(log.data.trim_start_matches("0x")).context("Minted log data is not hex")?;
+                if data.len() != 128 {
+                    bail!("Minted log data is {} bytes, expected 128", data.len());
+                }
+                Ok(MintedLog {
+                    lock_id: word(&data, 0).try_into()?,
+                    dest: word(&data, 1)[12..].try_into()?,
+                    amount: uint(word(&data, 2)).context("Minted amount does not fit in 128 bits")?,
+                    attestor: word(&data, 3)[12..].try_into()?,
+                    block_number: quantity(&log.block_number)?,
+                    tx_hash: log.transaction_hash,
+                })
+            })
+            .collect()
+    }
+
+    // `router.consumed(lockId)` at the latest block
+    pub async fn consumed(&self, router: &[u8; 20], lock_id: &[u8; 32]) -> Result<bool> {
+        Ok(uint(&self.view(router, CONSUMED_FUNCTION, lock_id).await?)? != 0)
+    }
+
+    // `router.registry().threshold()`: distinct attestor operators the router
+    // needs signatures from per mint
+    pub async fn attestation_threshold(&self, router: &[u8; 20]) -> Result<u128> {
+        let registry: [u8; 20] = self.view(router, REGISTRY_FUNCTION, &[]).await?[12..].try_into()?;
+        uint(&self.view(&registry, THRESHOLD_FUNCTION, &[]).await?)
+    }
+
+    // A view function of `to` returning a single word, at the latest block
+    async fn view(&self, to: &[u8; 20], function: &str, args: &[u8]) -> Result<Vec<u8>> {
+        let mut data = attestation::keccak256(function.as_bytes())[..4].to_vec();
+        data.extend_from_slice(args);
+        let call = json!({ "to": attestation::format_hex(to), "data": attestation::format_hex(&data) });
+        let result: String = self.call("eth_call", json!([call, "latest"])).await?;
+        let result = hex::decode(result.trim_start_matches("0x")).with_context(|| format!("{} result is not hex", function))?;
+        if result.len() != 32 {
+            bail!("{} returned {} bytes, expected 32", function, result.len());
+        }
+        Ok(result)
+    }
+}
+
+// The `index`th 32-byte ABI word of `data`
+fn word(data: &[u8], index: usize) -> &[u8] {
+    &data[index * 32..(index + 1) * 32]
+}
+
+fn uint(word: &[u8]) -> Result<u128> {
+    if word[..16].iter().any(|byte| *byte != 0) {
+        bail!("value exceeds 128 bits");
+    }
+    Ok(u128::from_be_bytes(word[16..].try_into()?))
+}
+
+fn quantity(value: &str) -> Result<u64> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).with_context(|| format!("invalid quantity {}", value))
+}
+
+// What one reconciliation pass changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reconciled {
+    pub minted: usize,
+    pub flagged: usize,
+}
+
+#[derive(Clone)]
+pub struct Reconciler {
+    rpc: FluentRpc,
+    router: [u8; 20],
+    storage: ProofStorage,
+    events: EventBus,
+    max_block_range: u64,
+    // Next block to search for `Minted` logs
+    next_block: Arc<AtomicU64>,
+}
+
+impl Reconciler {
+    pub fn new(rpc_url: &str, router: [u8; 20], config: &FluentConfig, storage: ProofStorage, events: EventBus) -> Self {
+        Self {
+            rpc: FluentRpc::new(rpc_url, Duration::from_secs(config.timeout_secs)),
+            router,
+            storage,
+            events,
+            max_block_range: config.max_block_range.max(1),
+            next_block: Arc::new(AtomicU64::new(config.start_block)),
+        }
+    }
+
+    pub async fn attestation_threshold(&self) -> Result<u128> {
+        self.rpc.attestation_threshold(&self.router).await
+    }
+
+    pub async fn run(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.reconcile().await {
+                println!("⚠️  Mint reconciliation failed: {:#}", e);
+            }
+        }
+    }
+
+    // Reads the `Minted` logs up to the latest block, then checks the
+    // attestations that expired by its timestamp
+    pub async fn reconcile(&self) -> Result<Reconciled> {
+        let head = self.rpc.latest_block().await?;
+        let mut reconciled = Reconciled::default();
+        let mut from = self.next_block.load(Ordering::SeqCst);
+        while from <= head.number {
+            let to = head.number.min(from + self.max_block_range - 1);
+            for log in self.rpc.minted_logs(&self.router, from, to).await? {
+                self.record_mint(&log, &mut reconciled).await;
+            }
+            from = to + 1;
+            self.next_block.store(from, Ordering::SeqCst);
+        }
+
+        let expired: Vec<(String, [u8; 32])> = self
+            .storage
+            .read()
+            .await
+            .values()
+            .filter(|proof| proof.status == ProofStatus::Attested && !proof.flags.contains(&MintFlag::ExpiredUnused))
+            .filter_map(|proof| Some((proof.proof_id.clone(), proof.attestation.as_ref()?.lock_proof.clone())))
+            .filter(|(_, lock_proof)| lock_proof.expiry < head.timestamp)
+            .map(|(proof_id, lock_proof)| (proof_id, lock_proof.lock_id))
+            .collect();
+        for (proof_id, lock_id) in expired {
+            let consumed = self.rpc.consumed(&self.router, &lock_id).await?;
+            let mut storage = self.storage.write().await;
+            let Some(proof) = storage.get_mut(&proof_id) else { continue };
+            let unchanged = proof.status == ProofStatus::Attested
+                && proof.attestation.as_ref().is_some_and(|a| a.lock_proof.lock_id == lock_id);
+            if !unchanged {
+                continue;
+            }
+            if consumed {
+                // Minted before `start_block`, so there is no log to record
+                proof.set_status(ProofStatus::Minted);
+                self.events.publish(ProofEvent::new(ProofEventKind::Minted, proof));
+                reconciled.minted += 1;
+            } else {
+                println!("🚩 Attestation of proof {} expired unused", proof_id);
+                proof.flags.push(MintFlag::ExpiredUnused);
+                self.events.publish(ProofEvent::new(ProofEventKind::Flagged, proof));
+                reconciled.flagged += 1;
+            }
+        }
+        Ok(reconciled)
+    }
+
+    async fn record_mint(&self, log: &MintedLog, reconciled: &mut Reconciled) {
+        let mut storage = self.storage.write().await;
+        let proofs = storage
+            .values_mut()
+            .filter(|proof| proof.attestation.as_ref().is_some_and(|a| a.lock_proof.lock_id == log.lock_id));
+        let mut known = false;
+        for proof in proofs {
+            known = true;
+            // Logs are read again after a restart
+            if proof.mint.is_some() {
+                continue;
+            }
+            proof.mint = Some(MintRecord {
+                tx_hash: log.tx_hash.clone(),
+                block_number: log.block_number,
+                amount: log.amount,
+                attestor: attestation::format_hex(&log.attestor),
+            });
+            proof.flags.retain(|flag| *flag != MintFlag::ExpiredUnused);
+            if proof.status != ProofStatus::Minted {
+                println!("🪙 Proof {} minted in {}", proof.proof_id, log.tx_hash);
+                proof.set_status(ProofStatus::Minted);
+                self.events.publish(ProofEvent::new(ProofEventKind::Minted, proof));
+                reconciled.minted += 1;
+            }
+            let attested = proof.attestation.as_ref().map(|a| a.lock_proof.amount);
+            if attested != Some(log.amount) && !proof.flags.contains(&MintFlag::AmountMismatch) {
+                println!(
+                    "🚩 Proof {} attested {} but minted {}",
+                    proof.proof_id,
+                    attested.unwrap_or_default(),
+                    log.amount
+                );
+                proof.flags.push(MintFlag::AmountMismatch);
+                self.events.publish(ProofEvent::new(ProofEventKind::Flagged, proof));
+                reconciled.flagged += 1;
+            }
+        }
+        if !known {
+            println!("ℹ️  lockId {} was minted from an attestation this server did not issue", attestation::format_hex(&log.lock_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::TransactionProof;
+    use crate::attestation::{Attestation, LockProof};
+    use crate::storage::ProofStore;
+    use crate::testing::StubChain;
+
+    const ROUTER: [u8; 20] = [0x11; 20];
+    const ATTESTOR: [u8; 20] = [0x22; 20];
+
+    fn attested(id: &str, tx_byte: u8, amount: u128, expiry: u64) -> TransactionProof {
+        let lock_proof = LockProof::new([tx_byte; 32], [0x33; 20], amount, expiry);
+        TransactionProof {
+            tx_id: hex::encode([tx_byte; 32]),
+            tx_key: "aa".repeat(32),
+            recipient_address: crate::testing::ADDRESS.to_string(),
+            amount: None,
+            proof_id: id.to_string(),
+            status: ProofStatus::Attested,
+            updated_at: 0,
+            attestation: Some(Attestation {
+                lock_proof,
+                signature: "0x".to_string(),
+                attestor: attestation::format_hex(&ATTESTOR),
+                issued_at: 0,
+                conversion: None,
+                signatures: Vec::new(),
+                previous_signature: None,
+                network: None,
+            }),
+            policy: None,
+            mint: None,
+            flags: Vec::new(),
+            commitment: None,
+            block: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn mints_are_recorded_and_mismatches_and_expiries_flagged() {
+        let storage = Arc::new(ProofStore::in_memory());
+        let proofs = [
+            attested("exact", 1, 500, 9_000),
+            attested("short", 2, 500, 9_000),
+            attested("expired", 3, 500, 4_000),
+            attested("early", 4, 500, 4_000),
+            attested("pending", 5, 500, 9_000),
+        ];
+        let lock_ids: Vec<[u8; 32]> =
+            proofs.iter().map(|p| p.attestation.as_ref().unwrap().lock_proof.lock_id).collect();
+        storage.write().await.extend(proofs.map(|p| (p.proof_id.clone(), p)));
+
+        let chain = StubChain::new(ROUTER);
+        chain.set_head(10, 5_000);
+        chain.mint(3, lock_ids[0], 500, ATTESTOR);
+        chain.mint(7, lock_ids[1], 400, ATTESTOR);
+        // Below start_block, only visible through consumed()
+        chain.mint(0, lock_ids[3], 500, ATTESTOR);
+        let config = FluentConfig {
+            start_block: 1,
+            max_block_range: 4,
+            ..FluentConfig::default()
+        };
+        let events = EventBus::new(16);
+        let mut received = events.subscribe();
+        let reconciler = Reconciler::new(&chain.spawn(), ROUTER, &config, storage.clone(), events);
+
+        let reconciled = reconciler.reconcile().await.unwrap();
+        assert_eq!(reconciled, Reconciled { minted: 3, flagged: 2 });
+        let proofs = storage.read().await.clone();
+        assert_eq!(proofs["exact"].status, ProofStatus::Minted);
+        assert_eq!(proofs["exact"].mint.as_ref().unwrap().block_number, 3);
+        assert!(proofs["exact"].flags.is_empty());
+        assert_eq!(proofs["short"].mint.as_ref().unwrap().amount, 400);
+        assert_eq!(proofs["short"].flags, [MintFlag::AmountMismatch]);
+        assert_eq!(proofs["expired"].status, ProofStatus::Attested);
+        assert_eq!(proofs["expired"].flags, [MintFlag::ExpiredUnused]);
+        assert_eq!((proofs["early"].status, proofs["early"].mint.clone()), (ProofStatus::Minted, None));
+        assert_eq!((proofs["pending"].status, proofs["pending"].flags.len()), (ProofStatus::Attested, 0));
+        assert_eq!(received.recv().await.unwrap().kind, ProofEventKind::Minted);
+
+        // Another pass over the same chain changes nothing
+        assert_eq!(reconciler.reconcile().await.unwrap(), Reconciled::default());
+    }
+
+    #[tokio::test]
+    async fn the_threshold_is_read_through_the_routers_registry() {
+        let chain = StubChain::new(ROUTER);
+        chain.set_threshold(2);
+        let reconciler = Reconciler::new(
+            &chain.spawn(),
+            ROUTER,
+            &FluentConfig::default(),
+            Arc::new(ProofStore::in_memory()),
+            EventBus::new(16),
+        );
+        assert_eq!(reconciler.attestation_threshold().await.unwrap(), 2);
+    }
+}
@@ -0,0 +1,169 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+// Read-only RPC methods that are safe to retry. Anything not listed here
+// (e.g. `send_raw_transaction`, `transfer`, `relay_tx`) is attempted once.
+const IDEMPOTENT_METHODS: &[&str] = &[
+    "get_info",
+    "get_version",
+    "get_height",
+    "get_transactions",
+    "get_block_header_by_height",
+    "get_block_header_by_hash",
+    "get_tx_key",
+    "check_tx_key",
+    "get_address",
+];
+
+pub fn is_idempotent(method: &str) -> bool {
+    IDEMPOTENT_METHODS.contains(&method)
+}
+
+// Exponential backoff with "equal jitter": half the delay is fixed, the other
+// half random, so concurrent retries against a recovering node spread out.
+pub fn jittered_backoff(attempt: u32, base: Duration, max: Duration) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    let delay = base.saturating_mul(factor).min(max);
+    let half = delay / 2;
+    half + half.mul_f64(rand::thread_rng().r#gen::<f64>())
+}
+
+// Network failures and timeouts, as opposed to an RPC answering with an error
+pub fn is_transport_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<reqwest::Error>())
+}
+
+// Returned instead of calling an endpoint whose breaker is open
+#[derive(Debug, Clone)]
+pub struct CircuitOpen {
+    pub method: String,
+}
+
+impl std::fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: circuit breaker open for every endpoint", self.method)
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+pub fn is_circuit_open(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<CircuitOpen>())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    #[default]
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+// Opens after `threshold` consecutive transport failures. After `cooldown`
+// a single trial call is let through: success closes it, failure re-opens it.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            consecutive_failures: 0,
+            opened_at: None,
+            trial_in_flight: false,
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        match self.opened_at {
+            None => BreakerState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    // Whether a call may go through right now
+    pub fn allow(&mut self) -> bool {
+        match self.state() {
+            BreakerState::Closed => true,
+            BreakerState::Open => false,
+            BreakerState::HalfOpen if self.trial_in_flight => false,
+            BreakerState::HalfOpen => {
+                self.trial_in_flight = true;
+                true
+            }
+        }
+    }
+
+    pub fn on_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+        self.trial_in_flight = false;
+    }
+
+    pub fn on_failure(&mut self) {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if self.trial_in_flight || self.consecutive_failures >= self.threshold {
+            self.opened_at = Some(Instant::now());
+        }
+        self.trial_in_flight = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaker_opens_then_half_opens_for_a_single_trial() {
+        let mut breaker = CircuitBreaker::new(2, Duration::from_millis(20));
+        breaker.on_failure();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        breaker.on_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(!breaker.allow());
+
+        std::thread::sleep(Duration::from_millis(25));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+
+        // A failed trial re-opens immediately
+        breaker.on_failure();
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        std::thread::sleep(Duration::from_millis(25));
+        assert!(breaker.allow());
+        breaker.on_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn backoff_is_jittered_within_bounds() {
+        let base = Duration::from_millis(100);
+        let max = Duration::from_millis(1000);
+        for _ in 0..50 {
+            let third = jittered_backoff(3, base, max);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+            let capped = jittered_backoff(10, base, max);
+            assert!(capped >= Duration::from_millis(500) && capped <= max);
+        }
+        assert!(is_idempotent("check_tx_key"));
+        assert!(!is_idempotent("send_raw_transaction"));
+    }
+}
@@ -0,0 +1,290 @@
+// Deleting proofs: by their owner through `DELETE /proof/{id}`, or by the
+// purge job once they stayed in one status longer than `[retention]` allows.
+// A deleted proof leaves a tombstone with only its id, so the audit entries
+// that name it still resolve. The audit log itself is never rewritten; each
+// deletion is appended to it.
+use anyhow::Result;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::api::{ProofStatus, TransactionProof};
+use crate::audit::{AuditKind, AuditLog};
+use crate::config::RetentionConfig;
+use crate::events::{EventBus, ProofEvent, ProofEventKind, unix_now};
+use crate::policy::SECS_PER_DAY;
+use crate::privacy::{self, ProofPreimage};
+use crate::proofs::EXPIRY_MARGIN_SECS;
+use crate::storage::{ProofStorage, ProofStore};
+
+#[derive(Debug)]
+pub enum DeleteError {
+    NotFound(String),
+    AlreadyDeleted,
+    // The preimage does not open the proof, so the caller is not its owner
+    NotOwner,
+    // Its attestation may still be minted or counts towards today's caps
+    // until this time
+    Retained(u64),
+    Audit(anyhow::Error),
+}
+
+impl DeleteError {
+    // Short title used as the `error` field of API responses
+    pub fn title(&self) -> &'static str {
+        match self {
+            DeleteError::NotFound(_) => "Proof not found",
+            DeleteError::AlreadyDeleted => "Proof deleted",
+            DeleteError::NotOwner => "Preimage mismatch",
+            DeleteError::Retained(_) => "Proof still needed",
+            DeleteError::Audit(_) => "Failed to record audit entry",
+        }
+    }
+}
+
+impl std::fmt::Display for DeleteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeleteError::NotFound(proof_id) => write!(f, "{}", proof_id),
+            DeleteError::AlreadyDeleted => write!(f, "This proof has been deleted"),
+            DeleteError::NotOwner => write!(f, "The tx_id, tx_key and recipient_address do not match this proof"),
+            DeleteError::Retained(until) => {
+                write!(f, "The proof's attestation is still in use; it can be deleted from {}", until)
+            }
+            DeleteError::Audit(e) => write!(f, "{:#}", e),
+        }
+    }
+}
+
+impl std::error::Error for DeleteError {}
+
+// Until when `proof` must be kept: while its attestation can be minted, and
+// until the end of the attestation's UTC day so the daily caps stay correct
+pub fn retained_until(proof: &TransactionProof) -> Option<u64> {
+    let attestation = proof.attestation.as_ref()?;
+    let day_end = (attestation.issued_at / SECS_PER_DAY + 1) * SECS_PER_DAY;
+    let live_until = match proof.status {
+        ProofStatus::Attested => attestation.lock_proof.expiry.saturating_add(EXPIRY_MARGIN_SECS),
+        _ => 0,
+    };
+    Some(day_end.max(live_until))
+}
+
+// Replaces `proof` with its tombstone
+pub fn tombstone(proof: &mut TransactionProof, now: u64) {
+    *proof = TransactionProof {
+        tx_id: String::new(),
+        tx_key: String::new(),
+        recipient_address: String::new(),
+        amount: None,
+        proof_id: std::mem::take(&mut proof.proof_id),
+        status: ProofStatus::Deleted,
+        updated_at: now,
+        attestation: None,
+        policy: None,
+        mint: None,
+        flags: Vec::new(),
+        commitment: None,
+        block: None,
+    };
+}
+
+// Deletes a proof for whoever presents its deposit data
+pub async fn delete(
+    storage: &ProofStore,
+    events: &EventBus,
+    audit: &AuditLog,
+    proof_id: &str,
+    preimage: &ProofPreimage,
+) -> Result<(), DeleteError> {
+    let now = unix_now();
+    let mut proofs = storage.write().await;
+    let proof = proofs.get_mut(proof_id).ok_or_else(|| DeleteError::NotFound(proof_id.to_string()))?;
+    if proof.status == ProofStatus::Deleted {
+        return Err(DeleteError::AlreadyDeleted);
+    }
+    if !privacy::opens(proof, preimage) {
+        return Err(DeleteError::NotOwner);
+    }
+    if let Some(until) = retained_until(proof).filter(|until| now < *until) {
+        return Err(DeleteError::Retained(until));
+    }
+    remove(proof, events, audit, "owner", now).await.map_err(DeleteError::Audit)
+}
+
+// Audits the deletion first: a proof is never dropped without its record
+async fn remove(proof: &mut TransactionProof, events: &EventBus, audit: &AuditLog, reason: &str, now: u64) -> Result<()> {
+    let data = json!({ "reason": reason, "status": proof.status, "updated_at": proof.updated_at });
+    audit.record(AuditKind::Deletion, Some(&proof.proof_id), data).await?;
+    tombstone(proof, now);
+    events.publish(ProofEvent::new(ProofEventKind::Deleted, proof));
+    Ok(())
+}
+
+fn window(config: &RetentionConfig, status: ProofStatus) -> Option<u64> {
+    match status {
+        ProofStatus::Created => config.created_secs,
+        ProofStatus::Validated => config.validated_secs,
+        ProofStatus::Invalidated => config.invalidated_secs,
+        ProofStatus::Attested => config.attested_secs,
+        ProofStatus::Minted => config.minted_secs,
+        ProofStatus::Deleted => None,
+    }
+}
+
+// Deletes proofs that outlived their status's retention window
+#[derive(Clone)]
+pub struct Purger {
+    storage: ProofStorage,
+    events: EventBus,
+    audit: Arc<AuditLog>,
+    config: RetentionConfig,
+}
+
+impl Purger {
+    pub fn new(config: &RetentionConfig, storage: ProofStorage, events: EventBus, audit: Arc<AuditLog>) -> Self {
+        Self {
+            storage,
+            events,
+            audit,
+            config: config.clone(),
+        }
+    }
+
+    // Whether any window is set
+    pub fn enabled(&self) -> bool {
+        [ProofStatus::Created, ProofStatus::Validated, ProofStatus::Invalidated, ProofStatus::Attested, ProofStatus::Minted]
+            .into_iter()
+            .any(|status| window(&self.config, status).is_some())
+    }
+
+    pub async fn run(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match self.purge(unix_now()).await {
+                Ok(0) => {}
+                Ok(deleted) => println!("🗑️  Deleted {} proofs past their retention window", deleted),
+                Err(e) => println!("❌ Retention purge failed: {:#}", e),
+            }
+        }
+    }
+
+    // Returns how many proofs were deleted
+    pub async fn purge(&self, now: u64) -> Result<usize> {
+        let mut deleted = 0;
+        let mut proofs = self.storage.write().await;
+        for proof in proofs.values_mut() {
+            let Some(window) = window(&self.config, proof.status) else { continue };
+            // Proofs stored before status changes were timed start their window now
+            if proof.updated_at == 0 {
+                proof.updated_at = now;
+                continue;
+            }
+            let expired = now >= proof.updated_at.saturating_add(window);
+            let retained = retained_until(proof).is_some_and(|until| now < until);
+            if expired && !retained {
+                remove(proof, &self.events, &self.audit, "retention", now).await?;
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Piconero;
+    use crate::attestation::{Attestation, LockProof};
+
+    fn proof(proof_id: &str, status: ProofStatus, updated_at: u64) -> TransactionProof {
+        TransactionProof {
+            tx_id: "ab".repeat(32),
+            tx_key: "cd".repeat(32),
+            recipient_address: crate::testing::ADDRESS.to_string(),
+            amount: Some(Piconero(5)),
+            proof_id: proof_id.to_string(),
+            status,
+            updated_at,
+            attestation: None,
+            policy: None,
+            mint: None,
+            flags: Vec::new(),
+            commitment: None,
+            block: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn only_the_owner_deletes_and_the_audit_chain_records_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_path = dir.path().join("audit.jsonl");
+        let audit = AuditLog::open(&audit_path).unwrap();
+        let (storage, events) = (ProofStore::in_memory(), EventBus::new(4));
+        let plain = proof("p1", ProofStatus::Validated, 1);
+        let owner = ProofPreimage::of(&plain);
+        storage.write().await.insert("p1".to_string(), plain);
+        privacy::conceal(storage.write().await.get_mut("p1").unwrap()).unwrap();
+
+        let stranger = ProofPreimage { tx_key: "ce".repeat(32), ..owner.clone() };
+        let refused = delete(&storage, &events, &audit, "p1", &stranger).await;
+        assert!(matches!(refused, Err(DeleteError::NotOwner)));
+        delete(&storage, &events, &audit, "p1", &owner).await.unwrap();
+
+        let tombstone = storage.read().await["p1"].clone();
+        assert_eq!(tombstone.status, ProofStatus::Deleted);
+        assert!(tombstone.commitment.is_none() && tombstone.amount.is_none());
+        let again = delete(&storage, &events, &audit, "p1", &owner).await;
+        assert!(matches!(again, Err(DeleteError::AlreadyDeleted)));
+        assert_eq!(crate::audit::verify(&audit_path).unwrap().entries, 1);
+    }
+
+    #[tokio::test]
+    async fn the_purge_keeps_attestations_that_are_still_in_use() {
+        let now = 20 * SECS_PER_DAY + 500;
+        let storage: ProofStorage = Arc::new(ProofStore::in_memory());
+        let mut attested = proof("attested", ProofStatus::Attested, now - 100);
+        attested.attestation = Some(Attestation {
+            lock_proof: LockProof::new([0xab; 32], [0x02; 20], 5, now + 3600),
+            signature: "0x01".to_string(),
+            attestor: "0x03".to_string(),
+            issued_at: now - 100,
+            conversion: None,
+            signatures: Vec::new(),
+            previous_signature: None,
+            network: None,
+        });
+        for proof in [
+            proof("old", ProofStatus::Created, now - 7200),
+            proof("recent", ProofStatus::Created, now - 60),
+            proof("legacy", ProofStatus::Created, 0),
+            proof("kept", ProofStatus::Validated, 1),
+            attested,
+        ] {
+            storage.write().await.insert(proof.proof_id.clone(), proof);
+        }
+        let config = RetentionConfig {
+            created_secs: Some(3600),
+            attested_secs: Some(0),
+            ..RetentionConfig::default()
+        };
+        let purger = Purger::new(&config, storage.clone(), EventBus::new(4), Arc::new(AuditLog::disabled()));
+        assert!(purger.enabled());
+
+        assert_eq!(purger.purge(now).await.unwrap(), 1);
+        let status = |id: &str, proofs: &std::collections::HashMap<String, TransactionProof>| proofs[id].status;
+        let proofs = storage.read().await.clone();
+        assert_eq!(status("old", &proofs), ProofStatus::Deleted);
+        assert_eq!(status("recent", &proofs), ProofStatus::Created);
+        assert_eq!(status("kept", &proofs), ProofStatus::Validated);
+        assert_eq!(status("attested", &proofs), ProofStatus::Attested);
+        // Untimed proofs get their window started instead
+        assert_eq!(proofs["legacy"].updated_at, now);
+
+        // Past the attestation's expiry and day, only the fresh proof remains
+        let later = 22 * SECS_PER_DAY;
+        assert_eq!(purger.purge(later).await.unwrap(), 3);
+        assert_eq!(status("kept", &storage.read().await.clone()), ProofStatus::Validated);
+    }
+}
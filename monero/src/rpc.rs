@@ -0,0 +1,340 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::api::TxKey;
+use crate::config::{MoneroConfig, RpcPolicyConfig};
+use crate::network::Network;
+use crate::pool::RpcPool;
+use crate::scanner::{self, MatchedOutput};
+
+// Thin JSON-RPC transport for monerod / monero-wallet-rpc. Every request is
+// bounded by the timeout configured for its method.
+#[derive(Clone)]
+pub struct RpcClient {
+    http: reqwest::Client,
+    policy: Arc<RpcPolicyConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcEnvelope<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+// Subset of monerod `get_info`
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DaemonInfo {
+    pub height: u64,
+    pub target_height: u64,
+    pub synchronized: bool,
+    pub nettype: String,
+}
+
+impl RpcClient {
+    pub fn new(policy: RpcPolicyConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            policy: Arc::new(policy),
+        }
+    }
+
+    pub fn policy(&self) -> &RpcPolicyConfig {
+        &self.policy
+    }
+
+    // POST {base_url}/json_rpc
+    pub async fn json_rpc<T: DeserializeOwned>(&self, base_url: &str, method: &str, params: Value) -> Result<T> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "0",
+            "method": method,
+            "params": params,
+        });
+        let envelope: RpcEnvelope<T> = self.post(base_url, "json_rpc", method, &body).await?;
+        if let Some(error) = envelope.error {
+            return Err(anyhow!("{} failed: {} (code {})", method, error.message, error.code));
+        }
+        envelope.result.ok_or_else(|| anyhow!("{} returned no result", method))
+    }
+
+    // monerod "other" endpoints such as POST {base_url}/get_transactions
+    pub async fn other<T: DeserializeOwned>(&self, base_url: &str, path: &str, body: Value) -> Result<T> {
+        self.post(base_url, path, path, &body).await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, base_url: &str, path: &str, method: &str, body: &Value) -> Result<T> {
+        let url = format!("{}/{}", base_url.trim_end_matches('/'), path);
+        let response = self
+            .http
+            .post(&url)
+            .timeout(self.policy.timeout(method))
+            .json(body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("request to {} failed", url))?;
+        response
+            .json()
+            .await
+            .with_context(|| format!("invalid response from {}", url))
+    }
+}
+
+// Monero service for RPC calls
+#[derive(Clone)]
+pub struct MoneroService {
+    pub pool: RpcPool,
+    pub wallet: Option<RpcPool>,
+    pub network: Network,
+    rpc: RpcClient,
+}
+
+// monerod `/get_transactions`
+#[derive(Debug, Deserialize)]
+struct GetTransactionsResult {
+    #[serde(default)]
+    txs: Vec<TransactionEntry>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct TransactionEntry {
+    tx_hash: String,
+    // Set when requested with `decode_as_json`
+    #[serde(default)]
+    as_json: String,
+    #[serde(default)]
+    block_height: u64,
+    #[serde(default)]
+    block_timestamp: u64,
+    #[serde(default)]
+    in_pool: bool,
+}
+
+// What `check_tx_key` found, and the daemon data it was computed from
+#[derive(Debug, Clone)]
+pub struct TxKeyCheck {
+    pub outputs: Vec<MatchedOutput>,
+    // The transaction's hash, outputs, encrypted amounts and their commitments
+    pub evidence: Value,
+    // Where it was mined; None while it is in the pool
+    pub block_height: Option<u64>,
+}
+
+impl MoneroService {
+    pub fn new(config: &MoneroConfig) -> Result<Self> {
+        let rpc = RpcClient::new(config.rpc.clone());
+        Ok(Self {
+            pool: RpcPool::new(&config.daemons, config.quorum, rpc.clone()).with_network(config.network),
+            wallet: config
+                .wallet
+                .as_ref()
+                .map(|url| RpcPool::new(std::slice::from_ref(url), 1, rpc.clone())),
+            network: config.network,
+            rpc,
+        })
+    }
+
+    // Fails if a daemon reported another nettype than `monero.network` in
+    // the last health check
+    pub fn check_network(&self) -> Result<()> {
+        for daemon in self.pool.status() {
+            if let Some(nettype) = daemon.nettype.filter(|nettype| nettype != self.network.as_str()) {
+                bail!("daemon {} is on {}, but monero.network is {}", daemon.url, nettype, self.network);
+            }
+        }
+        Ok(())
+    }
+    
+    // Get transaction key from wallet RPC, including the additional keys of
+    // subaddress outputs
+    pub async fn get_tx_key(&self, tx_id: &str) -> Result<TxKey> {
+        if let Some(ref wallet) = self.wallet {
+            println!("🔑 Getting TX key for {} from wallet", tx_id);
+            #[derive(Deserialize)]
+            struct GetTxKey {
+                tx_key: String,
+            }
+            let result: GetTxKey = wallet
+                .call("get_tx_key", |url| {
+                    let rpc = self.rpc.clone();
+                    async move { rpc.json_rpc(&url, "get_tx_key", serde_json::json!({ "txid": tx_id })).await }
+                })
+                .await?;
+            TxKey::parse(&result.tx_key).context("wallet returned an invalid tx key")
+        } else {
+            Err(anyhow::anyhow!("Wallet RPC not configured"))
+        }
+    }
+    
+    // Finds the outputs paying `address` with the tx key(s); in quorum mode
+    // the daemons must agree on the transaction's outputs
+    pub async fn check_tx_key(&self, tx_id: &str, tx_key: &TxKey, address: &str) -> Result<TxKeyCheck> {
+        println!("🔍 Checking TX: {} for address: {} via daemon pool", tx_id, address);
+        let address = self.network.check_address(address)?;
+        let body = serde_json::json!({ "txs_hashes": [tx_id], "decode_as_json": true });
+        let (outputs, evidence, block_height) = self
+            .pool
+            .quorum_call("get_transactions", |url| {
+                let rpc = self.rpc.clone();
+                let body = body.clone();
+                async move {
+                    let result: GetTransactionsResult = rpc.other(&url, "get_transactions", body).await?;
+                    let tx = result
+                        .txs
+                        .into_iter()
+                        .next()
+                        .ok_or_else(|| anyhow!("transaction {} not found", tx_id))?;
+                    let decoded: Value = serde_json::from_str(&tx.as_json).context("transaction was not decoded")?;
+                    let evidence = serde_json::json!({
+                        "tx_hash": tx.tx_hash,
+                        "vout": decoded["vout"],
+                        "ecdh_info": decoded["rct_signatures"]["ecdhInfo"],
+                        "out_pk": decoded["rct_signatures"]["outPk"],
+                    });
+                    let block_height = (!tx.in_pool).then_some(tx.block_height);
+                    Ok((scanner::parse_outputs(&decoded)?, evidence, block_height))
+                }
+            })
+            .await?;
+        Ok(TxKeyCheck {
+            // Outputs to subaddresses are only found with their additional keys
+            outputs: scanner::scan(&outputs, tx_key, &address)?,
+            evidence,
+            block_height,
+        })
+    }
+    
+    // Get transaction details from daemon
+    pub async fn get_transaction(&self, tx_id: &str) -> Result<serde_json::Value> {
+        println!("📊 Getting transaction details for: {} from daemon pool", tx_id);
+        let body = serde_json::json!({ "txs_hashes": [tx_id] });
+        self.pool
+            .quorum_call("get_transactions", |url| {
+                let rpc = self.rpc.clone();
+                let body = body.clone();
+                async move {
+                    let result: GetTransactionsResult = rpc.other(&url, "get_transactions", body).await?;
+                    let tx = result
+                        .txs
+                        .into_iter()
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("transaction {} not found", tx_id))?;
+                    Ok(serde_json::json!({
+                        "tx_hash": tx.tx_hash,
+                        "height": tx.block_height,
+                        "timestamp": tx.block_timestamp,
+                        "in_pool": tx.in_pool
+                    }))
+                }
+            })
+            .await
+    }
+
+    // Blocks mined on top of each transaction's block, 0 while it sits in the
+    // pool. Transactions the daemons don't know are left out.
+    pub async fn confirmations(&self, tx_ids: &[&str]) -> Result<HashMap<String, u64>> {
+        if tx_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let body = serde_json::json!({ "txs_hashes": tx_ids });
+        let txs = self
+            .pool
+            .quorum_call("get_transactions", |url| {
+                let rpc = self.rpc.clone();
+                let body = body.clone();
+                async move {
+                    let result: GetTransactionsResult = rpc.other(&url, "get_transactions", body).await?;
+                    Ok(result.txs)
+                }
+            })
+            .await?;
+        let height = self.get_info().await?.height;
+        Ok(txs
+            .into_iter()
+            .map(|tx| {
+                let confirmations = if tx.in_pool { 0 } else { height.saturating_sub(tx.block_height) };
+                (tx.tx_hash, confirmations)
+            })
+            .collect())
+    }
+
+    // The height of the block `tx_id` was mined in; None while it is in the
+    // pool or once no daemon knows it
+    pub async fn tx_height(&self, tx_id: &str) -> Result<Option<u64>> {
+        let body = serde_json::json!({ "txs_hashes": [tx_id] });
+        self.pool
+            .quorum_call("get_transactions", |url| {
+                let rpc = self.rpc.clone();
+                let body = body.clone();
+                async move {
+                    let result: GetTransactionsResult = rpc.other(&url, "get_transactions", body).await?;
+                    let tx = result.txs.into_iter().next();
+                    Ok(tx.filter(|tx| !tx.in_pool).map(|tx| tx.block_height))
+                }
+            })
+            .await
+    }
+
+    // Hash of the main chain's block at `height`; in quorum mode the daemons
+    // must agree on it
+    pub async fn block_hash(&self, height: u64) -> Result<String> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Header {
+            hash: String,
+        }
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct HeaderResult {
+            block_header: Header,
+        }
+        let result: HeaderResult = self
+            .pool
+            .quorum_call("get_block_header_by_height", |url| {
+                let rpc = self.rpc.clone();
+                async move {
+                    rpc.json_rpc(&url, "get_block_header_by_height", serde_json::json!({ "height": height }))
+                        .await
+                }
+            })
+            .await?;
+        Ok(result.block_header.hash)
+    }
+
+    pub async fn get_info(&self) -> Result<DaemonInfo> {
+        self.pool
+            .call("get_info", |url| {
+                let rpc = self.rpc.clone();
+                async move { rpc.json_rpc(&url, "get_info", serde_json::json!({})).await }
+            })
+            .await
+    }
+
+    // monero-wallet-rpc `get_version`, used as a wallet liveness probe
+    pub async fn get_wallet_version(&self) -> Result<u32> {
+        let wallet = self
+            .wallet
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Wallet RPC not configured"))?;
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+        let result: Version = wallet
+            .call("get_version", |url| {
+                let rpc = self.rpc.clone();
+                async move { rpc.json_rpc(&url, "get_version", serde_json::json!({})).await }
+            })
+            .await?;
+        Ok(result.version)
+    }
+}
@@ -0,0 +1,422 @@
+// Finds the outputs of a transaction that pay an address, given the tx
+// private key(s). This is the check monero-wallet-rpc's `check_tx_key`
+// does, run locally on the daemon's `get_transactions` data so every
+// matched output can be reported. Consensus does not check the encrypted
+// amounts, so each one must open the output's amount commitment.
+use anyhow::{Context, Result, anyhow, bail};
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+use utoipa::ToSchema;
+
+use crate::amount::Piconero;
+use crate::api::TxKey;
+
+// A decoded standard, integrated or subaddress
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    pub network_byte: u64,
+    pub spend_key: [u8; 32],
+    pub view_key: [u8; 32],
+    pub payment_id: Option<[u8; 8]>,
+}
+
+impl Address {
+    pub fn parse(address: &str) -> Result<Self> {
+        let data = base58_decode(address)?;
+        if data.len() < 4 {
+            bail!("address is too short");
+        }
+        let (payload, checksum) = data.split_at(data.len() - 4);
+        if keccak(&[payload])[..4] != *checksum {
+            bail!("address checksum mismatch");
+        }
+        let (network_byte, keys) = read_varint(payload)?;
+        let (spend_key, view_key, payment_id) = match keys.len() {
+            64 => (&keys[..32], &keys[32..], None),
+            72 => (&keys[..32], &keys[32..64], Some(keys[64..].try_into()?)),
+            n => bail!("address has {} bytes of keys, expected 64 or 72", n),
+        };
+        Ok(Self {
+            network_byte,
+            spend_key: spend_key.try_into()?,
+            view_key: view_key.try_into()?,
+            payment_id,
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        let mut data = write_varint(self.network_byte);
+        data.extend_from_slice(&self.spend_key);
+        data.extend_from_slice(&self.view_key);
+        if let Some(payment_id) = &self.payment_id {
+            data.extend_from_slice(payment_id);
+        }
+        let checksum = keccak(&[&data]);
+        data.extend_from_slice(&checksum[..4]);
+        base58_encode(&data)
+    }
+}
+
+// An output as listed by the daemon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOutput {
+    pub key: [u8; 32],
+    pub amount: OutputAmount,
+    // `rct_signatures.outPk`, mask·G + amount·H; None for plain amounts
+    pub commitment: Option<[u8; 32]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputAmount {
+    // Coinbase and pre-RingCT outputs
+    Plain(u64),
+    // 8-byte `ecdhInfo.amount` of bulletproof transactions
+    Compact([u8; 8]),
+    // 32-byte `ecdhInfo.mask` and `ecdhInfo.amount` of early RingCT transactions
+    Legacy { mask: [u8; 32], amount: [u8; 32] },
+}
+
+// An output that pays the checked address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct MatchedOutput {
+    pub index: u64,
+    pub amount: Piconero,
+    pub amount_xmr: String,
+    pub one_time_key: String,
+}
+
+// Outputs of a transaction decoded by `get_transactions` with `decode_as_json`
+pub fn parse_outputs(tx: &Value) -> Result<Vec<TxOutput>> {
+    let vout = tx["vout"].as_array().ok_or_else(|| anyhow!("transaction has no outputs"))?;
+    let ecdh_info = tx["rct_signatures"]["ecdhInfo"].as_array();
+    let out_pk = tx["rct_signatures"]["outPk"].as_array();
+    vout.iter()
+        .enumerate()
+        .map(|(index, output)| {
+            let target = &output["target"];
+            let key = target["tagged_key"]["key"].as_str().or(target["key"].as_str());
+            let key = key.ok_or_else(|| anyhow!("output {} has no key", index))?;
+            let (amount, commitment) = match output["amount"].as_u64().unwrap_or_default() {
+                0 => {
+                    let info = ecdh_info.and_then(|info| info.get(index));
+                    let encrypted = info
+                        .and_then(|info| info["amount"].as_str())
+                        .ok_or_else(|| anyhow!("output {} has no encrypted amount", index))?;
+                    let bytes = hex::decode(encrypted).with_context(|| format!("output {} amount", index))?;
+                    let amount = match bytes.len() {
+                        8 => OutputAmount::Compact(bytes.try_into().unwrap_or_default()),
+                        32 => {
+                            let mask = info.and_then(|info| info["mask"].as_str()).unwrap_or_default();
+                            OutputAmount::Legacy {
+                                mask: hex32(mask).with_context(|| format!("output {} mask", index))?,
+                                amount: bytes.try_into().unwrap_or_default(),
+                            }
+                        }
+                        n => bail!("output {} amount has {} bytes", index, n),
+                    };
+                    let commitment = out_pk
+                        .and_then(|out_pk| out_pk.get(index))
+                        .and_then(|commitment| commitment.as_str())
+                        .ok_or_else(|| anyhow!("output {} has no commitment", index))?;
+                    (amount, Some(hex32(commitment).with_context(|| format!("output {} commitment", index))?))
+                }
+                amount => (OutputAmount::Plain(amount), None),
+            };
+            Ok(TxOutput {
+                key: hex32(key).with_context(|| format!("output {} key", index))?,
+                amount,
+                commitment,
+            })
+        })
+        .collect()
+}
+
+// Checks every output against the main tx key and, for subaddress
+// transactions, the additional key of the same index
+pub fn scan(outputs: &[TxOutput], tx_key: &TxKey, address: &Address) -> Result<Vec<MatchedOutput>> {
+    let view_key = point(&address.view_key).context("invalid view key in address")?;
+    let spend_key = point(&address.spend_key).context("invalid spend key in address")?;
+    let main = derivation(&secret(&tx_key.main)?, &view_key);
+    let additional = tx_key
+        .additional
+        .iter()
+        .map(|key| Ok(derivation(&secret(key)?, &view_key)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut matched = Vec::new();
+    for (index, output) in outputs.iter().enumerate() {
+        let index = index as u64;
+        let candidates = std::iter::once(&main).chain(additional.get(index as usize));
+        for derivation in candidates {
+            let shared = derivation_scalar(derivation, index);
+            if one_time_key(&shared, &spend_key) == output.key {
+                let (amount, mask) = decrypt_amount(&output.amount, &shared);
+                let opens = match (mask, &output.commitment) {
+                    (None, _) => true,
+                    (Some(mask), Some(commitment)) => commit(&mask, amount) == *commitment,
+                    (Some(_), None) => false,
+                };
+                if !opens {
+                    println!("🚨 Output {} claims {} piconero, which its commitment does not hold; ignored", index, amount);
+                    break;
+                }
+                let amount = Piconero(amount);
+                matched.push(MatchedOutput {
+                    index,
+                    amount,
+                    amount_xmr: amount.xmr(),
+                    one_time_key: hex::encode(output.key),
+                });
+                break;
+            }
+        }
+    }
+    Ok(matched)
+}
+
+// Sum of the matched amounts; fails rather than wrapping
+pub fn total(outputs: &[MatchedOutput]) -> Result<Piconero> {
+    Piconero::checked_sum(outputs.iter().map(|output| output.amount))
+        .ok_or_else(|| anyhow!("received amount overflows"))
+}
+
+// 8·r·A
+fn derivation(secret: &Scalar, public: &EdwardsPoint) -> [u8; 32] {
+    (secret * public).mul_by_cofactor().compress().to_bytes()
+}
+
+// Hs(derivation || varint(index))
+fn derivation_scalar(derivation: &[u8; 32], index: u64) -> Scalar {
+    Scalar::from_bytes_mod_order(keccak(&[derivation, &write_varint(index)]))
+}
+
+// Hs(...)·G + B
+fn one_time_key(shared: &Scalar, spend_key: &EdwardsPoint) -> [u8; 32] {
+    (shared * ED25519_BASEPOINT_TABLE + spend_key).compress().to_bytes()
+}
+
+// The amount and, for RingCT outputs, the mask of its commitment
+fn decrypt_amount(amount: &OutputAmount, shared: &Scalar) -> (u64, Option<Scalar>) {
+    match amount {
+        OutputAmount::Plain(amount) => (*amount, None),
+        OutputAmount::Compact(encrypted) => {
+            let pad = keccak(&[b"amount", shared.as_bytes()]);
+            let mut bytes = [0u8; 8];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = encrypted[i] ^ pad[i];
+            }
+            (u64::from_le_bytes(bytes), Some(commitment_mask(shared)))
+        }
+        OutputAmount::Legacy { mask, amount } => {
+            let first = Scalar::from_bytes_mod_order(keccak(&[shared.as_bytes()]));
+            let second = Scalar::from_bytes_mod_order(keccak(&[first.as_bytes()]));
+            let mask = Scalar::from_bytes_mod_order(*mask) - first;
+            // An amount past 64 bits does not open the commitment below
+            let amount = Scalar::from_bytes_mod_order(*amount) - second;
+            (u64::from_le_bytes(amount.as_bytes()[..8].try_into().unwrap_or_default()), Some(mask))
+        }
+    }
+}
+
+// Hs("commitment_mask" || Hs(...)), the mask of bulletproof-era outputs
+fn commitment_mask(shared: &Scalar) -> Scalar {
+    Scalar::from_bytes_mod_order(keccak(&[b"commitment_mask", shared.as_bytes()]))
+}
+
+// Monero's amount generator `rct::H`
+const H: [u8; 32] = [
+    0x8b, 0x65, 0x59, 0x70, 0x15, 0x37, 0x99, 0xaf, 0x2a, 0xea, 0xdc, 0x9f, 0xf1, 0xad, 0xd0, 0xea, 0x6c, 0x72, 0x51,
+    0xd5, 0x41, 0x54, 0xcf, 0xa9, 0x2c, 0x17, 0x3a, 0x0d, 0xd3, 0x9c, 0x1f, 0x94,
+];
+
+// Whether `view_key` is the private view key of `address`
+pub fn is_view_key_of(view_key: &str, address: &Address) -> Result<bool> {
+    let secret = Scalar::from_canonical_bytes(hex32(view_key).context("invalid view key")?)
+        .into_option()
+        .ok_or_else(|| anyhow!("invalid view key"))?;
+    Ok((&secret * ED25519_BASEPOINT_TABLE).compress().to_bytes() == address.view_key)
+}
+
+// mask·G + amount·H
+fn commit(mask: &Scalar, amount: u64) -> [u8; 32] {
+    let h = CompressedEdwardsY(H).decompress().expect("rct::H is a curve point");
+    (mask * ED25519_BASEPOINT_TABLE + Scalar::from(amount) * h).compress().to_bytes()
+}
+
+fn secret(key: &str) -> Result<Scalar> {
+    Ok(Scalar::from_bytes_mod_order(hex32(key).context("invalid tx key")?))
+}
+
+fn point(bytes: &[u8; 32]) -> Result<EdwardsPoint> {
+    CompressedEdwardsY(*bytes)
+        .decompress()
+        .ok_or_else(|| anyhow!("not a curve point"))
+}
+
+fn hex32(value: &str) -> Result<[u8; 32]> {
+    hex::decode(value)?
+        .try_into()
+        .map_err(|_| anyhow!("expected 32 bytes"))
+}
+
+fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn write_varint(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+    bytes
+}
+
+fn read_varint(data: &[u8]) -> Result<(u64, &[u8])> {
+    let mut value = 0u64;
+    for (i, byte) in data.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, &data[i + 1..]));
+        }
+    }
+    bail!("invalid varint")
+}
+
+// Monero's base58 encodes 8-byte blocks into 11 characters each; a shorter
+// final block uses the matching entry of this table
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const ENCODED_BLOCK_SIZES: [usize; 9] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+
+fn base58_encode(data: &[u8]) -> String {
+    let mut encoded = String::new();
+    for block in data.chunks(8) {
+        let mut value = block.iter().fold(0u64, |value, byte| (value << 8) | u64::from(*byte));
+        let mut chars = vec![BASE58_ALPHABET[0]; ENCODED_BLOCK_SIZES[block.len()]];
+        for c in chars.iter_mut().rev() {
+            *c = BASE58_ALPHABET[(value % 58) as usize];
+            value /= 58;
+        }
+        encoded.push_str(&String::from_utf8_lossy(&chars));
+    }
+    encoded
+}
+
+fn base58_decode(encoded: &str) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    for block in encoded.as_bytes().chunks(11) {
+        let size = ENCODED_BLOCK_SIZES
+            .iter()
+            .position(|size| *size == block.len())
+            .ok_or_else(|| anyhow!("invalid base58 length"))?;
+        let mut value = 0u128;
+        for c in block {
+            let digit = BASE58_ALPHABET
+                .iter()
+                .position(|a| a == c)
+                .ok_or_else(|| anyhow!("invalid base58 character {:?}", *c as char))?;
+            value = value * 58 + digit as u128;
+        }
+        if value >> (8 * size) != 0 {
+            bail!("base58 block overflows");
+        }
+        data.extend_from_slice(&value.to_be_bytes()[16 - size..]);
+    }
+    Ok(data)
+}
+
+// Builds outputs the way a sending wallet does, for the stub daemon
+#[cfg(test)]
+pub(crate) fn build_output(tx_secret: &[u8; 32], address: &Address, index: u64, amount: u64) -> TxOutput {
+    let secret = Scalar::from_bytes_mod_order(*tx_secret);
+    let view_key = point(&address.view_key).unwrap();
+    let spend_key = point(&address.spend_key).unwrap();
+    let shared = derivation_scalar(&derivation(&secret, &view_key), index);
+    let pad = keccak(&[b"amount", shared.as_bytes()]);
+    let mut encrypted = amount.to_le_bytes();
+    for (i, byte) in encrypted.iter_mut().enumerate() {
+        *byte ^= pad[i];
+    }
+    TxOutput {
+        key: one_time_key(&shared, &spend_key),
+        amount: OutputAmount::Compact(encrypted),
+        commitment: Some(commit(&commitment_mask(&shared), amount)),
+    }
+}
+
+// An address whose keys are derived from `seed`
+#[cfg(test)]
+pub(crate) fn test_address(network_byte: u64, seed: u64) -> Address {
+    let key = |n: u64| (&Scalar::from(n) * ED25519_BASEPOINT_TABLE).compress().to_bytes();
+    Address {
+        network_byte,
+        spend_key: key(seed),
+        view_key: key(seed + 1),
+        payment_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ADDRESS;
+
+    #[test]
+    fn decodes_and_reencodes_addresses() {
+        let address = Address::parse(ADDRESS).unwrap();
+        assert_eq!(address.network_byte, 18);
+        assert_eq!(address.encode(), ADDRESS);
+
+        let mut corrupted = ADDRESS.to_string();
+        corrupted.replace_range(10..11, "z");
+        assert!(Address::parse(&corrupted).is_err());
+    }
+
+    #[test]
+    fn finds_every_output_paying_the_address() {
+        let address = test_address(42, 7);
+        let other = test_address(42, 100);
+        let tx_secret = [0x11; 32];
+        let outputs = vec![
+            build_output(&tx_secret, &other, 0, 5),
+            build_output(&tx_secret, &address, 1, 1_000),
+            build_output(&tx_secret, &address, 2, 2_500),
+        ];
+        let tx_key = TxKey::parse(&hex::encode(tx_secret)).unwrap();
+
+        let matched = scan(&outputs, &tx_key, &address).unwrap();
+        let found: Vec<(u64, u64)> = matched.iter().map(|o| (o.index, o.amount.0)).collect();
+        assert_eq!(found, [(1, 1_000), (2, 2_500)]);
+        assert_eq!(total(&matched).unwrap(), Piconero(3_500));
+        assert!(scan(&outputs, &tx_key, &test_address(42, 50)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn amounts_must_open_their_commitment() {
+        let address = test_address(18, 7);
+        let tx_secret = [0x11; 32];
+        let tx_key = TxKey::parse(&hex::encode(tx_secret)).unwrap();
+        let honest = build_output(&tx_secret, &address, 0, 1_000);
+        assert_eq!(scan(std::slice::from_ref(&honest), &tx_key, &address).unwrap()[0].amount, Piconero(1_000));
+
+        // The sender encrypts another amount than the one committed to
+        let OutputAmount::Compact(mut encrypted) = honest.amount else { unreachable!() };
+        let claimed = 1_000_000_000_000u64.to_le_bytes();
+        for (i, byte) in encrypted.iter_mut().enumerate() {
+            *byte ^= 1_000u64.to_le_bytes()[i] ^ claimed[i];
+        }
+        let tampered = TxOutput { amount: OutputAmount::Compact(encrypted), ..honest.clone() };
+        assert!(scan(&[tampered], &tx_key, &address).unwrap().is_empty());
+        let uncommitted = TxOutput { commitment: None, ..honest };
+        assert!(scan(&[uncommitted], &tx_key, &address).unwrap().is_empty());
+    }
+}
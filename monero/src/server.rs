@@ -0,0 +1,2211 @@
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::Filter;
+use serde::de::DeserializeOwned;
+use warp::http::HeaderValue;
+use warp::hyper::body::Bytes;
+
+use crate::amount::{Piconero, WfoidConversion};
+use crate::api::{
+    AttestRequest, ProofStatus, AttestResponse, DeletedResponse, ErrorResponse, HealthResponse, LivenessResponse,
+    OutstandingAttestation, ProofRequest, ProofResponse, ProofV1, ProofV2, ReadinessResponse, ReplayResponse, TransactionProof,
+    ValidationRequest, ValidationResponse, ValidationResponseV2,
+};
+use crate::attestation::{self, Attestor, PartialSignature};
+use crate::audit::{AuditEntry, AuditLog};
+use crate::config::Config;
+use crate::coordination::{self, Coordinator, CosignError, CosignRequest};
+use crate::events::{self, EventBus, EventFilter, ProofEvent};
+use crate::export::{self, Encoder, ExportQuery, Format, ImportQuery, ImportResponse, Record};
+use crate::finality::Watcher;
+use crate::health;
+use crate::network;
+use crate::openapi;
+use crate::policy::{Policy, PolicyEngine};
+use crate::privacy::{self, ProofPreimage};
+use crate::proofs::{self, AttestError};
+use crate::reconcile::Reconciler;
+use crate::resilience;
+use crate::retention::{self, DeleteError, Purger};
+use crate::rpc::MoneroService;
+use crate::storage::{ProofStorage, ProofStore};
+use crate::supervisor::{self, Shutdown, Supervisor};
+use crate::validation::{self, InvalidRequest, Validate};
+use crate::webhooks::{DeadLetter, RegisterWebhookRequest, RetryPolicy, SIGNATURE_HEADER, WebhookEndpoint, Webhooks};
+
+// Bearer token guarding operator endpoints (webhook management)
+#[derive(Clone)]
+pub struct AdminToken(Option<Arc<String>>);
+
+impl AdminToken {
+    pub fn new(token: Option<String>) -> Self {
+        Self(token.filter(|t| !t.is_empty()).map(Arc::new))
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("MONERO_ADMIN_TOKEN").ok())
+    }
+
+    // Without a configured token the admin endpoints stay closed
+    fn authorizes(&self, header: Option<&str>) -> bool {
+        match (&self.0, header.and_then(|h| h.strip_prefix("Bearer "))) {
+            (Some(token), Some(presented)) => privacy::constant_time_eq(token.as_bytes(), presented.as_bytes()),
+            _ => false,
+        }
+    }
+}
+
+pub struct Route {
+    pub method: &'static str,
+    pub path: &'static str,
+    pub summary: &'static str,
+}
+
+// Every route served by `routes()`, plus the deprecated unversioned aliases
+// of the `/v1` routes (see `legacy_path`). Printed at startup; the OpenAPI
+// tests check that the spec documents exactly these.
+pub const ROUTES: &[Route] = &[
+    Route { method: "GET", path: "/health", summary: "Health check" },
+    Route { method: "GET", path: "/health/live", summary: "Liveness probe" },
+    Route { method: "GET", path: "/health/ready", summary: "Readiness probe" },
+    Route { method: "POST", path: "/v1/proof", summary: "Generate transaction proof" },
+    Route { method: "POST", path: "/v1/validate", summary: "Validate transaction" },
+    Route { method: "GET", path: "/v1/proofs", summary: "Get all proofs" },
+    Route { method: "GET", path: "/v1/proof/{id}", summary: "Get specific proof" },
+    Route { method: "DELETE", path: "/v1/proof/{id}", summary: "Delete a proof (owner)" },
+    Route { method: "POST", path: "/v1/proof/{id}/attest", summary: "Sign a bridge attestation" },
+    Route { method: "GET", path: "/v1/events", summary: "Server-Sent Events stream of proof events" },
+    Route { method: "GET", path: "/v1/events/ws", summary: "WebSocket stream of proof events" },
+    Route { method: "POST", path: "/v1/webhooks", summary: "Register a webhook (admin)" },
+    Route { method: "GET", path: "/v1/webhooks", summary: "List webhooks (admin)" },
+    Route { method: "DELETE", path: "/v1/webhooks/{id}", summary: "Remove a webhook (admin)" },
+    Route { method: "GET", path: "/v1/webhooks/dead-letters", summary: "Failed deliveries (admin)" },
+    Route { method: "POST", path: "/v1/webhooks/dead-letters/{id}/replay", summary: "Replay a failed delivery (admin)" },
+    Route { method: "POST", path: "/v1/gossip/attestations", summary: "Co-sign a peer's attestation (gossip secret)" },
+    Route { method: "GET", path: "/v1/export/proofs", summary: "Export every proof (admin)" },
+    Route { method: "GET", path: "/v1/export/audit", summary: "Export the audit log (admin)" },
+    Route { method: "POST", path: "/v1/import/proofs", summary: "Import proofs (admin)" },
+    Route { method: "POST", path: "/v1/import/audit", summary: "Import audit entries (admin)" },
+    Route { method: "POST", path: "/v2/proof", summary: "Generate transaction proof" },
+    Route { method: "POST", path: "/v2/validate", summary: "Validate transaction" },
+    Route { method: "GET", path: "/v2/proofs", summary: "Get all proofs" },
+    Route { method: "GET", path: "/v2/proof/{id}", summary: "Get specific proof" },
+    Route { method: "DELETE", path: "/v2/proof/{id}", summary: "Delete a proof (owner)" },
+    Route { method: "POST", path: "/v2/proof/{id}/attest", summary: "Sign a bridge attestation" },
+    Route { method: "POST", path: "/v2/proof/{id}/open", summary: "Open a proof stored as a commitment" },
+    Route { method: "GET", path: "/v2/attestations", summary: "Attestations that can still be minted" },
+    Route { method: "GET", path: "/v2/events", summary: "Server-Sent Events stream of proof events" },
+    Route { method: "GET", path: "/v2/events/ws", summary: "WebSocket stream of proof events" },
+    Route { method: "POST", path: "/v2/webhooks", summary: "Register a webhook (admin)" },
+    Route { method: "GET", path: "/v2/webhooks", summary: "List webhooks (admin)" },
+    Route { method: "DELETE", path: "/v2/webhooks/{id}", summary: "Remove a webhook (admin)" },
+    Route { method: "GET", path: "/v2/webhooks/dead-letters", summary: "Failed deliveries (admin)" },
+    Route { method: "POST", path: "/v2/webhooks/dead-letters/{id}/replay", summary: "Replay a failed delivery (admin)" },
+    Route { method: "POST", path: "/v2/gossip/attestations", summary: "Co-sign a peer's attestation (gossip secret)" },
+    Route { method: "GET", path: "/v2/export/proofs", summary: "Export every proof (admin)" },
+    Route { method: "GET", path: "/v2/export/audit", summary: "Export the audit log (admin)" },
+    Route { method: "POST", path: "/v2/import/proofs", summary: "Import proofs (admin)" },
+    Route { method: "POST", path: "/v2/import/audit", summary: "Import audit entries (admin)" },
+    Route { method: "GET", path: "/openapi.json", summary: "OpenAPI 3 document" },
+    Route { method: "GET", path: "/docs", summary: "API documentation" },
+];
+
+// The unversioned path a `/v1` route is also served under, with
+// `Deprecation`, `Sunset` and `Link` headers pointing at the `/v1` path
+pub fn legacy_path(path: &str) -> Option<&str> {
+    path.strip_prefix("/v1")
+}
+
+// Everything the HTTP handlers share
+#[derive(Clone)]
+pub struct ServerState {
+    pub storage: ProofStorage,
+    pub monero_service: Arc<MoneroService>,
+    pub attestor: Option<Arc<Attestor>>,
+    pub events: EventBus,
+    pub webhooks: Webhooks,
+    pub admin_token: AdminToken,
+    pub shutdown: Shutdown,
+    pub max_sync_lag: u64,
+    pub legacy_sunset: Option<String>,
+    pub policy: PolicyEngine,
+    pub audit: Arc<AuditLog>,
+}
+
+// How often the config file is checked for policy changes
+const POLICY_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+// How often minted proofs are checked for sealing
+const PURGE_INTERVAL: Duration = Duration::from_secs(60);
+
+// Starts the server and its background workers, returns after a graceful
+// shutdown. The policy is reloaded from `config_path` when it changes.
+pub async fn run(config: Config, config_path: Option<PathBuf>) -> Result<()> {
+    let storage: ProofStorage = Arc::new(
+        ProofStore::open(config.storage.data_dir.as_deref())
+            .and_then(|store| store.with_privacy(config.storage.privacy))
+            .context("could not open proof storage")?,
+    );
+    // Background workers are restarted with backoff if they crash
+    let mut supervisor = Supervisor::new(Duration::from_secs(1), Duration::from_secs(60));
+    let shutdown = supervisor.shutdown_signal();
+
+    match storage.data_dir() {
+        Some(dir) => {
+            println!("💾 Persisting proofs to {} ({} loaded)", dir.display(), storage.read().await.len());
+            let flush_interval = Duration::from_secs(config.storage.flush_interval_secs.max(1));
+            let store = storage.clone();
+            supervisor.spawn("storage-flusher", move || store.clone().run_flusher(flush_interval));
+        }
+        None => println!("⚠️  storage.data_dir not set, proofs are kept in memory only"),
+    }
+    if storage.is_private() {
+        println!("🔒 Privacy mode: proofs are stored as commitments and opened with their preimage");
+    }
+    if let Some(after_secs) = config.storage.purge_after_mint_secs {
+        if config.fluent.rpc_url.is_none() {
+            println!("⚠️  storage.purge_after_mint_secs is set but fluent.rpc_url is not, no proof is ever marked minted");
+        }
+        let store = storage.clone();
+        supervisor.spawn("privacy-purger", move || privacy::run_purger(store.clone(), after_secs, PURGE_INTERVAL));
+    }
+
+    // Initialize Monero service
+    let monero_service = Arc::new(MoneroService::new(&config.monero).context("could not set up Monero RPC")?);
+    monero_service.pool.check_health().await;
+    // Refuse to start against a daemon of another network
+    monero_service.check_network()?;
+    println!("🌐 Monero network: {}", monero_service.network);
+    for daemon in monero_service.pool.status() {
+        if daemon.healthy {
+            println!("✅ Monero daemon {} reachable ({} ms)", daemon.url, daemon.latency_ms.unwrap_or_default());
+        } else {
+            println!("⚠️  Warning: Monero daemon {} unreachable", daemon.url);
+        }
+    }
+    if monero_service.pool.quorum() > 1 {
+        println!("🗳️  Quorum mode: {} daemons must agree", monero_service.pool.quorum());
+    }
+    if let Some(url) = &config.monero.wallet {
+        println!("✅ Using Monero wallet at {}", url);
+    }
+    let health_interval = Duration::from_secs(config.monero.health_check_interval_secs.max(1));
+    let pool = monero_service.pool.clone();
+    supervisor.spawn("daemon-health-checks", move || pool.clone().run_health_checks(health_interval));
+
+    // Attestor key from `[attestor]` (ATTESTOR_PRIVKEY by default), ROUTER and CHAIN_ID
+    let attestor = match Attestor::load(&config.attestor) {
+        Ok(Some(attestor)) => {
+            println!("✅ Attestor {} loaded", attestation::format_hex(&attestor.address()));
+            match attestor.previous(events::unix_now()) {
+                Some(previous) => println!(
+                    "🔑 Previous attestor {} co-signs until {}",
+                    attestation::format_hex(&previous.signer.address()),
+                    previous.valid_until
+                ),
+                None if config.attestor.previous.is_some() => {
+                    println!("⚠️  attestor.previous is past its valid_until and no longer signs")
+                }
+                None => {}
+            }
+            if config.bridge.deposit_address.is_none() {
+                println!("⚠️  bridge.deposit_address not set, attestations are refused");
+            }
+            let conversion = WfoidConversion::new(&config.bridge)?;
+            let coordinator = Coordinator::from_env(&config.coordination)?;
+            match &coordinator {
+                Some(coordinator) => println!(
+                    "🤝 Threshold attestation: {} of {} validators",
+                    coordinator.threshold(),
+                    coordinator.nodes()
+                ),
+                None if !config.coordination.peers.is_empty() => {
+                    println!("⚠️  GOSSIP_SECRET not set, coordination.peers are ignored")
+                }
+                None => {}
+            }
+            Some(Arc::new(
+                attestor
+                    .with_network(config.monero.network)
+                    .with_deposit_address(config.bridge.deposit_address.clone())
+                    .with_conversion(conversion)
+                    .with_coordinator(coordinator),
+            ))
+        }
+        Ok(None) => {
+            println!("⚠️  No attestor key configured, attestations are disabled");
+            None
+        }
+        Err(e) => {
+            println!("⚠️  Warning: Could not load attestor key: {}", e);
+            None
+        }
+    };
+
+    let policy = PolicyEngine::new(Policy::new(&config.policy)?);
+    if let Some(path) = config_path {
+        let policy = policy.clone();
+        supervisor.spawn("policy-reloader", move || policy.clone().watch(path.clone(), POLICY_RELOAD_INTERVAL));
+    }
+
+    let audit = match config.audit_path() {
+        Some(path) => {
+            let audit = AuditLog::open(&path).context("could not open the audit log, check it with `monero verify-audit`")?;
+            println!("🧾 Auditing decisions to {}", path.display());
+            Arc::new(audit)
+        }
+        None => {
+            println!("⚠️  Neither audit.path nor storage.data_dir is set, decisions are not audited");
+            Arc::new(AuditLog::disabled())
+        }
+    };
+
+    // Proof lifecycle events and webhook delivery
+    let events = EventBus::new(1024);
+    let webhooks = Webhooks::open(
+        RetryPolicy::default(),
+        config.storage.data_dir.as_deref(),
+        std::env::var("WEBHOOK_DEAD_LETTER_LOG").ok().map(Into::into),
+    )?;
+    if config.storage.data_dir.is_some() {
+        let (endpoints, dead_letters) = webhooks.counts().await;
+        println!("🪝 Loaded {} webhooks and {} dead letters", endpoints, dead_letters);
+    } else {
+        println!("⚠️  storage.data_dir is not set, webhooks and dead letters are lost on restart");
+    }
+    {
+        let webhooks = webhooks.clone();
+        let events = events.clone();
+        supervisor.spawn("webhook-dispatcher", move || webhooks.dispatcher(&events));
+    }
+
+    // Mints of our attestations, read from the router's chain
+    if let Some(url) = &config.fluent.rpc_url {
+        let router = match &attestor {
+            Some(attestor) => Ok(attestor.router),
+            None => std::env::var("ROUTER")
+                .context("ROUTER is not set")
+                .and_then(|router| attestation::parse_address(&router)),
+        };
+        match router {
+            Ok(router) => {
+                println!("🪙 Reconciling mints of router {} via {}", attestation::format_hex(&router), url);
+                let reconciler = Reconciler::new(url, router, &config.fluent, storage.clone(), events.clone());
+                if let Some(attestor) = &attestor {
+                    // The router mints only with the registry's threshold of signatures
+                    let ours = attestor.coordinator.as_ref().map_or(1, |coordinator| coordinator.threshold());
+                    let reconciler = reconciler.clone();
+                    tokio::spawn(async move {
+                        match reconciler.attestation_threshold().await {
+                            Ok(theirs) if theirs != ours as u128 => println!(
+                                "⚠️  AttestorRegistry.threshold is {} but attestations carry {} signatures, they will not mint",
+                                theirs, ours
+                            ),
+                            Ok(_) => {}
+                            Err(e) => println!("⚠️  Could not read AttestorRegistry.threshold: {:#}", e),
+                        }
+                    });
+                }
+                let interval = Duration::from_secs(config.fluent.poll_interval_secs.max(1));
+                supervisor.spawn("mint-reconciler", move || reconciler.clone().run(interval));
+            }
+            Err(e) => println!("⚠️  fluent.rpc_url is set but mints are not reconciled: {:#}", e),
+        }
+    }
+
+    // Proofs past their status's retention window become tombstones
+    let purger = Purger::new(&config.retention, storage.clone(), events.clone(), audit.clone());
+    if purger.enabled() {
+        println!("🗑️  Purging proofs past their retention window");
+        let interval = Duration::from_secs(config.retention.purge_interval_secs.max(1));
+        supervisor.spawn("retention-purger", move || purger.clone().run(interval));
+    }
+
+    // Validated deposits are re-checked until their block is final
+    let watcher = Watcher::new(&config.finality, monero_service.clone(), storage.clone(), events.clone(), audit.clone());
+    let interval = Duration::from_secs(config.finality.check_interval_secs.max(1));
+    supervisor.spawn("finality-watcher", move || watcher.clone().run(interval));
+
+    let routes = routes(ServerState {
+        storage: storage.clone(),
+        monero_service,
+        attestor,
+        events,
+        webhooks,
+        admin_token: AdminToken::from_env(),
+        shutdown: shutdown.clone(),
+        max_sync_lag: config.monero.max_sync_lag_blocks,
+        legacy_sunset: config.server.legacy_sunset.clone(),
+        policy,
+        audit,
+    });
+
+    println!("📡 Server running on http://{}", config.server.bind);
+    println!("🔗 Available endpoints:");
+    for route in ROUTES {
+        println!("   {:<4} {} - {}", route.method, route.path, route.summary);
+    }
+    println!("   Unversioned /proof, /validate, ... are deprecated aliases of /v1");
+
+    // Stop accepting connections on SIGINT/SIGTERM and let in-flight requests
+    // (and their RPC calls) finish, bounded by the shutdown timeout
+    let (_, server) = warp::serve(routes)
+        .try_bind_with_graceful_shutdown(config.server.bind, shutdown.clone().wait())
+        .with_context(|| format!("could not bind {}", config.server.bind))?;
+    let mut server = tokio::spawn(server);
+    tokio::select! {
+        _ = supervisor::termination_signal() => {}
+        _ = &mut server => {
+            println!("❌ HTTP server stopped unexpectedly");
+        }
+    }
+
+    let drain_timeout = Duration::from_secs(config.server.shutdown_timeout_secs);
+    println!("🛑 Shutting down, draining requests for up to {:?}...", drain_timeout);
+    supervisor.request_shutdown();
+    if tokio::time::timeout(drain_timeout, &mut server).await.is_err() {
+        println!("⚠️  Drain timeout reached, dropping remaining connections");
+        server.abort();
+    }
+    supervisor.shutdown(drain_timeout).await;
+
+    match storage.flush().await {
+        Ok(()) if storage.data_dir().is_some() => println!("💾 Proofs flushed to disk"),
+        Ok(()) => {}
+        Err(e) => println!("❌ Final proof flush failed: {:#}", e),
+    }
+    println!("👋 Shutdown complete");
+    Ok(())
+}
+
+pub fn routes(
+    state: ServerState,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    // CORS headers; browsers only show the deprecation headers to the dapp when exposed
+    let cors = warp::cors()
+        .allow_any_origin()
+        .allow_headers(vec!["content-type", "authorization"])
+        .allow_methods(vec!["GET", "POST", "DELETE", "OPTIONS"])
+        .expose_headers(vec!["deprecation", "sunset", "link"]);
+
+    // Health check endpoints
+    let health = warp::path("health")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_monero_service(state.monero_service.clone()))
+        .map(handle_health);
+
+    let health_live = warp::path!("health" / "live")
+        .and(warp::get())
+        .map(handle_health_live);
+
+    let max_sync_lag = state.max_sync_lag;
+    let health_ready = warp::path!("health" / "ready")
+        .and(warp::get())
+        .and(with_monero_service(state.monero_service.clone()))
+        .and(with_storage(state.storage.clone()))
+        .and(with_attestor(state.attestor.clone()))
+        .and_then(move |monero_service, storage, attestor| {
+            handle_health_ready(monero_service, storage, attestor, max_sync_lag)
+        });
+
+    // `/v1` keeps the original response shapes, `/v2` returns richer proofs.
+    // Events and webhooks are the same in both.
+    let v1 = proof_routes_v1(state.clone()).or(event_and_webhook_routes(state.clone()));
+    let v2 = proof_routes_v2(state.clone()).or(event_and_webhook_routes(state.clone()));
+    // The original unversioned paths stay as deprecated aliases of `/v1`
+    let legacy = deprecated(v1.clone(), state.legacy_sunset.as_deref());
+
+    // Machine-readable spec and a browsable UI for it
+    let openapi_json = warp::path!("openapi.json")
+        .and(warp::get())
+        .map(openapi::handle_openapi);
+
+    let docs = warp::path!("docs")
+        .and(warp::get())
+        .map(openapi::handle_docs);
+
+    health
+        .or(health_live)
+        .or(health_ready)
+        .or(warp::path("v1").and(v1))
+        .or(warp::path("v2").and(v2))
+        .or(openapi_json)
+        .or(docs)
+        .or(legacy)
+        .recover(handle_rejection)
+        .with(cors)
+}
+
+fn proof_routes_v1(
+    state: ServerState,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    // Generate transaction proof endpoint
+    let generate_proof = warp::path("proof")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(with_storage(state.storage.clone()))
+        .and(with_monero_service(state.monero_service.clone()))
+        .and(with_events(state.events.clone()))
+        .and_then(handle_generate_proof);
+
+    // Validate transaction endpoint
+    let validate_tx = warp::path("validate")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(with_storage(state.storage.clone()))
+        .and(with_monero_service(state.monero_service.clone()))
+        .and(with_events(state.events.clone()))
+        .and(with_audit(state.audit.clone()))
+        .and_then(handle_validate_transaction);
+
+    // Get all proofs endpoint
+    let get_proofs = warp::path("proofs")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_storage(state.storage.clone()))
+        .and_then(handle_get_proofs);
+
+    // Get specific proof endpoint
+    let get_proof = warp::path("proof")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_storage(state.storage.clone()))
+        .and_then(handle_get_proof);
+
+    // Tombstone a proof for whoever presents its deposit data
+    let delete_proof = warp::path("proof")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(json_body())
+        .and(with_storage(state.storage.clone()))
+        .and(with_events(state.events.clone()))
+        .and(with_audit(state.audit.clone()))
+        .and_then(handle_delete_proof);
+
+    // Sign a LockProof for a validated proof
+    let attest_proof = warp::path("proof")
+        .and(warp::path::param::<String>())
+        .and(warp::path("attest"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(with_storage(state.storage.clone()))
+        .and(with_attestor(state.attestor.clone()))
+        .and(with_policy(state.policy.clone()))
+        .and(with_events(state.events.clone()))
+        .and(with_audit(state.audit.clone()))
+        .and_then(handle_attest_proof);
+
+    attest_proof
+        .or(generate_proof)
+        .or(validate_tx)
+        .or(get_proofs)
+        .or(get_proof)
+        .or(delete_proof)
+}
+
+// Same paths as `/v1`, every proof comes back as a `ProofV2`
+fn proof_routes_v2(
+    state: ServerState,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let generate_proof = warp::path("proof")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(with_storage(state.storage.clone()))
+        .and(with_monero_service(state.monero_service.clone()))
+        .and(with_events(state.events.clone()))
+        .and_then(handle_generate_proof_v2);
+
+    let validate_tx = warp::path("validate")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(with_storage(state.storage.clone()))
+        .and(with_monero_service(state.monero_service.clone()))
+        .and(with_events(state.events.clone()))
+        .and(with_audit(state.audit.clone()))
+        .and_then(handle_validate_transaction_v2);
+
+    let get_proofs = warp::path("proofs")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_storage(state.storage.clone()))
+        .and(with_monero_service(state.monero_service.clone()))
+        .and_then(handle_get_proofs_v2);
+
+    let get_proof = warp::path("proof")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_storage(state.storage.clone()))
+        .and(with_monero_service(state.monero_service.clone()))
+        .and_then(handle_get_proof_v2);
+
+    let delete_proof = warp::path("proof")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(json_body())
+        .and(with_storage(state.storage.clone()))
+        .and(with_events(state.events.clone()))
+        .and(with_audit(state.audit.clone()))
+        .and_then(handle_delete_proof_v2);
+
+    let attest_proof = warp::path("proof")
+        .and(warp::path::param::<String>())
+        .and(warp::path("attest"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(with_storage(state.storage.clone()))
+        .and(with_monero_service(state.monero_service.clone()))
+        .and(with_attestor(state.attestor.clone()))
+        .and(with_policy(state.policy.clone()))
+        .and(with_events(state.events.clone()))
+        .and(with_audit(state.audit.clone()))
+        .and_then(handle_attest_proof_v2);
+
+    let open_proof = warp::path("proof")
+        .and(warp::path::param::<String>())
+        .and(warp::path("open"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(json_body())
+        .and(with_storage(state.storage.clone()))
+        .and(with_monero_service(state.monero_service.clone()))
+        .and_then(handle_open_proof_v2);
+
+    let get_attestations = warp::path("attestations")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_storage(state.storage.clone()))
+        .and_then(handle_get_attestations_v2);
+
+    attest_proof
+        .or(generate_proof)
+        .or(validate_tx)
+        .or(get_proofs)
+        .or(get_proof)
+        .or(delete_proof)
+        .or(open_proof)
+        .or(get_attestations)
+}
+
+fn event_and_webhook_routes(
+    state: ServerState,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    // Webhook management (admin token required)
+    let register_webhook = warp::path("webhooks")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(with_admin(state.admin_token.clone()))
+        .and(json_body())
+        .and(with_webhooks(state.webhooks.clone()))
+        .and_then(handle_register_webhook);
+
+    let list_webhooks = warp::path("webhooks")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(with_admin(state.admin_token.clone()))
+        .and(with_webhooks(state.webhooks.clone()))
+        .and_then(handle_list_webhooks);
+
+    let delete_webhook = warp::path("webhooks")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(with_admin(state.admin_token.clone()))
+        .and(with_webhooks(state.webhooks.clone()))
+        .and_then(handle_delete_webhook);
+
+    let list_dead_letters = warp::path!("webhooks" / "dead-letters")
+        .and(warp::get())
+        .and(with_admin(state.admin_token.clone()))
+        .and(with_webhooks(state.webhooks.clone()))
+        .and_then(handle_list_dead_letters);
+
+    let replay_dead_letter = warp::path!("webhooks" / "dead-letters" / String / "replay")
+        .and(warp::post())
+        .and(with_admin(state.admin_token.clone()))
+        .and(with_webhooks(state.webhooks.clone()))
+        .and_then(handle_replay_dead_letter);
+
+    // Live proof lifecycle events, filterable and resumable
+    let events_sse = warp::path("events")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<EventFilter>())
+        .and(warp::sse::last_event_id::<u64>())
+        .and(with_events(state.events.clone()))
+        .and(with_shutdown(state.shutdown.clone()))
+        .map(handle_events_sse);
+
+    // Peer validators asking this node to co-sign (gossip secret required)
+    let gossip_attestation = warp::path!("gossip" / "attestations")
+        .and(warp::post())
+        .and(warp::header::optional::<String>(SIGNATURE_HEADER))
+        .and(warp::body::content_length_limit(MAX_BODY_BYTES))
+        .and(warp::body::bytes())
+        .and(with_storage(state.storage.clone()))
+        .and(with_monero_service(state.monero_service.clone()))
+        .and(with_attestor(state.attestor.clone()))
+        .and(with_policy(state.policy.clone()))
+        .and(with_audit(state.audit.clone()))
+        .and_then(handle_gossip_attestation);
+
+    let events_ws = warp::path!("events" / "ws")
+        .and(warp::ws())
+        .and(warp::query::<EventFilter>())
+        .and(with_events(state.events.clone()))
+        .and(with_shutdown(state.shutdown.clone()))
+        .map(handle_events_ws);
+
+    // Bulk export and import for accounting and migration (admin token required)
+    let export_proofs = warp::path!("export" / "proofs")
+        .and(warp::get())
+        .and(warp::query::<ExportQuery>())
+        .and(with_admin(state.admin_token.clone()))
+        .and(with_storage(state.storage.clone()))
+        .and_then(handle_export_proofs);
+
+    let export_audit = warp::path!("export" / "audit")
+        .and(warp::get())
+        .and(warp::query::<ExportQuery>())
+        .and(with_admin(state.admin_token.clone()))
+        .and(with_audit(state.audit.clone()))
+        .and_then(handle_export_audit);
+
+    let import_proofs = warp::path!("import" / "proofs")
+        .and(warp::post())
+        .and(warp::query::<ImportQuery>())
+        .and(with_admin(state.admin_token.clone()))
+        .and(warp::body::content_length_limit(MAX_IMPORT_BYTES))
+        .and(warp::body::bytes())
+        .and(with_storage(state.storage.clone()))
+        .and_then(handle_import_proofs);
+
+    let import_audit = warp::path!("import" / "audit")
+        .and(warp::post())
+        .and(warp::query::<ImportQuery>())
+        .and(with_admin(state.admin_token.clone()))
+        .and(warp::body::content_length_limit(MAX_IMPORT_BYTES))
+        .and(warp::body::bytes())
+        .and(with_audit(state.audit.clone()))
+        .and_then(handle_import_audit);
+
+    register_webhook
+        .or(list_webhooks)
+        .or(list_dead_letters)
+        .or(replay_dead_letter)
+        .or(delete_webhook)
+        .or(events_ws)
+        .or(events_sse)
+        .or(gossip_attestation)
+        .or(export_proofs)
+        .or(export_audit)
+        .or(import_proofs)
+        .or(import_audit)
+}
+
+// Serves `filter` at the root with `Deprecation` and `Sunset` headers and a
+// `successor-version` link to the same path under `/v1`
+fn deprecated<F, R>(
+    filter: F,
+    sunset: Option<&str>,
+) -> impl Filter<Extract = (warp::reply::Response,), Error = warp::Rejection> + Clone + use<F, R>
+where
+    F: Filter<Extract = (R,), Error = warp::Rejection> + Clone + Send + Sync + 'static,
+    R: warp::Reply,
+{
+    let sunset = sunset.and_then(|sunset| HeaderValue::from_str(sunset).ok());
+    warp::path::full()
+        .and(filter)
+        .map(move |path: warp::path::FullPath, reply: R| {
+            let mut response = warp::Reply::into_response(reply);
+            let headers = response.headers_mut();
+            headers.insert("deprecation", HeaderValue::from_static("true"));
+            if let Some(sunset) = &sunset {
+                headers.insert("sunset", sunset.clone());
+            }
+            if let Ok(link) = HeaderValue::from_str(&format!("</v1{}>; rel=\"successor-version\"", path.as_str())) {
+                headers.insert("link", link);
+            }
+            response
+        })
+}
+
+// Largest accepted request body
+pub const MAX_BODY_BYTES: u64 = 16 * 1024;
+
+// Largest accepted `/import` body
+pub const MAX_IMPORT_BYTES: u64 = 64 * 1024 * 1024;
+
+// A size-limited JSON body that has passed `Validate`; failures reject with
+// `InvalidRequest` and are answered by `handle_rejection`
+fn json_body<T>() -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone
+where
+    T: DeserializeOwned + Validate + Send,
+{
+    warp::body::content_length_limit(MAX_BODY_BYTES)
+        .and(warp::body::bytes())
+        .and_then(|body: Bytes| async move { validation::parse::<T>(&body).map_err(warp::reject::custom) })
+}
+
+// Turns body rejections into the JSON error envelope; everything else keeps
+// warp's default handling
+async fn handle_rejection(rejection: warp::Rejection) -> Result<warp::reply::Response, warp::Rejection> {
+    use warp::Reply;
+    if let Some(InvalidRequest(fields)) = rejection.find() {
+        let body = ErrorResponse {
+            error: "Invalid request".to_string(),
+            message: InvalidRequest(fields.clone()).to_string(),
+            fields: fields.clone(),
+        };
+        return Ok(warp::reply::with_status(warp::reply::json(&body), warp::http::StatusCode::BAD_REQUEST).into_response());
+    }
+    if rejection.find::<warp::reject::PayloadTooLarge>().is_some() {
+        let message = format!("Request bodies are limited to {} bytes, {} for imports", MAX_BODY_BYTES, MAX_IMPORT_BYTES);
+        return Ok(json_error("Payload too large", message, warp::http::StatusCode::PAYLOAD_TOO_LARGE).into_response());
+    }
+    if rejection.find::<warp::reject::LengthRequired>().is_some() {
+        let message = "Send the body with a Content-Length header";
+        return Ok(json_error("Length required", message, warp::http::StatusCode::LENGTH_REQUIRED).into_response());
+    }
+    Err(rejection)
+}
+
+fn with_storage(
+    storage: ProofStorage,
+) -> impl Filter<Extract = (ProofStorage,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || storage.clone())
+}
+
+fn with_monero_service(
+    service: Arc<MoneroService>,
+) -> impl Filter<Extract = (Arc<MoneroService>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || service.clone())
+}
+
+fn with_events(
+    events: EventBus,
+) -> impl Filter<Extract = (EventBus,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || events.clone())
+}
+
+fn with_webhooks(
+    webhooks: Webhooks,
+) -> impl Filter<Extract = (Webhooks,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || webhooks.clone())
+}
+
+fn with_attestor(
+    attestor: Option<Arc<Attestor>>,
+) -> impl Filter<Extract = (Option<Arc<Attestor>>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || attestor.clone())
+}
+
+fn with_audit(
+    audit: Arc<AuditLog>,
+) -> impl Filter<Extract = (Arc<AuditLog>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || audit.clone())
+}
+
+fn with_policy(
+    policy: PolicyEngine,
+) -> impl Filter<Extract = (PolicyEngine,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || policy.clone())
+}
+
+fn with_shutdown(
+    shutdown: Shutdown,
+) -> impl Filter<Extract = (Shutdown,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || shutdown.clone())
+}
+
+// Resolves to whether the request carries the admin bearer token
+fn with_admin(
+    token: AdminToken,
+) -> impl Filter<Extract = (bool,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .map(move |header: Option<String>| token.authorizes(header.as_deref()))
+}
+
+fn json_error(
+    error: &str,
+    message: impl std::fmt::Display,
+    status: warp::http::StatusCode,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({
+            "error": error,
+            "message": message.to_string()
+        })),
+        status,
+    )
+}
+
+// Open circuit breakers fast-fail with 503 so clients back off
+fn rpc_error_status(error: &anyhow::Error) -> warp::http::StatusCode {
+    if resilience::is_circuit_open(error) {
+        warp::http::StatusCode::SERVICE_UNAVAILABLE
+    } else if network::is_wrong_network(error) {
+        warp::http::StatusCode::BAD_REQUEST
+    } else if proofs::is_duplicate_deposit(error) {
+        warp::http::StatusCode::CONFLICT
+    } else {
+        warp::http::StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+// `error` is what failed, `message` the underlying RPC error
+fn rpc_failure(error: &anyhow::Error) -> warp::reply::WithStatus<warp::reply::Json> {
+    let cause = error.chain().skip(1).map(ToString::to_string).collect::<Vec<_>>().join(": ");
+    json_error(&error.to_string(), cause, rpc_error_status(error))
+}
+
+fn unauthorized() -> warp::reply::WithStatus<warp::reply::Json> {
+    json_error(
+        "Unauthorized",
+        "A valid admin bearer token is required",
+        warp::http::StatusCode::UNAUTHORIZED,
+    )
+}
+
+// Degraded while any RPC breaker is open, unhealthy once every daemon's is
+#[utoipa::path(get, path = "/health", tag = "health",
+    responses(
+        (status = 200, description = "Healthy or degraded", body = HealthResponse),
+        (status = 503, description = "Every daemon circuit breaker is open", body = HealthResponse),
+    ))]
+pub(crate) fn handle_health(monero_service: Arc<MoneroService>) -> warp::reply::WithStatus<warp::reply::Json> {
+    let daemons = monero_service.pool.status();
+    let wallet = monero_service.wallet.as_ref().and_then(|wallet| wallet.status().pop());
+    let tripped = daemons
+        .iter()
+        .chain(wallet.iter())
+        .any(|endpoint| endpoint.breaker != resilience::BreakerState::Closed);
+    let (status, code) = if monero_service.pool.all_open() {
+        ("unhealthy", warp::http::StatusCode::SERVICE_UNAVAILABLE)
+    } else if tripped {
+        ("degraded", warp::http::StatusCode::OK)
+    } else {
+        ("healthy", warp::http::StatusCode::OK)
+    };
+    warp::reply::with_status(
+        warp::reply::json(&HealthResponse {
+            status: status.to_string(),
+            service: "monero-validation-server".to_string(),
+            version: health::VERSION.to_string(),
+            network: monero_service.network,
+            daemons,
+            wallet,
+        }),
+        code,
+    )
+}
+
+// The process is up and serving requests
+#[utoipa::path(get, path = "/health/live", tag = "health",
+    responses((status = 200, description = "The process is serving requests", body = LivenessResponse)))]
+pub(crate) fn handle_health_live() -> warp::reply::Json {
+    warp::reply::json(&LivenessResponse {
+        status: "alive".to_string(),
+        version: health::VERSION.to_string(),
+    })
+}
+
+// 503 until every dependency check passes
+#[utoipa::path(get, path = "/health/ready", tag = "health",
+    responses(
+        (status = 200, description = "Every dependency check passed", body = ReadinessResponse),
+        (status = 503, description = "At least one check failed", body = ReadinessResponse),
+    ))]
+pub(crate) async fn handle_health_ready(
+    monero_service: Arc<MoneroService>,
+    storage: ProofStorage,
+    attestor: Option<Arc<Attestor>>,
+    max_sync_lag: u64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let readiness = health::readiness(&monero_service, &storage, attestor.as_ref(), max_sync_lag).await;
+    let (status, code) = if readiness.ready {
+        ("ready", warp::http::StatusCode::OK)
+    } else {
+        ("not_ready", warp::http::StatusCode::SERVICE_UNAVAILABLE)
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ReadinessResponse {
+            status: status.to_string(),
+            version: health::VERSION.to_string(),
+            checks: readiness.checks,
+        }),
+        code,
+    ))
+}
+
+#[utoipa::path(post, path = "/v1/proof", tag = "proofs", request_body = ProofRequest,
+    responses(
+        (status = 200, description = "Proof created from the wallet's tx key", body = ProofResponse),
+        (status = 400, description = "Invalid request body; `fields` lists each problem", body = ErrorResponse),
+        (status = 409, description = "Another live proof claims this transaction", body = ErrorResponse),
+        (status = 500, description = "Wallet or daemon RPC failed", body = ErrorResponse),
+        (status = 503, description = "Every daemon circuit breaker is open", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_generate_proof(
+    request: ProofRequest,
+    storage: ProofStorage,
+    monero_service: Arc<MoneroService>,
+    events: EventBus,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    println!("🔐 Generating proof for TX: {}", request.tx_id);
+
+    match proofs::create_proof(&monero_service, &storage, &events, &request).await {
+        Ok(proof) => Ok(warp::reply::with_status(
+            warp::reply::json(&ProofResponse {
+                proof_id: proof.proof_id,
+                tx_key: proof.tx_key,
+                message: "Proof generated successfully using Monero RPC".to_string(),
+            }),
+            warp::http::StatusCode::OK,
+        )),
+        Err(e) => {
+            println!("❌ Error generating proof: {:#}", e);
+            Ok(rpc_failure(&e))
+        }
+    }
+}
+
+#[utoipa::path(post, path = "/v1/validate", tag = "proofs", request_body = ValidationRequest,
+    responses(
+        (status = 200, description = "Validation result; `valid` is false if the key does not match", body = ValidationResponse),
+        (status = 400, description = "Invalid request body; `fields` lists each problem", body = ErrorResponse),
+        (status = 500, description = "Daemon RPC failed or daemons disagree", body = ErrorResponse),
+        (status = 503, description = "Every daemon circuit breaker is open", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_validate_transaction(
+    request: ValidationRequest,
+    storage: ProofStorage,
+    monero_service: Arc<MoneroService>,
+    events: EventBus,
+    audit: Arc<AuditLog>,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    println!("✅ Validating transaction: {}", request.tx_id);
+
+    match proofs::validate(&monero_service, &storage, &events, &audit, &request).await {
+        Ok(validation) => Ok(warp::reply::with_status(
+            warp::reply::json(&validation.response()),
+            warp::http::StatusCode::OK,
+        )),
+        Err(e) => {
+            println!("❌ Error validating transaction: {:#}", e);
+            Ok(rpc_failure(&e))
+        }
+    }
+}
+
+#[utoipa::path(get, path = "/v1/proofs", tag = "proofs",
+    responses((status = 200, description = "Every stored proof, without its tx key", body = [ProofV1])))]
+pub(crate) async fn handle_get_proofs(
+    storage: ProofStorage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    println!("📋 Getting all proofs");
+    
+    // Committed proofs are only shown to whoever holds their preimage
+    let storage = storage.read().await;
+    let proofs: Vec<ProofV1> =
+        storage.values().filter(|proof| listed(proof)).map(|proof| privacy::public(proof).into()).collect();
+    
+    Ok(warp::reply::json(&proofs))
+}
+
+#[utoipa::path(get, path = "/v1/proof/{id}", tag = "proofs",
+    params(("id" = String, Path, description = "Proof id")),
+    responses(
+        (status = 200, description = "The proof", body = ProofV1),
+        (status = 403, description = "Stored as a commitment, open it with `POST /v2/proof/{id}/open`", body = ErrorResponse),
+        (status = 404, description = "Unknown proof id", body = ErrorResponse),
+        (status = 410, description = "The proof was deleted", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_get_proof(
+    proof_id: String,
+    storage: ProofStorage,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    println!("🔍 Getting proof: {}", proof_id);
+    
+    let storage = storage.read().await;
+    
+    match storage.get(&proof_id) {
+        Some(proof) if proof.status == ProofStatus::Deleted => Ok(deleted_proof()),
+        Some(proof) if proof.commitment.is_some() => Ok(preimage_required()),
+        Some(proof) => Ok(warp::reply::with_status(
+            warp::reply::json(&ProofV1::from(privacy::public(proof))),
+            warp::http::StatusCode::OK,
+        )),
+        None => Ok(json_error("Proof not found", proof_id, warp::http::StatusCode::NOT_FOUND)),
+    }
+}
+
+
+#[utoipa::path(post, path = "/v1/proof/{id}/attest", tag = "proofs", request_body = AttestRequest,
+    params(("id" = String, Path, description = "Proof id")),
+    responses(
+        (status = 200, description = "Signed LockProof for `BridgeRouter.mintWithAttestation`", body = AttestResponse),
+        (status = 400, description = "Invalid destination or transaction id", body = ErrorResponse),
+        (status = 403, description = "`preimage` is missing or does not match the proof's deposit", body = ErrorResponse),
+        (status = 404, description = "Unknown proof id", body = ErrorResponse),
+        (status = 409, description = "Proof not validated or already minted, its transaction attested through another proof, its attestation is still live, or a re-issue changes the destination", body = ErrorResponse),
+        (status = 422, description = "Not paid to the bridge's deposit address, no received amount, rejected by the deposit policy, or worth no wFOID", body = ErrorResponse),
+        (status = 500, description = "Signing failed, or the audit log is not writable", body = ErrorResponse),
+        (status = 503, description = "No attestor key or deposit address configured, or too few peers co-signed", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_attest_proof(
+    proof_id: String,
+    request: AttestRequest,
+    storage: ProofStorage,
+    attestor: Option<Arc<Attestor>>,
+    policy: PolicyEngine,
+    events: EventBus,
+    audit: Arc<AuditLog>,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    println!("✍️  Attesting proof: {}", proof_id);
+
+    match proofs::attest(attestor.as_deref(), &policy.current(), &storage, &events, &audit, &proof_id, &request).await {
+        Ok(attestation) => Ok(warp::reply::with_status(
+            warp::reply::json(&AttestResponse {
+                proof_id,
+                attestation,
+            }),
+            warp::http::StatusCode::OK,
+        )),
+        Err(e) => Ok(attest_failure(&e)),
+    }
+}
+
+// What `/proofs` shows: neither committed proofs nor tombstones
+fn listed(proof: &TransactionProof) -> bool {
+    proof.commitment.is_none() && proof.status != ProofStatus::Deleted
+}
+
+fn deleted_proof() -> warp::reply::WithStatus<warp::reply::Json> {
+    json_error("Proof deleted", "Only the proof id is kept", warp::http::StatusCode::GONE)
+}
+
+fn preimage_required() -> warp::reply::WithStatus<warp::reply::Json> {
+    json_error(
+        "Preimage required",
+        "This proof is stored as a commitment; open it with POST /v2/proof/{id}/open",
+        warp::http::StatusCode::FORBIDDEN,
+    )
+}
+
+#[utoipa::path(delete, path = "/v1/proof/{id}", tag = "proofs", request_body = ProofPreimage,
+    params(("id" = String, Path, description = "Proof id")),
+    responses(
+        (status = 200, description = "Deleted; only a tombstone with the proof id is kept", body = DeletedResponse),
+        (status = 400, description = "Invalid request body; `fields` lists each problem", body = ErrorResponse),
+        (status = 403, description = "The deposit data does not match the proof", body = ErrorResponse),
+        (status = 404, description = "Unknown proof id", body = ErrorResponse),
+        (status = 409, description = "Its attestation can still be minted or counts towards today's caps", body = ErrorResponse),
+        (status = 410, description = "Already deleted", body = ErrorResponse),
+        (status = 500, description = "The audit log is not writable", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_delete_proof(
+    proof_id: String,
+    preimage: ProofPreimage,
+    storage: ProofStorage,
+    events: EventBus,
+    audit: Arc<AuditLog>,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    println!("🗑️  Deleting proof: {}", proof_id);
+
+    match retention::delete(&storage, &events, &audit, &proof_id, &preimage).await {
+        Ok(()) => Ok(warp::reply::with_status(
+            warp::reply::json(&DeletedResponse { deleted: proof_id }),
+            warp::http::StatusCode::OK,
+        )),
+        Err(e) => Ok(delete_failure(&e)),
+    }
+}
+
+#[utoipa::path(delete, path = "/v2/proof/{id}", tag = "proofs", request_body = ProofPreimage,
+    params(("id" = String, Path, description = "Proof id")),
+    responses(
+        (status = 200, description = "Deleted; only a tombstone with the proof id is kept", body = DeletedResponse),
+        (status = 400, description = "Invalid request body; `fields` lists each problem", body = ErrorResponse),
+        (status = 403, description = "The deposit data does not match the proof", body = ErrorResponse),
+        (status = 404, description = "Unknown proof id", body = ErrorResponse),
+        (status = 409, description = "Its attestation can still be minted or counts towards today's caps", body = ErrorResponse),
+        (status = 410, description = "Already deleted", body = ErrorResponse),
+        (status = 500, description = "The audit log is not writable", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_delete_proof_v2(
+    proof_id: String,
+    preimage: ProofPreimage,
+    storage: ProofStorage,
+    events: EventBus,
+    audit: Arc<AuditLog>,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    handle_delete_proof(proof_id, preimage, storage, events, audit).await
+}
+
+fn delete_failure(e: &DeleteError) -> warp::reply::WithStatus<warp::reply::Json> {
+    let status = match e {
+        DeleteError::NotFound(_) => warp::http::StatusCode::NOT_FOUND,
+        DeleteError::AlreadyDeleted => warp::http::StatusCode::GONE,
+        DeleteError::NotOwner => warp::http::StatusCode::FORBIDDEN,
+        DeleteError::Retained(_) => warp::http::StatusCode::CONFLICT,
+        DeleteError::Audit(_) => {
+            println!("❌ Error deleting proof: {}", e);
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    json_error(e.title(), e, status)
+}
+
+fn attest_failure(e: &AttestError) -> warp::reply::WithStatus<warp::reply::Json> {
+    let status = match e {
+        AttestError::NotConfigured => warp::http::StatusCode::SERVICE_UNAVAILABLE,
+        AttestError::InvalidDestination(_) | AttestError::InvalidTransactionId(_) => {
+            warp::http::StatusCode::BAD_REQUEST
+        }
+        AttestError::NotFound(_) => warp::http::StatusCode::NOT_FOUND,
+        AttestError::DuplicateDeposit(_) => warp::http::StatusCode::CONFLICT,
+        AttestError::PreimageRequired | AttestError::PreimageMismatch => warp::http::StatusCode::FORBIDDEN,
+        AttestError::AlreadyAttested
+        | AttestError::Orphaned
+        | AttestError::WrongNetwork(_)
+        | AttestError::StillLive(_)
+        | AttestError::ReissueDestination(_)
+        | AttestError::NotValidated => warp::http::StatusCode::CONFLICT,
+        AttestError::UnknownAmount
+        | AttestError::NotBridgeDeposit
+        | AttestError::Policy(_)
+        | AttestError::Conversion(_) => {
+            warp::http::StatusCode::UNPROCESSABLE_ENTITY
+        }
+        AttestError::Signing(_) | AttestError::Audit(_) => {
+            println!("❌ Error signing attestation: {}", e);
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR
+        }
+        AttestError::Threshold(_) => {
+            println!("⚠️  {}", e);
+            warp::http::StatusCode::SERVICE_UNAVAILABLE
+        }
+    };
+    json_error(e.title(), e, status)
+}
+
+#[utoipa::path(post, path = "/v1/gossip/attestations", tag = "coordination", request_body = CosignRequest,
+    security(("gossip_signature" = [])),
+    responses(
+        (status = 200, description = "This node's signature over the LockProof", body = PartialSignature),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Missing, stale or wrong gossip signature", body = ErrorResponse),
+        (status = 409, description = "The LockProof is not what this node would attest", body = ErrorResponse),
+        (status = 422, description = "Rejected by this node's deposit policy", body = ErrorResponse),
+        (status = 500, description = "Daemon error while verifying the deposit, or the audit log is not writable", body = ErrorResponse),
+        (status = 503, description = "Coordination not enabled on this node, or a daemon circuit is open", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_gossip_attestation(
+    signature: Option<String>,
+    body: Bytes,
+    storage: ProofStorage,
+    monero_service: Arc<MoneroService>,
+    attestor: Option<Arc<Attestor>>,
+    policy: PolicyEngine,
+    audit: Arc<AuditLog>,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    let Some(attestor) = attestor.filter(|attestor| attestor.coordinator.is_some()) else {
+        return Ok(cosign_failure(&CosignError::NotConfigured));
+    };
+    let coordinator = attestor.coordinator.as_ref().expect("filtered above");
+    if !coordinator.authorizes(signature.as_deref(), &body) {
+        return Ok(json_error(
+            "Unauthorized",
+            "A valid gossip signature is required",
+            warp::http::StatusCode::UNAUTHORIZED,
+        ));
+    }
+    let request: CosignRequest = validation::parse(&body).map_err(warp::reject::custom)?;
+    println!("🤝 Co-signing lock {} for a peer", attestation::format_hex(&request.lock_proof.lock_id));
+
+    match coordination::cosign(&attestor, &policy.current(), &monero_service, &storage, &audit, &request).await {
+        Ok(partial) => Ok(warp::reply::with_status(warp::reply::json(&partial), warp::http::StatusCode::OK)),
+        Err(e) => Ok(cosign_failure(&e)),
+    }
+}
+
+fn cosign_failure(e: &CosignError) -> warp::reply::WithStatus<warp::reply::Json> {
+    let status = match e {
+        CosignError::NotConfigured => warp::http::StatusCode::SERVICE_UNAVAILABLE,
+        CosignError::Mismatch(_) => warp::http::StatusCode::CONFLICT,
+        CosignError::Policy(_) => warp::http::StatusCode::UNPROCESSABLE_ENTITY,
+        CosignError::Verification(e) => return rpc_failure(e),
+        CosignError::Signing(_) | CosignError::Audit(_) => {
+            println!("❌ Error co-signing attestation: {}", e);
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    json_error(e.title(), e, status)
+}
+
+// Looks up confirmations for `proofs`. A daemon failure leaves them null
+// rather than failing the request.
+async fn proofs_v2(monero_service: &MoneroService, proofs: Vec<TransactionProof>) -> Vec<ProofV2> {
+    let tx_ids: Vec<&str> = proofs.iter().map(|proof| proof.tx_id.as_str()).collect();
+    let confirmations = match monero_service.confirmations(&tx_ids).await {
+        Ok(confirmations) => confirmations,
+        Err(e) => {
+            println!("⚠️  Could not fetch confirmations: {:#}", e);
+            HashMap::new()
+        }
+    };
+    proofs
+        .into_iter()
+        .map(|proof| {
+            let confirmations = confirmations.get(&proof.tx_id).copied();
+            ProofV2::new(proof, confirmations)
+        })
+        .collect()
+}
+
+async fn proof_v2(monero_service: &MoneroService, proof: TransactionProof) -> ProofV2 {
+    proofs_v2(monero_service, vec![proof]).await.remove(0)
+}
+
+#[utoipa::path(post, path = "/v2/proof", tag = "proofs", request_body = ProofRequest,
+    responses(
+        (status = 201, description = "Proof created from the wallet's tx key", body = ProofV2),
+        (status = 400, description = "Invalid request body; `fields` lists each problem", body = ErrorResponse),
+        (status = 409, description = "Another live proof claims this transaction", body = ErrorResponse),
+        (status = 500, description = "Wallet or daemon RPC failed", body = ErrorResponse),
+        (status = 503, description = "Every daemon circuit breaker is open", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_generate_proof_v2(
+    request: ProofRequest,
+    storage: ProofStorage,
+    monero_service: Arc<MoneroService>,
+    events: EventBus,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    println!("🔐 Generating proof for TX: {}", request.tx_id);
+
+    match proofs::create_proof(&monero_service, &storage, &events, &request).await {
+        Ok(proof) => Ok(warp::reply::with_status(
+            warp::reply::json(&proof_v2(&monero_service, proof).await),
+            warp::http::StatusCode::CREATED,
+        )),
+        Err(e) => {
+            println!("❌ Error generating proof: {:#}", e);
+            Ok(rpc_failure(&e))
+        }
+    }
+}
+
+#[utoipa::path(post, path = "/v2/validate", tag = "proofs", request_body = ValidationRequest,
+    responses(
+        (status = 200, description = "Validation result and the matching stored proof, if any", body = ValidationResponseV2),
+        (status = 400, description = "Invalid request body; `fields` lists each problem", body = ErrorResponse),
+        (status = 500, description = "Daemon RPC failed or daemons disagree", body = ErrorResponse),
+        (status = 503, description = "Every daemon circuit breaker is open", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_validate_transaction_v2(
+    request: ValidationRequest,
+    storage: ProofStorage,
+    monero_service: Arc<MoneroService>,
+    events: EventBus,
+    audit: Arc<AuditLog>,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    println!("✅ Validating transaction: {}", request.tx_id);
+
+    match proofs::validate(&monero_service, &storage, &events, &audit, &request).await {
+        Ok(validation) => {
+            let confirmations = match monero_service.confirmations(&[request.tx_id.as_str()]).await {
+                Ok(confirmations) => confirmations.get(&request.tx_id).copied(),
+                Err(e) => {
+                    println!("⚠️  Could not fetch confirmations: {:#}", e);
+                    None
+                }
+            };
+            Ok(warp::reply::with_status(
+                warp::reply::json(&ValidationResponseV2 {
+                    valid: validation.valid,
+                    amount: validation.amount,
+                    amount_xmr: validation.amount.map(Piconero::xmr),
+                    outputs: validation.outputs,
+                    confirmations,
+                    proof: validation.proof.map(|proof| ProofV2::new(proof, confirmations)),
+                    network: validation.network,
+                }),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        Err(e) => {
+            println!("❌ Error validating transaction: {:#}", e);
+            Ok(rpc_failure(&e))
+        }
+    }
+}
+
+#[utoipa::path(get, path = "/v2/proofs", tag = "proofs",
+    responses((status = 200, description = "Every stored proof, without its tx key", body = [ProofV2])))]
+pub(crate) async fn handle_get_proofs_v2(
+    storage: ProofStorage,
+    monero_service: Arc<MoneroService>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    println!("📋 Getting all proofs");
+
+    // Committed proofs are only shown to whoever holds their preimage
+    let proofs: Vec<TransactionProof> =
+        storage.read().await.values().filter(|proof| listed(proof)).map(privacy::public).collect();
+    Ok(warp::reply::json(&proofs_v2(&monero_service, proofs).await))
+}
+
+#[utoipa::path(get, path = "/v2/proof/{id}", tag = "proofs",
+    params(("id" = String, Path, description = "Proof id")),
+    responses(
+        (status = 200, description = "The proof", body = ProofV2),
+        (status = 403, description = "Stored as a commitment, open it with `POST /v2/proof/{id}/open`", body = ErrorResponse),
+        (status = 404, description = "Unknown proof id", body = ErrorResponse),
+        (status = 410, description = "The proof was deleted", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_get_proof_v2(
+    proof_id: String,
+    storage: ProofStorage,
+    monero_service: Arc<MoneroService>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    println!("🔍 Getting proof: {}", proof_id);
+
+    let proof = storage.read().await.get(&proof_id).cloned();
+    match proof {
+        Some(proof) if proof.status == ProofStatus::Deleted => Ok(deleted_proof()),
+        Some(proof) if proof.commitment.is_some() => Ok(preimage_required()),
+        Some(proof) => Ok(warp::reply::with_status(
+            warp::reply::json(&proof_v2(&monero_service, privacy::public(&proof)).await),
+            warp::http::StatusCode::OK,
+        )),
+        None => Ok(json_error("Proof not found", proof_id, warp::http::StatusCode::NOT_FOUND)),
+    }
+}
+
+#[utoipa::path(post, path = "/v2/proof/{id}/open", tag = "proofs", request_body = ProofPreimage,
+    params(("id" = String, Path, description = "Proof id")),
+    responses(
+        (status = 200, description = "The proof with its deposit data filled in from the preimage", body = ProofV2),
+        (status = 400, description = "Invalid request body; `fields` lists each problem", body = ErrorResponse),
+        (status = 403, description = "The preimage does not open the proof", body = ErrorResponse),
+        (status = 404, description = "Unknown proof id", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_open_proof_v2(
+    proof_id: String,
+    preimage: ProofPreimage,
+    storage: ProofStorage,
+    monero_service: Arc<MoneroService>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    println!("🔓 Opening proof: {}", proof_id);
+
+    let proof = storage.read().await.get(&proof_id).cloned();
+    let Some(proof) = proof else {
+        return Ok(json_error("Proof not found", proof_id, warp::http::StatusCode::NOT_FOUND));
+    };
+    match privacy::reveal(&proof, &preimage) {
+        Some(proof) => Ok(warp::reply::with_status(
+            warp::reply::json(&proof_v2(&monero_service, proof).await),
+            warp::http::StatusCode::OK,
+        )),
+        None => Ok(json_error(
+            "Preimage mismatch",
+            "The preimage does not open this proof",
+            warp::http::StatusCode::FORBIDDEN,
+        )),
+    }
+}
+
+#[utoipa::path(post, path = "/v2/proof/{id}/attest", tag = "proofs", request_body = AttestRequest,
+    params(("id" = String, Path, description = "Proof id")),
+    responses(
+        (status = 200, description = "The attested proof; `attestation` holds the signed LockProof", body = ProofV2),
+        (status = 400, description = "Invalid destination or transaction id", body = ErrorResponse),
+        (status = 403, description = "`preimage` is missing or does not match the proof's deposit", body = ErrorResponse),
+        (status = 404, description = "Unknown proof id", body = ErrorResponse),
+        (status = 409, description = "Proof not validated or already minted, its transaction attested through another proof, its attestation is still live, or a re-issue changes the destination", body = ErrorResponse),
+        (status = 422, description = "Not paid to the bridge's deposit address, no received amount, rejected by the deposit policy, or worth no wFOID", body = ErrorResponse),
+        (status = 500, description = "Signing failed, or the audit log is not writable", body = ErrorResponse),
+        (status = 503, description = "No attestor key or deposit address configured, or too few peers co-signed", body = ErrorResponse),
+    ))]
+#[allow(clippy::too_many_arguments)] // one per warp filter
+pub(crate) async fn handle_attest_proof_v2(
+    proof_id: String,
+    request: AttestRequest,
+    storage: ProofStorage,
+    monero_service: Arc<MoneroService>,
+    attestor: Option<Arc<Attestor>>,
+    policy: PolicyEngine,
+    events: EventBus,
+    audit: Arc<AuditLog>,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    println!("✍️  Attesting proof: {}", proof_id);
+
+    if let Err(e) = proofs::attest(attestor.as_deref(), &policy.current(), &storage, &events, &audit, &proof_id, &request).await {
+        return Ok(attest_failure(&e));
+    }
+    let proof = storage.read().await.get(&proof_id).cloned();
+    // `attest` checked that the preimage opens the proof
+    let proof = proof.map(|proof| match &request.preimage {
+        Some(preimage) => privacy::reveal(&proof, preimage).unwrap_or(proof),
+        None => proof,
+    });
+    match proof {
+        Some(proof) => Ok(warp::reply::with_status(
+            warp::reply::json(&proof_v2(&monero_service, proof).await),
+            warp::http::StatusCode::OK,
+        )),
+        None => Ok(json_error("Proof not found", proof_id, warp::http::StatusCode::NOT_FOUND)),
+    }
+}
+
+#[utoipa::path(get, path = "/v2/attestations", tag = "proofs",
+    responses((status = 200, description = "Issued attestations that can still be minted, soonest expiry first", body = [OutstandingAttestation])))]
+pub(crate) async fn handle_get_attestations_v2(storage: ProofStorage) -> Result<impl warp::Reply, warp::Rejection> {
+    // Those of committed proofs would name their Monero tx and destination
+    let proofs = storage.read().await;
+    let outstanding = proofs::outstanding(proofs.values().filter(|proof| proof.commitment.is_none()), events::unix_now());
+    Ok(warp::reply::json(&outstanding))
+}
+
+#[utoipa::path(post, path = "/v1/webhooks", tag = "webhooks", request_body = RegisterWebhookRequest,
+    security(("admin_token" = [])),
+    responses(
+        (status = 201, description = "Registered; the only response that includes the secret", body = WebhookEndpoint),
+        (status = 400, description = "Invalid webhook", body = ErrorResponse),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_register_webhook(
+    authorized: bool,
+    request: RegisterWebhookRequest,
+    webhooks: Webhooks,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    if !authorized {
+        return Ok(unauthorized());
+    }
+    match webhooks.register(request).await {
+        // The secret is only ever returned here, at registration time
+        Ok(endpoint) => Ok(warp::reply::with_status(
+            warp::reply::json(&endpoint),
+            warp::http::StatusCode::CREATED,
+        )),
+        Err(e) => Ok(json_error("Invalid webhook", e, warp::http::StatusCode::BAD_REQUEST)),
+    }
+}
+
+#[utoipa::path(get, path = "/v1/webhooks", tag = "webhooks", security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Registered webhooks, secrets redacted", body = [WebhookEndpoint]),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_list_webhooks(
+    authorized: bool,
+    webhooks: Webhooks,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    if !authorized {
+        return Ok(unauthorized());
+    }
+    Ok(warp::reply::with_status(
+        warp::reply::json(&webhooks.list().await),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[utoipa::path(delete, path = "/v1/webhooks/{id}", tag = "webhooks", security(("admin_token" = [])),
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "Removed", body = DeletedResponse),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+        (status = 404, description = "Unknown webhook id", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_delete_webhook(
+    webhook_id: String,
+    authorized: bool,
+    webhooks: Webhooks,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    if !authorized {
+        return Ok(unauthorized());
+    }
+    if webhooks.remove(&webhook_id).await {
+        Ok(warp::reply::with_status(
+            warp::reply::json(&DeletedResponse { deleted: webhook_id }),
+            warp::http::StatusCode::OK,
+        ))
+    } else {
+        Ok(json_error("Webhook not found", webhook_id, warp::http::StatusCode::NOT_FOUND))
+    }
+}
+
+#[utoipa::path(get, path = "/v1/webhooks/dead-letters", tag = "webhooks", security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Deliveries that exhausted their retries", body = [DeadLetter]),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_list_dead_letters(
+    authorized: bool,
+    webhooks: Webhooks,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    if !authorized {
+        return Ok(unauthorized());
+    }
+    Ok(warp::reply::with_status(
+        warp::reply::json(&webhooks.dead_letters().await),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+#[utoipa::path(post, path = "/v1/webhooks/dead-letters/{id}/replay", tag = "webhooks", security(("admin_token" = [])),
+    params(("id" = String, Path, description = "Dead letter id")),
+    responses(
+        (status = 200, description = "Delivered this time", body = ReplayResponse),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+        (status = 502, description = "Delivery failed again", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_replay_dead_letter(
+    dead_letter_id: String,
+    authorized: bool,
+    webhooks: Webhooks,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    if !authorized {
+        return Ok(unauthorized());
+    }
+    println!("🔁 Replaying dead letter: {}", dead_letter_id);
+    match webhooks.replay(&dead_letter_id).await {
+        Ok(dead_letter) => Ok(warp::reply::with_status(
+            warp::reply::json(&ReplayResponse {
+                replayed: dead_letter.id,
+                event_id: dead_letter.event.id,
+            }),
+            warp::http::StatusCode::OK,
+        )),
+        Err(e) => Ok(json_error("Replay failed", e, warp::http::StatusCode::BAD_GATEWAY)),
+    }
+}
+
+// `Last-Event-ID` (sent by EventSource on reconnect) wins over `?last_event_id=`.
+// Event streams end on shutdown so they don't hold up the drain.
+// Streams `records` encoded as `format`, as a download named `name`. A
+// record that fails to load aborts the response, truncating the body.
+fn export_response<R, S>(records: S, format: Format, name: &str) -> warp::reply::Response
+where
+    R: Record + Send + 'static,
+    S: futures_util::Stream<Item = Result<R>> + Send + 'static,
+{
+    let mut encoder = Encoder::new(format);
+    let begin = encoder.begin::<R>();
+    let chunks = records
+        .map(Some)
+        .chain(futures_util::stream::iter([None]))
+        .map(move |record| match record {
+            Some(record) => record.and_then(|record| encoder.record(&record)),
+            None => Ok(encoder.finish()),
+        });
+    let body = futures_util::stream::iter([begin]).chain(chunks).map(|chunk| chunk.map_err(std::io::Error::other));
+    let mut response = warp::reply::Response::new(warp::hyper::Body::wrap_stream(body));
+    let headers = response.headers_mut();
+    headers.insert("content-type", HeaderValue::from_static(format.content_type()));
+    if let Ok(disposition) = HeaderValue::from_str(&format!("attachment; filename=\"{}.{}\"", name, format.extension())) {
+        headers.insert("content-disposition", disposition);
+    }
+    response
+}
+
+// Every stored proof as stored, so committed proofs stay committed
+#[utoipa::path(get, path = "/v1/export/proofs", tag = "export", security(("admin_token" = [])), params(ExportQuery),
+    responses(
+        (status = 200, description = "One proof per line or row, sorted by id", body = [TransactionProof]),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_export_proofs(
+    query: ExportQuery,
+    authorized: bool,
+    storage: ProofStorage,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    use warp::Reply;
+    if !authorized {
+        return Ok(unauthorized().into_response());
+    }
+    let mut proofs: Vec<TransactionProof> = storage.read().await.values().cloned().collect();
+    proofs.sort_by(|a, b| a.proof_id.cmp(&b.proof_id));
+    let records = futures_util::stream::iter(proofs.into_iter().map(Ok));
+    Ok(export_response(records, query.format.unwrap_or_default(), "proofs"))
+}
+
+#[utoipa::path(get, path = "/v1/export/audit", tag = "export", security(("admin_token" = [])), params(ExportQuery),
+    responses(
+        (status = 200, description = "The audit log, oldest entry first"),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+        (status = 404, description = "No audit log is configured", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_export_audit(
+    query: ExportQuery,
+    authorized: bool,
+    audit: Arc<AuditLog>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    use tokio::io::AsyncBufReadExt;
+    use warp::Reply;
+    if !authorized {
+        return Ok(unauthorized().into_response());
+    }
+    let Some(path) = audit.path() else {
+        return Ok(audit_disabled().into_response());
+    };
+    // A log nothing was recorded to yet is empty
+    let lines = match tokio::fs::File::open(path).await {
+        Ok(file) => Some(tokio::io::BufReader::new(file).lines()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Ok(json_error("Failed to read audit log", e, warp::http::StatusCode::INTERNAL_SERVER_ERROR).into_response()),
+    };
+    // Read line by line; stops after the first error
+    let entries = futures_util::stream::unfold(lines, |lines| async move {
+        let mut lines = lines?;
+        match lines.next_line().await {
+            Ok(Some(line)) => {
+                let entry = serde_json::from_str::<AuditEntry>(&line).context("not an audit entry");
+                Some((entry, Some(lines)))
+            }
+            Ok(None) => None,
+            Err(e) => Some((Err(e.into()), None)),
+        }
+    });
+    Ok(export_response(entries, query.format.unwrap_or_default(), "audit"))
+}
+
+#[utoipa::path(post, path = "/v1/import/proofs", tag = "export", security(("admin_token" = [])), params(ImportQuery),
+    request_body(content = [TransactionProof], description = "As written by `/export/proofs` in the same format"),
+    responses(
+        (status = 200, description = "Existing proof ids are skipped unless `overwrite`", body = ImportResponse),
+        (status = 400, description = "The body is not an export in this format", body = ErrorResponse),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_import_proofs(
+    query: ImportQuery,
+    authorized: bool,
+    body: Bytes,
+    storage: ProofStorage,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    if !authorized {
+        return Ok(unauthorized());
+    }
+    let proofs = match export::decode(body.as_ref(), query.format.unwrap_or_default()) {
+        Ok(proofs) => proofs,
+        Err(e) => return Ok(json_error("Invalid import", format!("{:#}", e), warp::http::StatusCode::BAD_REQUEST)),
+    };
+    let private = storage.is_private();
+    match export::import_proofs(&mut *storage.write().await, proofs, query.overwrite, private) {
+        Ok(response) => {
+            println!("📥 Imported {} proofs, skipped {} existing", response.imported, response.skipped);
+            Ok(warp::reply::with_status(warp::reply::json(&response), warp::http::StatusCode::OK))
+        }
+        Err(e) => Ok(json_error("Invalid import", format!("{:#}", e), warp::http::StatusCode::BAD_REQUEST)),
+    }
+}
+
+#[utoipa::path(post, path = "/v1/import/audit", tag = "export", security(("admin_token" = [])), params(ImportQuery),
+    request_body(description = "Entries as written by `/export/audit` in the same format"),
+    responses(
+        (status = 200, description = "Entries already in the log are skipped", body = ImportResponse),
+        (status = 400, description = "The body is not an export in this format", body = ErrorResponse),
+        (status = 401, description = "Missing or wrong admin token", body = ErrorResponse),
+        (status = 404, description = "No audit log is configured", body = ErrorResponse),
+        (status = 409, description = "The entries diverge from the log or leave a gap; nothing was written", body = ErrorResponse),
+    ))]
+pub(crate) async fn handle_import_audit(
+    query: ImportQuery,
+    authorized: bool,
+    body: Bytes,
+    audit: Arc<AuditLog>,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    if !authorized {
+        return Ok(unauthorized());
+    }
+    if audit.path().is_none() {
+        return Ok(audit_disabled());
+    }
+    let entries: Vec<AuditEntry> = match export::decode(body.as_ref(), query.format.unwrap_or_default()) {
+        Ok(entries) => entries,
+        Err(e) => return Ok(json_error("Invalid import", format!("{:#}", e), warp::http::StatusCode::BAD_REQUEST)),
+    };
+    match audit.import(entries).await {
+        Ok(response) => {
+            println!("📥 Imported {} audit entries, skipped {} existing", response.imported, response.skipped);
+            Ok(warp::reply::with_status(warp::reply::json(&response), warp::http::StatusCode::OK))
+        }
+        Err(e) => Ok(json_error("Audit chain mismatch", format!("{:#}", e), warp::http::StatusCode::CONFLICT)),
+    }
+}
+
+fn audit_disabled() -> warp::reply::WithStatus<warp::reply::Json> {
+    json_error(
+        "Audit log disabled",
+        "No audit log is configured on this server",
+        warp::http::StatusCode::NOT_FOUND,
+    )
+}
+
+#[utoipa::path(get, path = "/v1/events", tag = "events",
+    params(EventFilter, ("Last-Event-ID" = Option<u64>, Header, description = "Resume after this `seq`; wins over `last_event_id`")),
+    responses((status = 200, description = "Server-Sent Events; `event` is the event type, `id` its `seq`", body = ProofEvent, content_type = "text/event-stream")))]
+pub(crate) fn handle_events_sse(
+    mut filter: EventFilter,
+    last_event_id: Option<u64>,
+    events: EventBus,
+    shutdown: Shutdown,
+) -> impl warp::Reply {
+    filter.last_event_id = last_event_id.or(filter.last_event_id);
+    println!("📡 SSE subscriber connected: {:?}", filter);
+
+    let (truncated, stream) = events.stream(filter);
+    // Tell the client it missed events and should refetch `GET /proofs`
+    let resync = truncated.then(|| Ok(warp::sse::Event::default().event("resync").data("history truncated")));
+    let stream = futures_util::stream::iter(resync).chain(stream.map(|event| {
+        warp::sse::Event::default()
+            .id(event.seq.to_string())
+            .event(event.kind.as_str())
+            .json_data(&event)
+    }));
+    let stream = stream.take_until(shutdown.wait());
+
+    warp::sse::reply(warp::sse::keep_alive().stream(stream))
+}
+
+#[utoipa::path(get, path = "/v1/events/ws", tag = "events", params(EventFilter),
+    responses((status = 101, description = "WebSocket upgrade; one JSON `ProofEvent` per text message", body = ProofEvent)))]
+pub(crate) fn handle_events_ws(ws: warp::ws::Ws, filter: EventFilter, events: EventBus, shutdown: Shutdown) -> impl warp::Reply {
+    ws.on_upgrade(move |socket| async move {
+        println!("📡 WebSocket subscriber connected: {:?}", filter);
+        let (mut sink, mut incoming) = socket.split();
+        let (truncated, stream) = events.stream(filter);
+        let closing = shutdown.wait();
+        tokio::pin!(stream, closing);
+
+        if truncated {
+            let notice = serde_json::json!({ "type": "resync", "message": "history truncated" });
+            if sink.send(warp::ws::Message::text(notice.to_string())).await.is_err() {
+                return;
+            }
+        }
+        loop {
+            tokio::select! {
+                event = stream.next() => {
+                    let Some(event) = event else { break };
+                    let Ok(text) = serde_json::to_string(&event) else { continue };
+                    if sink.send(warp::ws::Message::text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                _ = &mut closing => break,
+                // Clients only ever close the socket; anything else is ignored
+                message = incoming.next() => {
+                    match message {
+                        Some(Ok(message)) if !message.is_close() => {}
+                        _ => break,
+                    }
+                }
+            }
+        }
+        let _ = sink.close().await;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{ADDRESS, StubDaemon, server_state};
+
+    const DEST: &str = "0x0000000000000000000000000000000000000002";
+
+    // Creates a proof for `tx_id` and returns the deposit data only its creator gets
+    async fn create<F>(routes: &F, tx_id: &str) -> (String, ProofPreimage)
+    where
+        F: Filter + 'static,
+        F::Extract: warp::Reply + Send,
+    {
+        let request = ProofRequest { tx_id: tx_id.to_string(), recipient_address: ADDRESS.to_string() };
+        let created = warp::test::request().method("POST").path("/v1/proof").json(&request).reply(routes).await;
+        assert_eq!(created.status(), 200);
+        let created: ProofResponse = serde_json::from_slice(created.body()).unwrap();
+        let preimage = ProofPreimage { tx_id: request.tx_id, tx_key: created.tx_key, recipient_address: request.recipient_address };
+        (created.proof_id, preimage)
+    }
+
+    fn validation(preimage: &ProofPreimage) -> ValidationRequest {
+        ValidationRequest {
+            tx_id: preimage.tx_id.clone(),
+            tx_key: preimage.tx_key.clone(),
+            recipient_address: preimage.recipient_address.clone(),
+            additional_tx_keys: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn legacy_paths_are_deprecated_aliases_of_v1() {
+        let mut state = server_state(StubDaemon::default().spawn());
+        state.legacy_sunset = Some("Wed, 30 Jun 2027 00:00:00 GMT".to_string());
+        let routes = routes(state);
+        let request = ProofRequest {
+            tx_id: "ef".repeat(32),
+            recipient_address: "44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A"
+                .to_string(),
+        };
+
+        let legacy = warp::test::request().method("POST").path("/proof").json(&request).reply(&routes).await;
+        assert_eq!(legacy.status(), 200);
+        assert_eq!(legacy.headers()["deprecation"], "true");
+        assert_eq!(legacy.headers()["sunset"], "Wed, 30 Jun 2027 00:00:00 GMT");
+        assert_eq!(legacy.headers()["link"], "</v1/proof>; rel=\"successor-version\"");
+
+        // `/v1` answers with the frozen shape and no deprecation headers
+        let request = ProofRequest { tx_id: "ee".repeat(32), ..request };
+        let v1 = warp::test::request().method("POST").path("/v1/proof").json(&request).reply(&routes).await;
+        assert!(v1.headers().get("deprecation").is_none());
+        let body: serde_json::Value = serde_json::from_slice(v1.body()).unwrap();
+        let mut keys: Vec<&str> = body.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["message", "proof_id", "tx_key"]);
+
+        let created: ProofResponse = serde_json::from_slice(v1.body()).unwrap();
+        let validation = ValidationRequest {
+            tx_id: request.tx_id,
+            tx_key: created.tx_key,
+            recipient_address: request.recipient_address,
+            additional_tx_keys: Vec::new(),
+        };
+        let v1 = warp::test::request().method("POST").path("/v1/validate").json(&validation).reply(&routes).await;
+        let body: serde_json::Value = serde_json::from_slice(v1.body()).unwrap();
+        let mut keys: Vec<&str> = body.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["amount", "message", "valid"]);
+        let v2 = warp::test::request().method("POST").path("/v2/validate").json(&validation).reply(&routes).await;
+        let body: serde_json::Value = serde_json::from_slice(v2.body()).unwrap();
+        assert_eq!(body["network"], "mainnet");
+    }
+
+    #[tokio::test]
+    async fn v1_proofs_keep_their_frozen_shape() {
+        let routes = routes(server_state(StubDaemon::default().spawn()));
+        let (proof_id, preimage) = create(&routes, &"ed".repeat(32)).await;
+        // Validating adds status, block and timestamps, none of which /v1 shows
+        let validated = warp::test::request().method("POST").path("/v1/validate").json(&validation(&preimage)).reply(&routes).await;
+        assert_eq!(validated.status(), 200);
+
+        let expected = serde_json::json!({
+            "tx_id": "ed".repeat(32),
+            "tx_key": "",
+            "recipient_address": ADDRESS,
+            "amount": 1_000_000,
+            "proof_id": proof_id,
+        });
+        let proof = warp::test::request().path(&format!("/v1/proof/{proof_id}")).reply(&routes).await;
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(proof.body()).unwrap(), expected);
+        let proofs = warp::test::request().path("/v1/proofs").reply(&routes).await;
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(proofs.body()).unwrap(), serde_json::json!([expected]));
+    }
+
+    #[test]
+    fn admin_tokens_must_match_exactly() {
+        let token = AdminToken::new(Some("s3cret".to_string()));
+        assert!(token.authorizes(Some("Bearer s3cret")));
+        for header in [None, Some("s3cret"), Some("Bearer s3cre"), Some("Bearer s3cret2"), Some("Bearer S3cret")] {
+            assert!(!token.authorizes(header), "{:?}", header);
+        }
+        assert!(!AdminToken::new(None).authorizes(Some("Bearer ")));
+    }
+
+    #[tokio::test]
+    async fn only_the_creator_can_delete_a_proof() {
+        let routes = routes(server_state(StubDaemon::default().spawn()));
+        let request = ProofRequest { tx_id: "ef".repeat(32), recipient_address: crate::testing::ADDRESS.to_string() };
+        let created = warp::test::request().method("POST").path("/v1/proof").json(&request).reply(&routes).await;
+        let created: ProofResponse = serde_json::from_slice(created.body()).unwrap();
+        assert!(!created.tx_key.is_empty());
+
+        // Listings leave the tx key out, so what they show proves nothing
+        let path = format!("/v1/proof/{}", created.proof_id);
+        for path in ["/v1/proofs".to_string(), "/v2/proofs".to_string(), path.clone(), format!("/v2/proof/{}", created.proof_id)] {
+            let listed = warp::test::request().path(&path).reply(&routes).await;
+            assert_eq!(listed.status(), 200, "{path}");
+            assert!(!String::from_utf8_lossy(listed.body()).contains(&created.tx_key), "{path}");
+        }
+        let listed = warp::test::request().path("/v1/proofs").reply(&routes).await;
+        let listed: Vec<ProofV1> = serde_json::from_slice(listed.body()).unwrap();
+        let guess = ProofPreimage { tx_id: listed[0].tx_id.clone(), tx_key: "ab".repeat(32), recipient_address: listed[0].recipient_address.clone() };
+        let third_party = warp::test::request().method("DELETE").path(&path).json(&guess).reply(&routes).await;
+        assert_eq!(third_party.status(), 403);
+
+        let owner = ProofPreimage { tx_key: created.tx_key, ..guess };
+        let deleted = warp::test::request().method("DELETE").path(&path).json(&owner).reply(&routes).await;
+        assert_eq!(deleted.status(), 200);
+        assert_eq!(warp::test::request().path(&path).reply(&routes).await.status(), 410);
+    }
+
+    #[tokio::test]
+    async fn v2_returns_full_proofs_and_opens_committed_ones() {
+        let mut state = server_state(StubDaemon::default().spawn());
+        state.storage = Arc::new(ProofStore::in_memory().with_privacy(true).unwrap());
+        let routes = routes(state);
+        let request = ProofRequest { tx_id: "ab".repeat(32), recipient_address: ADDRESS.to_string() };
+
+        let created = warp::test::request().method("POST").path("/v2/proof").json(&request).reply(&routes).await;
+        assert_eq!(created.status(), 201);
+        let created: ProofV2 = serde_json::from_slice(created.body()).unwrap();
+        assert_eq!((created.status, created.confirmations), (ProofStatus::Created, Some(10)));
+        let preimage = ProofPreimage {
+            tx_id: request.tx_id.clone(),
+            tx_key: created.tx_key.clone(),
+            recipient_address: request.recipient_address.clone(),
+        };
+
+        let validated = warp::test::request().method("POST").path("/v2/validate").json(&validation(&preimage)).reply(&routes).await;
+        let validated: ValidationResponseV2 = serde_json::from_slice(validated.body()).unwrap();
+        assert!(validated.valid);
+        assert_eq!(validated.amount_xmr.as_deref(), Some("0.000001000000"));
+        assert_eq!(validated.proof.unwrap().status, ProofStatus::Validated);
+
+        // Stored as a commitment: hidden from listings, opened by the preimage only
+        let path = format!("/v2/proof/{}", created.proof_id);
+        let listed: Vec<ProofV2> =
+            serde_json::from_slice(warp::test::request().path("/v2/proofs").reply(&routes).await.body()).unwrap();
+        assert!(listed.is_empty());
+        assert_eq!(warp::test::request().path(&path).reply(&routes).await.status(), 403);
+        assert_eq!(warp::test::request().path("/v2/proof/missing").reply(&routes).await.status(), 404);
+        let open = format!("{}/open", path);
+        let wrong = ProofPreimage { tx_key: "cd".repeat(32), ..preimage.clone() };
+        let refused = warp::test::request().method("POST").path(&open).json(&wrong).reply(&routes).await;
+        assert_eq!(refused.status(), 403);
+        let opened = warp::test::request().method("POST").path(&open).json(&preimage).reply(&routes).await;
+        assert_eq!(opened.status(), 200);
+        let opened: ProofV2 = serde_json::from_slice(opened.body()).unwrap();
+        assert_eq!((opened.tx_id, opened.tx_key), (request.tx_id, created.tx_key));
+    }
+
+    #[tokio::test]
+    async fn only_the_depositor_attests_and_live_attestations_are_listed_and_kept() {
+        let mut state = server_state(StubDaemon::default().spawn());
+        let attestor = Attestor::new(&"4c".repeat(32), "0x0000000000000000000000000000000000000001", 20994)
+            .unwrap()
+            .with_deposit_address(Some(ADDRESS.to_string()));
+        state.attestor = Some(Arc::new(attestor));
+        let routes = routes(state);
+        let (proof_id, preimage) = create(&routes, &"ab".repeat(32)).await;
+        let attest = format!("/v2/proof/{}/attest", proof_id);
+
+        // Not validated yet
+        let request = AttestRequest { dest: DEST.to_string(), preimage: Some(preimage.clone()) };
+        let early = warp::test::request().method("POST").path(&attest).json(&request).reply(&routes).await;
+        assert_eq!(early.status(), 409);
+        let validated = warp::test::request().method("POST").path("/v1/validate").json(&validation(&preimage)).reply(&routes).await;
+        assert_eq!(validated.status(), 200);
+
+        // A third party only knows what the listings show
+        let listed: Vec<ProofV1> =
+            serde_json::from_slice(warp::test::request().path("/v1/proofs").reply(&routes).await.body()).unwrap();
+        let guess = ProofPreimage { tx_id: listed[0].tx_id.clone(), tx_key: "cd".repeat(32), recipient_address: listed[0].recipient_address.clone() };
+        for preimage in [None, Some(guess)] {
+            let request = AttestRequest { dest: "0x0000000000000000000000000000000000000003".to_string(), preimage };
+            let refused = warp::test::request().method("POST").path(&attest).json(&request).reply(&routes).await;
+            assert_eq!(refused.status(), 403);
+        }
+        let invalid = AttestRequest { dest: "0x02".to_string(), preimage: Some(preimage.clone()) };
+        let refused = warp::test::request().method("POST").path(&attest).json(&invalid).reply(&routes).await;
+        assert_eq!(refused.status(), 400);
+        let missing = warp::test::request().method("POST").path("/v2/proof/missing/attest").json(&request).reply(&routes).await;
+        assert_eq!(missing.status(), 404);
+
+        let attested = warp::test::request().method("POST").path(&attest).json(&request).reply(&routes).await;
+        assert_eq!(attested.status(), 200);
+        let attested: ProofV2 = serde_json::from_slice(attested.body()).unwrap();
+        let attestation = attested.attestation.unwrap();
+        assert_eq!(attestation.lock_proof.lock_id, attestation::lock_id(&attestation.lock_proof.monero_tx));
+        let again = warp::test::request().method("POST").path(&attest).json(&request).reply(&routes).await;
+        assert_eq!(again.status(), 409);
+
+        let outstanding: Vec<OutstandingAttestation> =
+            serde_json::from_slice(warp::test::request().path("/v2/attestations").reply(&routes).await.body()).unwrap();
+        assert_eq!(outstanding.len(), 1);
+        assert_eq!(outstanding[0].proof_id, proof_id);
+        assert_eq!(outstanding[0].lock_proof, attestation.lock_proof);
+
+        // Kept while it can be minted, even for its owner
+        let path = format!("/v2/proof/{}", proof_id);
+        let kept = warp::test::request().method("DELETE").path(&path).json(&preimage).reply(&routes).await;
+        assert_eq!(kept.status(), 409);
+    }
+
+    #[tokio::test]
+    async fn deletion_answers_each_outcome() {
+        let routes = routes(server_state(StubDaemon::default().spawn()));
+        let (proof_id, preimage) = create(&routes, &"ab".repeat(32)).await;
+        let path = format!("/v2/proof/{}", proof_id);
+
+        let missing = warp::test::request().method("DELETE").path("/v2/proof/missing").json(&preimage).reply(&routes).await;
+        assert_eq!(missing.status(), 404);
+        let invalid = ProofPreimage { tx_key: "zz".to_string(), ..preimage.clone() };
+        let invalid = warp::test::request().method("DELETE").path(&path).json(&invalid).reply(&routes).await;
+        assert_eq!(invalid.status(), 400);
+        let deleted = warp::test::request().method("DELETE").path(&path).json(&preimage).reply(&routes).await;
+        assert_eq!(deleted.status(), 200);
+        let deleted: DeletedResponse = serde_json::from_slice(deleted.body()).unwrap();
+        assert_eq!(deleted.deleted, proof_id);
+        let again = warp::test::request().method("DELETE").path(&path).json(&preimage).reply(&routes).await;
+        assert_eq!(again.status(), 410);
+        assert_eq!(warp::test::request().path(&path).reply(&routes).await.status(), 410);
+        // A tombstone frees the transaction for a new proof
+        create(&routes, &preimage.tx_id).await;
+    }
+
+    #[tokio::test]
+    async fn admin_routes_refuse_missing_and_wrong_tokens() {
+        let admin = routes(server_state(StubDaemon::default().spawn()));
+        let mut closed = server_state(StubDaemon::default().spawn());
+        closed.admin_token = AdminToken::new(None);
+        let closed = routes(closed);
+        let requests = [
+            ("POST", "/v1/webhooks", Some(serde_json::json!({ "url": "http://127.0.0.1:1/hook" }).to_string())),
+            ("GET", "/v1/webhooks", None),
+            ("DELETE", "/v1/webhooks/id", None),
+            ("GET", "/v2/webhooks/dead-letters", None),
+            ("POST", "/v1/webhooks/dead-letters/id/replay", None),
+            ("GET", "/v1/export/proofs", None),
+            ("GET", "/v2/export/audit?format=csv", None),
+            ("POST", "/v1/import/proofs", Some(String::new())),
+            ("POST", "/v1/import/audit", Some(String::new())),
+            ("GET", "/webhooks", None),
+        ];
+        for (method, path, body) in requests {
+            for authorization in [None, Some("Bearer wrong"), Some("admin"), Some("Bearer admin ")] {
+                let mut request = warp::test::request().method(method).path(path);
+                if let Some(authorization) = authorization {
+                    request = request.header("authorization", authorization);
+                }
+                if let Some(body) = &body {
+                    request = request.body(body.clone());
+                }
+                let response = request.reply(&admin).await;
+                assert_eq!(response.status(), 401, "{method} {path} {authorization:?}");
+                let error: ErrorResponse = serde_json::from_slice(response.body()).unwrap();
+                assert_eq!(error.error, "Unauthorized");
+            }
+            // Without a configured token nothing authorizes
+            let mut request = warp::test::request().method(method).path(path).header("authorization", "Bearer ");
+            if let Some(body) = &body {
+                request = request.body(body.clone());
+            }
+            assert_eq!(request.reply(&closed).await.status(), 401, "{method} {path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn webhooks_are_managed_with_the_admin_token() {
+        let routes = routes(server_state(StubDaemon::default().spawn()));
+        let admin = |method: &str, path: &str| {
+            warp::test::request().method(method).path(path).header("authorization", "Bearer admin")
+        };
+        let register = RegisterWebhookRequest { url: "http://127.0.0.1:1/hook".to_string(), secret: Some("shh".to_string()), events: Vec::new() };
+
+        let registered = admin("POST", "/v1/webhooks").json(&register).reply(&routes).await;
+        assert_eq!(registered.status(), 201);
+        let registered: WebhookEndpoint = serde_json::from_slice(registered.body()).unwrap();
+        assert_eq!(registered.secret, "shh");
+        let invalid = RegisterWebhookRequest { url: "ftp://127.0.0.1/hook".to_string(), ..register };
+        assert_eq!(admin("POST", "/v1/webhooks").json(&invalid).reply(&routes).await.status(), 400);
+
+        let listed: Vec<WebhookEndpoint> = serde_json::from_slice(admin("GET", "/v2/webhooks").reply(&routes).await.body()).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].secret, "********");
+        let dead: Vec<DeadLetter> =
+            serde_json::from_slice(admin("GET", "/v1/webhooks/dead-letters").reply(&routes).await.body()).unwrap();
+        assert!(dead.is_empty());
+        assert_eq!(admin("POST", "/v1/webhooks/dead-letters/missing/replay").reply(&routes).await.status(), 502);
+
+        let path = format!("/v1/webhooks/{}", registered.id);
+        assert_eq!(admin("DELETE", &path).reply(&routes).await.status(), 200);
+        assert_eq!(admin("DELETE", &path).reply(&routes).await.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn bodies_are_limited_to_16_kib_except_imports() {
+        let routes = routes(server_state(StubDaemon::default().spawn()));
+        let oversized = vec![b' '; MAX_BODY_BYTES as usize + 1];
+        let limited = [
+            ("POST", "/v1/proof"),
+            ("POST", "/v2/validate"),
+            ("POST", "/v1/proof/id/attest"),
+            ("DELETE", "/v2/proof/id"),
+            ("POST", "/v2/proof/id/open"),
+            ("POST", "/v1/webhooks"),
+            ("POST", "/v1/gossip/attestations"),
+        ];
+        for (method, path) in limited {
+            let response = warp::test::request()
+                .method(method)
+                .path(path)
+                .header("authorization", "Bearer admin")
+                .body(oversized.clone())
+                .reply(&routes)
+                .await;
+            assert_eq!(response.status(), 413, "{method} {path}");
+            let error: ErrorResponse = serde_json::from_slice(response.body()).unwrap();
+            assert_eq!(error.error, "Payload too large");
+        }
+
+        // Imports take up to 64 MiB; this one is only blank lines
+        let response = warp::test::request()
+            .method("POST")
+            .path("/v1/import/proofs")
+            .header("authorization", "Bearer admin")
+            .body(oversized)
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn proofs_are_exported_and_imported_between_servers() {
+        let source = routes(server_state(StubDaemon::default().spawn()));
+        let (proof_id, preimage) = create(&source, &"ab".repeat(32)).await;
+        let admin = |method: &str, path: &str| {
+            warp::test::request().method(method).path(path).header("authorization", "Bearer admin")
+        };
+
+        for (format, content_type) in [("jsonl", "application/x-ndjson"), ("csv", "text/csv"), ("json", "application/json")] {
+            let exported = admin("GET", &format!("/v1/export/proofs?format={format}")).reply(&source).await;
+            assert_eq!(exported.status(), 200);
+            assert_eq!(exported.headers()["content-type"], content_type);
+            assert_eq!(exported.headers()["content-disposition"], format!("attachment; filename=\"proofs.{format}\""));
+            // Exports are complete, tx key included, unlike the listings
+            assert!(String::from_utf8_lossy(exported.body()).contains(&preimage.tx_key), "{format}");
+
+            let target = routes(server_state(StubDaemon::default().spawn()));
+            let path = format!("/v1/import/proofs?format={format}");
+            let imported = admin("POST", &path).body(exported.body().clone()).reply(&target).await;
+            assert_eq!(imported.status(), 200, "{format}");
+            let imported: ImportResponse = serde_json::from_slice(imported.body()).unwrap();
+            assert_eq!((imported.imported, imported.skipped), (1, 0));
+            let again: ImportResponse =
+                serde_json::from_slice(admin("POST", &path).body(exported.body().clone()).reply(&target).await.body()).unwrap();
+            assert_eq!((again.imported, again.skipped), (0, 1));
+            let copy = warp::test::request().path(&format!("/v1/proof/{proof_id}")).reply(&target).await;
+            assert_eq!(copy.status(), 200);
+        }
+
+        let invalid = admin("POST", "/v1/import/proofs").body("not json").reply(&source).await;
+        assert_eq!(invalid.status(), 400);
+        // The test state has no audit log
+        assert_eq!(admin("GET", "/v1/export/audit").reply(&source).await.status(), 404);
+        assert_eq!(admin("POST", "/v1/import/audit").body("").reply(&source).await.status(), 404);
+    }
+}
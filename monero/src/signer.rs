@@ -0,0 +1,253 @@
+// Where the attestor key lives: an environment variable, a key file, an
+// encrypted keystore or a remote signing service. `[attestor]` selects the
+// backend; `Attestor` only sees a `Signer`.
+use anyhow::{Context, Result, anyhow, bail};
+use futures_util::future::BoxFuture;
+use k256::ecdsa::SigningKey;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::attestation;
+use crate::config::KeyConfig;
+use crate::keystore::Keystore;
+
+pub trait Signer: Send + Sync {
+    fn address(&self) -> [u8; 20];
+
+    // 65-byte r || s || v over a 32-byte digest, v in {27, 28}
+    fn sign_digest(&self, digest: [u8; 32]) -> BoxFuture<'_, Result<[u8; 65]>>;
+}
+
+// Loads the signer `config` describes; `None` for an unset environment key
+pub fn load(config: &KeyConfig) -> Result<Option<Arc<dyn Signer>>> {
+    let signer: Arc<dyn Signer> = match config {
+        KeyConfig::Env { var } => match std::env::var(var) {
+            Ok(private_key) => Arc::new(LocalSigner::from_hex(&private_key).with_context(|| format!("invalid {}", var))?),
+            Err(_) => return Ok(None),
+        },
+        KeyConfig::File { path } => Arc::new(LocalSigner::from_file(path)?),
+        KeyConfig::Keystore { path, password_file } => {
+            let password = match password_file {
+                Some(file) => read_secret_file(file)?.trim_end_matches(['\r', '\n']).to_string(),
+                None => std::env::var("ATTESTOR_KEYSTORE_PASSWORD")
+                    .context("set ATTESTOR_KEYSTORE_PASSWORD or attestor password_file to unlock the keystore")?,
+            };
+            Arc::new(LocalSigner::from_keystore(path, &password)?)
+        }
+        KeyConfig::Remote { url, address, timeout_secs } => Arc::new(RemoteSigner::new(
+            url,
+            attestation::parse_address(address)?,
+            std::env::var("ATTESTOR_SIGNER_TOKEN").ok(),
+            Duration::from_secs(*timeout_secs),
+        )),
+    };
+    Ok(Some(signer))
+}
+
+// A key held in this process
+pub struct LocalSigner(SigningKey);
+
+impl LocalSigner {
+    pub fn new(signing_key: SigningKey) -> Self {
+        Self(signing_key)
+    }
+
+    pub fn from_hex(private_key: &str) -> Result<Self> {
+        let key_bytes = hex::decode(private_key.trim().trim_start_matches("0x")).context("attestor key is not hex")?;
+        Ok(Self(SigningKey::from_slice(&key_bytes).context("invalid attestor key")?))
+    }
+
+    // A hex key in a file only its owner may read
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Self::from_hex(&read_secret_file(path)?).with_context(|| format!("invalid key in {}", path.display()))
+    }
+
+    pub fn from_keystore(path: &Path, password: &str) -> Result<Self> {
+        let json = std::fs::read(path).with_context(|| format!("could not read keystore {}", path.display()))?;
+        let keystore: Keystore =
+            serde_json::from_slice(&json).with_context(|| format!("{} is not a JSON keystore", path.display()))?;
+        let signing_key = keystore.decrypt(password).with_context(|| format!("could not unlock {}", path.display()))?;
+        Ok(Self(signing_key))
+    }
+}
+
+impl Signer for LocalSigner {
+    fn address(&self) -> [u8; 20] {
+        attestation::key_address(&self.0)
+    }
+
+    fn sign_digest(&self, digest: [u8; 32]) -> BoxFuture<'_, Result<[u8; 65]>> {
+        let signed = self
+            .0
+            .sign_prehash_recoverable(&digest)
+            .map(|(signature, recovery_id)| {
+                let mut bytes = [0u8; 65];
+                bytes[..64].copy_from_slice(&signature.to_bytes());
+                bytes[64] = 27 + recovery_id.to_byte();
+                bytes
+            })
+            .map_err(|e| anyhow!("signing failed: {}", e));
+        Box::pin(std::future::ready(signed))
+    }
+}
+
+// `POST <url>` with a `SignRequest`, answered by a `SignResponse`. Sent with
+// `Authorization: Bearer $ATTESTOR_SIGNER_TOKEN` when it is set.
+pub struct RemoteSigner {
+    url: String,
+    address: [u8; 20],
+    token: Option<String>,
+    timeout: Duration,
+    http: reqwest::Client,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignRequest {
+    pub address: String,
+    pub digest: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignResponse {
+    pub signature: String,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, address: [u8; 20], token: Option<String>, timeout: Duration) -> Self {
+        Self {
+            url: url.to_string(),
+            address,
+            token: token.filter(|t| !t.is_empty()),
+            timeout,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    async fn sign(&self, digest: [u8; 32]) -> Result<[u8; 65]> {
+        let request = SignRequest {
+            address: attestation::format_hex(&self.address),
+            digest: attestation::format_hex(&digest),
+        };
+        let mut call = self.http.post(&self.url).timeout(self.timeout).json(&request);
+        if let Some(token) = &self.token {
+            call = call.bearer_auth(token);
+        }
+        let response = call.send().await.context("remote signer unreachable")?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("remote signer answered {}", status);
+        }
+        let response: SignResponse = response.json().await.context("invalid remote signer response")?;
+        let signature: [u8; 65] = hex::decode(response.signature.trim_start_matches("0x"))
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| anyhow!("remote signer returned a malformed signature"))?;
+        // A signer holding another key would produce attestations nobody accepts
+        let signer = attestation::recover_digest(&digest, &signature)?;
+        if signer != self.address {
+            bail!("remote signer signed with {} instead of {}", attestation::format_hex(&signer), request.address);
+        }
+        Ok(signature)
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn address(&self) -> [u8; 20] {
+        self.address
+    }
+
+    fn sign_digest(&self, digest: [u8; 32]) -> BoxFuture<'_, Result<[u8; 65]>> {
+        Box::pin(self.sign(digest))
+    }
+}
+
+// Refuses files that group or others can read
+fn read_secret_file(path: &Path) -> Result<String> {
+    let metadata = std::fs::metadata(path).with_context(|| format!("could not read {}", path.display()))?;
+    if !metadata.is_file() {
+        bail!("{} is not a file", path.display());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            bail!("{} is accessible by group or others (mode {:o}), chmod 600 it", path.display(), mode);
+        }
+    }
+    std::fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keystore::ScryptParams;
+    use warp::Filter;
+
+    const KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    #[tokio::test]
+    async fn file_and_keystore_backends_load_the_same_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let expected = LocalSigner::from_hex(KEY).unwrap().address();
+
+        let key_file = dir.path().join("attestor.key");
+        std::fs::write(&key_file, format!("{}\n", KEY)).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&key_file, std::fs::Permissions::from_mode(0o644)).unwrap();
+            let error = LocalSigner::from_file(&key_file).err().unwrap();
+            assert!(error.to_string().contains("chmod 600"), "{:#}", error);
+            std::fs::set_permissions(&key_file, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+        let signer = load(&KeyConfig::File { path: key_file }).unwrap().unwrap();
+        assert_eq!(signer.address(), expected);
+
+        let keystore_file = dir.path().join("attestor.json");
+        let signing_key = LocalSigner::from_hex(KEY).unwrap().0;
+        let keystore = Keystore::encrypt(&signing_key, "pw", ScryptParams { n: 1 << 10, r: 8, p: 1 }).unwrap();
+        std::fs::write(&keystore_file, serde_json::to_vec(&keystore).unwrap()).unwrap();
+        let password_file = dir.path().join("password");
+        std::fs::write(&password_file, "pw\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&password_file, std::fs::Permissions::from_mode(0o400)).unwrap();
+        }
+        let config = KeyConfig::Keystore { path: keystore_file, password_file: Some(password_file) };
+        let signer = load(&config).unwrap().unwrap();
+        assert_eq!(signer.address(), expected);
+        let signature = signer.sign_digest([7; 32]).await.unwrap();
+        assert_eq!(attestation::recover_digest(&[7; 32], &signature).unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn remote_signatures_must_come_from_the_configured_key() {
+        // A signing service holding KEY
+        let stub = warp::post()
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::body::json())
+            .then(|authorization: Option<String>, request: SignRequest| async move {
+                assert_eq!(authorization.as_deref(), Some("Bearer s3cret"));
+                let digest = attestation::parse_hex32(&request.digest).unwrap();
+                let signature = LocalSigner::from_hex(KEY).unwrap().sign_digest(digest).await.unwrap();
+                warp::reply::json(&SignResponse { signature: attestation::format_hex(&signature) })
+            });
+        let (addr, server) = warp::serve(stub).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        let url = format!("http://{}/sign", addr);
+        let token = Some("s3cret".to_string());
+
+        let address = LocalSigner::from_hex(KEY).unwrap().address();
+        let signer = RemoteSigner::new(&url, address, token.clone(), Duration::from_secs(5));
+        let signature = signer.sign_digest([9; 32]).await.unwrap();
+        assert_eq!(attestation::recover_digest(&[9; 32], &signature).unwrap(), address);
+
+        let impostor = RemoteSigner::new(&url, [0x11; 20], token, Duration::from_secs(5));
+        let error = impostor.sign_digest([9; 32]).await.unwrap_err();
+        assert!(error.to_string().contains("instead of"), "{:#}", error);
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::api::{ProofStatus, TransactionProof};
+use crate::privacy;
+
+const PROOFS_FILE: &str = "proofs.json";
+
+pub type ProofStorage = Arc<ProofStore>;
+
+// Proofs keyed by proof id. Without a data directory everything stays in
+// memory; with one, the map is snapshotted to `<data_dir>/proofs.json`.
+pub struct ProofStore {
+    proofs: RwLock<HashMap<String, TransactionProof>>,
+    data_dir: Option<PathBuf>,
+    // New proofs are stored as commitments, see `privacy`
+    private: bool,
+    // Bumped on every write access; the snapshot on disk is at `flushed`
+    generation: AtomicU64,
+    flushed: AtomicU64,
+    // Serializes snapshot writes
+    flush_lock: Mutex<()>,
+}
+
+impl ProofStore {
+    pub fn in_memory() -> Self {
+        Self {
+            proofs: RwLock::new(HashMap::new()),
+            data_dir: None,
+            private: false,
+            generation: AtomicU64::new(0),
+            flushed: AtomicU64::new(0),
+            flush_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn open(data_dir: Option<&Path>) -> Result<Self> {
+        let Some(data_dir) = data_dir else {
+            return Ok(Self::in_memory());
+        };
+        std::fs::create_dir_all(data_dir)
+            .with_context(|| format!("could not create data dir {}", data_dir.display()))?;
+        let path = data_dir.join(PROOFS_FILE);
+        let proofs = if path.exists() {
+            let bytes = std::fs::read(&path).with_context(|| format!("could not read {}", path.display()))?;
+            serde_json::from_slice(&bytes).with_context(|| format!("corrupt proof store {}", path.display()))?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            proofs: RwLock::new(proofs),
+            data_dir: Some(data_dir.to_path_buf()),
+            private: false,
+            generation: AtomicU64::new(0),
+            flushed: AtomicU64::new(0),
+            flush_lock: Mutex::new(()),
+        })
+    }
+
+    // In privacy mode, proofs stored in plaintext so far are concealed too
+    pub fn with_privacy(mut self, enabled: bool) -> Result<Self> {
+        self.private = enabled;
+        if enabled {
+            let proofs = self.proofs.get_mut();
+            let mut concealed = 0;
+            let plaintext = |proof: &&mut TransactionProof| proof.commitment.is_none() && proof.status != ProofStatus::Deleted;
+            for proof in proofs.values_mut().filter(plaintext) {
+                privacy::conceal(proof)?;
+                concealed += 1;
+            }
+            if concealed > 0 {
+                println!("🔒 Replaced the deposit data of {} stored proofs with commitments", concealed);
+                *self.generation.get_mut() += 1;
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.private
+    }
+
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.data_dir.as_deref()
+    }
+
+    pub async fn read(&self) -> RwLockReadGuard<'_, HashMap<String, TransactionProof>> {
+        self.proofs.read().await
+    }
+
+    // Any write access marks the store for the next flush
+    pub async fn write(&self) -> RwLockWriteGuard<'_, HashMap<String, TransactionProof>> {
+        let guard = self.proofs.write().await;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        guard
+    }
+
+    // Atomically replaces the snapshot (write to a temp file, then rename).
+    // Safe to cancel: an interrupted flush leaves the previous snapshot and is
+    // redone by the next call.
+    pub async fn flush(&self) -> Result<()> {
+        let Some(data_dir) = &self.data_dir else {
+            return Ok(());
+        };
+        let _flushing = self.flush_lock.lock().await;
+        let (generation, bytes) = {
+            let proofs = self.proofs.read().await;
+            let generation = self.generation.load(Ordering::SeqCst);
+            if generation == self.flushed.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+            (generation, serde_json::to_vec_pretty(&*proofs)?)
+        };
+        let path = data_dir.join(PROOFS_FILE);
+        let tmp = data_dir.join(format!("{}.tmp", PROOFS_FILE));
+        tokio::fs::write(&tmp, &bytes)
+            .await
+            .with_context(|| format!("could not write {}", tmp.display()))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .with_context(|| format!("could not replace {}", path.display()))?;
+        self.flushed.store(generation, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub async fn run_flusher(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.flush().await {
+                println!("❌ Failed to persist proofs: {:#}", e);
+            }
+        }
+    }
+
+    // Probes the data directory with a throwaway file
+    pub async fn check_writable(&self) -> Result<()> {
+        let Some(data_dir) = &self.data_dir else {
+            return Ok(());
+        };
+        let probe = data_dir.join(".write-probe");
+        tokio::fs::write(&probe, b"ok")
+            .await
+            .with_context(|| format!("{} is not writable", data_dir.display()))?;
+        tokio::fs::remove_file(&probe).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Piconero;
+    use crate::api::ProofStatus;
+
+    #[tokio::test]
+    async fn flushed_proofs_survive_a_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ProofStore::open(Some(dir.path())).unwrap();
+        store.check_writable().await.unwrap();
+        store.write().await.insert(
+            "p1".to_string(),
+            TransactionProof {
+                tx_id: "aa".repeat(32),
+                tx_key: "bb".repeat(32),
+                recipient_address: "addr".to_string(),
+                amount: Some(Piconero(5)),
+                proof_id: "p1".to_string(),
+                status: ProofStatus::Validated,
+                updated_at: 0,
+                attestation: None,
+                policy: None,
+                mint: None,
+                flags: Vec::new(),
+                commitment: None,
+                block: None,
+            },
+        );
+        store.flush().await.unwrap();
+
+        let reopened = ProofStore::open(Some(dir.path())).unwrap();
+        let proofs = reopened.read().await;
+        assert_eq!(proofs["p1"].status, ProofStatus::Validated);
+        assert_eq!(proofs["p1"].amount, Some(Piconero(5)));
+    }
+}
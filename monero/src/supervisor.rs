@@ -0,0 +1,165 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::resilience::jittered_backoff;
+
+// Resolves once shutdown has been requested. Cheap to clone into handlers.
+#[derive(Clone)]
+pub struct Shutdown(watch::Receiver<bool>);
+
+impl Shutdown {
+    pub fn is_requested(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    pub async fn wait(mut self) {
+        // An error means the supervisor is gone, which is a shutdown too
+        let _ = self.0.wait_for(|requested| *requested).await;
+    }
+}
+
+// Runs long-lived background workers and restarts them with jittered backoff
+// when they panic or return. A worker that stayed up for `stable_after`
+// starts again from the base delay.
+pub struct Supervisor {
+    shutdown: watch::Sender<bool>,
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+    base_delay: Duration,
+    max_delay: Duration,
+    stable_after: Duration,
+}
+
+impl Supervisor {
+    pub fn new(base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            shutdown: watch::channel(false).0,
+            tasks: Vec::new(),
+            base_delay,
+            max_delay,
+            stable_after: Duration::from_secs(60),
+        }
+    }
+
+    pub fn shutdown_signal(&self) -> Shutdown {
+        Shutdown(self.shutdown.subscribe())
+    }
+
+    pub fn spawn<F, Fut>(&mut self, name: &'static str, task: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let shutdown = self.shutdown_signal();
+        let (base_delay, max_delay, stable_after) = (self.base_delay, self.max_delay, self.stable_after);
+        let handle = tokio::spawn(async move {
+            let mut restarts = 0;
+            while !shutdown.is_requested() {
+                let started = Instant::now();
+                let mut worker = tokio::spawn(task());
+                let outcome = tokio::select! {
+                    outcome = &mut worker => outcome,
+                    _ = shutdown.clone().wait() => {
+                        worker.abort();
+                        break;
+                    }
+                };
+                match outcome {
+                    Ok(()) => println!("⚠️  Background task {} exited", name),
+                    Err(e) => println!("❌ Background task {} crashed: {}", name, e),
+                }
+                if started.elapsed() >= stable_after {
+                    restarts = 0;
+                }
+                restarts += 1;
+                let delay = jittered_backoff(restarts, base_delay, max_delay);
+                println!("🔁 Restarting {} in {:?} (restart #{})", name, delay, restarts);
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = shutdown.clone().wait() => break,
+                }
+            }
+        });
+        self.tasks.push((name, handle));
+    }
+
+    // Signals shutdown to workers and the HTTP server alike
+    pub fn request_shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    // Waits up to `timeout` for every worker to stop, then aborts the rest
+    pub async fn shutdown(self, timeout: Duration) {
+        self.request_shutdown();
+        let deadline = tokio::time::Instant::now() + timeout;
+        for (name, mut handle) in self.tasks {
+            if tokio::time::timeout_at(deadline, &mut handle).await.is_err() {
+                println!("⚠️  Background task {} did not stop in time, aborting", name);
+                handle.abort();
+            }
+        }
+    }
+}
+
+// SIGINT (Ctrl-C) or, on Unix, SIGTERM
+pub async fn termination_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn crashed_tasks_are_restarted_until_shutdown() {
+        let mut supervisor = Supervisor::new(Duration::from_millis(5), Duration::from_millis(20));
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        supervisor.spawn("flaky", move || {
+            let counter = counter.clone();
+            async move {
+                // Panic twice, then behave like a normal long-running worker
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("worker failed");
+                }
+                std::future::pending::<()>().await;
+            }
+        });
+
+        for _ in 0..100 {
+            if runs.load(Ordering::SeqCst) >= 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+        let shutdown = supervisor.shutdown_signal();
+        tokio::time::timeout(Duration::from_secs(1), supervisor.shutdown(Duration::from_millis(100)))
+            .await
+            .unwrap();
+        assert!(shutdown.is_requested());
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+}
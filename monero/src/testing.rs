@@ -0,0 +1,307 @@
+// Local stand-ins for monerod / monero-wallet-rpc and the Fluent chain used
+// by unit tests
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use warp::Filter;
+
+use crate::attestation::{self, abi_word};
+use crate::audit::AuditLog;
+use crate::config::MoneroConfig;
+use crate::events::EventBus;
+use crate::policy::PolicyEngine;
+use crate::rpc::MoneroService;
+use crate::scanner::{self, Address, OutputAmount};
+use crate::server::{AdminToken, ServerState};
+use crate::storage::ProofStore;
+use crate::supervisor::Supervisor;
+use crate::webhooks::{RetryPolicy, Webhooks};
+
+// Donation address of the Monero project, a valid mainnet standard address
+pub const ADDRESS: &str =
+    "44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A";
+
+// Main tx private key of every stub transaction
+const MAIN_SECRET: [u8; 32] = [0x11; 32];
+
+#[derive(Clone)]
+pub struct StubDaemon {
+    pub height: u64,
+    pub target_height: u64,
+    pub synchronized: bool,
+    pub nettype: String,
+    // Every transaction pays these piconero amounts to `recipient`, after a
+    // change output at index 0
+    pub recipient: String,
+    pub outputs: Vec<u64>,
+    // Derive each output from its own additional key, as wallets do when
+    // paying subaddresses; `get_tx_key` then returns them after the main key
+    pub additional_keys: bool,
+    pub delay_ms: u64,
+    // Bump to replace every block with another, as in a reorg
+    pub reorgs: Arc<AtomicU64>,
+    // Report transactions as back in the pool
+    pub in_pool: Arc<AtomicBool>,
+}
+
+impl Default for StubDaemon {
+    fn default() -> Self {
+        Self {
+            height: 100,
+            target_height: 100,
+            synchronized: true,
+            // Like `ADDRESS`
+            nettype: "mainnet".to_string(),
+            recipient: ADDRESS.to_string(),
+            outputs: vec![1_000_000],
+            additional_keys: false,
+            delay_ms: 0,
+            reorgs: Arc::default(),
+            in_pool: Arc::default(),
+        }
+    }
+}
+
+impl StubDaemon {
+    // Serves JSON-RPC and `/get_transactions` on an ephemeral port, returns its base URL
+    pub fn spawn(self) -> String {
+        let rpc_stub = self.clone();
+        let json_rpc = warp::path("json_rpc")
+            .and(warp::post())
+            .and(warp::body::json())
+            .then(move |request: Value| {
+                let stub = rpc_stub.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(stub.delay_ms)).await;
+                    warp::reply::json(&stub.json_rpc(&request))
+                }
+            });
+        let tx_stub = self.clone();
+        let get_transactions = warp::path("get_transactions")
+            .and(warp::post())
+            .and(warp::body::json())
+            .map(move |request: Value| warp::reply::json(&tx_stub.get_transactions(&request)));
+
+        let (addr, server) = warp::serve(json_rpc.or(get_transactions)).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        format!("http://{}", addr)
+    }
+
+    fn json_rpc(&self, request: &Value) -> Value {
+        let params = &request["params"];
+        let result = match request["method"].as_str().unwrap_or_default() {
+            "get_info" => json!({
+                "height": self.height,
+                "target_height": self.target_height,
+                "synchronized": self.synchronized,
+                "nettype": self.nettype,
+                "status": "OK",
+            }),
+            "get_tx_key" => {
+                let additional: Vec<String> = self.additional_secrets().iter().map(hex::encode).collect();
+                json!({ "tx_key": hex::encode(MAIN_SECRET) + &additional.concat() })
+            }
+            "get_version" => json!({ "version": 65562 }),
+            "get_block_header_by_height" => {
+                let height = params["height"].as_u64().unwrap_or_default();
+                json!({ "block_header": { "height": height, "hash": self.block_hash(height) }, "status": "OK" })
+            }
+            method => {
+                return json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": { "code": -32601, "message": format!("Method not found: {} {}", method, params) },
+                });
+            }
+        };
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+    }
+
+    // Changes with every reorg
+    pub fn block_hash(&self, height: u64) -> String {
+        format!("{:032x}{:032x}", self.reorgs.load(Ordering::SeqCst), height)
+    }
+
+    fn get_transactions(&self, request: &Value) -> Value {
+        let in_pool = self.in_pool.load(Ordering::SeqCst);
+        let txs: Vec<Value> = request["txs_hashes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|hash| {
+                json!({
+                    "tx_hash": hash,
+                    "as_json": self.transaction().to_string(),
+                    "block_height": if in_pool { 0 } else { self.height - 10 },
+                    "block_timestamp": 1_700_000_000u64,
+                    "in_pool": in_pool,
+                })
+            })
+            .collect();
+        json!({ "txs": txs, "status": "OK" })
+    }
+
+    fn additional_secrets(&self) -> Vec<[u8; 32]> {
+        if !self.additional_keys {
+            return Vec::new();
+        }
+        (0..=self.outputs.len()).map(|i| [0x22 + i as u8; 32]).collect()
+    }
+
+    // The decoded transaction `get_transactions` returns with `decode_as_json`
+    fn transaction(&self) -> Value {
+        let recipient = Address::parse(&self.recipient).unwrap();
+        let change = scanner::test_address(recipient.network_byte, 1000);
+        let additional = self.additional_secrets();
+        let (vout, rct): (Vec<Value>, Vec<(Value, Value)>) = std::iter::once((&change, 0))
+            .chain(self.outputs.iter().map(|amount| (&recipient, *amount)))
+            .enumerate()
+            .map(|(index, (address, amount))| {
+                let secret = additional.get(index).unwrap_or(&MAIN_SECRET);
+                let output = scanner::build_output(secret, address, index as u64, amount);
+                let OutputAmount::Compact(encrypted) = output.amount else { unreachable!() };
+                (
+                    json!({ "amount": 0, "target": { "tagged_key": { "key": hex::encode(output.key), "view_tag": "00" } } }),
+                    (json!({ "amount": hex::encode(encrypted) }), json!(output.commitment.map(hex::encode))),
+                )
+            })
+            .unzip();
+        let (ecdh_info, out_pk): (Vec<Value>, Vec<Value>) = rct.into_iter().unzip();
+        json!({ "version": 2, "vout": vout, "rct_signatures": { "type": 6, "ecdhInfo": ecdh_info, "outPk": out_pk } })
+    }
+}
+
+// Fluent JSON-RPC with a BridgeRouter at `router`, serving the `Minted` logs
+// and `consumed` lockIds added with `mint`, and its AttestorRegistry at
+// `STUB_REGISTRY`
+#[derive(Clone)]
+pub struct StubChain {
+    router: [u8; 20],
+    state: Arc<Mutex<ChainState>>,
+}
+
+#[derive(Default)]
+struct ChainState {
+    height: u64,
+    timestamp: u64,
+    // (block, log)
+    logs: Vec<(u64, Value)>,
+    consumed: HashSet<[u8; 32]>,
+    threshold: u128,
+}
+
+const STUB_REGISTRY: [u8; 20] = [0x44; 20];
+
+impl StubChain {
+    pub fn new(router: [u8; 20]) -> Self {
+        Self {
+            router,
+            state: Arc::default(),
+        }
+    }
+
+    pub fn set_head(&self, height: u64, timestamp: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.height = height;
+        state.timestamp = timestamp;
+    }
+
+    pub fn set_threshold(&self, threshold: u128) {
+        self.state.lock().unwrap().threshold = threshold;
+    }
+
+    pub fn mint(&self, block: u64, lock_id: [u8; 32], amount: u128, attestor: [u8; 20]) {
+        let mut state = self.state.lock().unwrap();
+        // The reconciler goes by lockId only, so any dest will do
+        let data = [lock_id, abi_word(&[0x33; 20]), abi_word(&amount.to_be_bytes()), abi_word(&attestor)].concat();
+        let log = json!({
+            "data": attestation::format_hex(&data),
+            "blockNumber": format!("0x{:x}", block),
+            "transactionHash": attestation::format_hex(&attestation::keccak256(&data)),
+        });
+        state.logs.push((block, log));
+        state.consumed.insert(lock_id);
+    }
+
+    pub fn spawn(&self) -> String {
+        let stub = self.clone();
+        let rpc = warp::post()
+            .and(warp::body::json())
+            .map(move |request: Value| warp::reply::json(&stub.handle(&request)));
+        let (addr, server) = warp::serve(rpc).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        format!("http://{}", addr)
+    }
+
+    fn handle(&self, request: &Value) -> Value {
+        let state = self.state.lock().unwrap();
+        let params = &request["params"];
+        let quantity = |value: &Value| u64::from_str_radix(value.as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+        let result = match request["method"].as_str().unwrap_or_default() {
+            "eth_getBlockByNumber" => json!({
+                "number": format!("0x{:x}", state.height),
+                "timestamp": format!("0x{:x}", state.timestamp),
+            }),
+            "eth_getLogs" => {
+                let filter = &params[0];
+                assert_eq!(filter["address"], attestation::format_hex(&self.router));
+                let (from, to) = (quantity(&filter["fromBlock"]), quantity(&filter["toBlock"]));
+                let logs: Vec<&Value> =
+                    state.logs.iter().filter(|(block, _)| (from..=to).contains(block)).map(|(_, log)| log).collect();
+                json!(logs)
+            }
+            "eth_call" => {
+                let data = hex::decode(params[0]["data"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+                let selector = |function: &str| attestation::keccak256(function.as_bytes())[..4].to_vec();
+                let word = if data[..4] == selector("registry()") {
+                    abi_word(&STUB_REGISTRY)
+                } else if data[..4] == selector("threshold()") {
+                    assert_eq!(params[0]["to"], attestation::format_hex(&STUB_REGISTRY));
+                    abi_word(&state.threshold.to_be_bytes())
+                } else {
+                    let lock_id: [u8; 32] = data[4..].try_into().unwrap();
+                    abi_word(&[state.consumed.contains(&lock_id) as u8])
+                };
+                json!(attestation::format_hex(&word))
+            }
+            method => {
+                return json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32601, "message": method } });
+            }
+        };
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+    }
+}
+
+// A URL nothing listens on
+pub fn dead_url() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{}", addr)
+}
+
+// Server state backed by `daemon` for both daemon and wallet RPC, with an
+// in-memory store, no attestor and admin token "admin"
+pub fn server_state(daemon: String) -> ServerState {
+    let monero_service = MoneroService::new(&MoneroConfig {
+        daemons: vec![daemon.clone()],
+        wallet: Some(daemon),
+        ..MoneroConfig::default()
+    })
+    .unwrap();
+    ServerState {
+        storage: Arc::new(ProofStore::in_memory()),
+        monero_service: Arc::new(monero_service),
+        attestor: None,
+        events: EventBus::new(16),
+        webhooks: Webhooks::new(RetryPolicy::default(), None),
+        admin_token: AdminToken::new(Some("admin".to_string())),
+        shutdown: Supervisor::new(Duration::from_secs(1), Duration::from_secs(1)).shutdown_signal(),
+        max_sync_lag: 2,
+        legacy_sunset: None,
+        policy: PolicyEngine::default(),
+        audit: Arc::new(AuditLog::disabled()),
+    }
+}
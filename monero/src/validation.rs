@@ -0,0 +1,208 @@
+// Field-level checks on request bodies. The server and the CLI run them
+// before anything reaches `MoneroService`.
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::api::{AttestRequest, ProofRequest, ValidationRequest};
+use crate::attestation;
+use crate::coordination::CosignRequest;
+use crate::privacy::ProofPreimage;
+use crate::scanner::Address;
+use crate::webhooks::RegisterWebhookRequest;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+// A request body that failed to parse or validate
+#[derive(Debug)]
+pub struct InvalidRequest(pub Vec<FieldError>);
+
+impl std::fmt::Display for InvalidRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<String> = self.0.iter().map(|e| format!("{}: {}", e.field, e.message)).collect();
+        write!(f, "{}", fields.join("; "))
+    }
+}
+
+impl std::error::Error for InvalidRequest {}
+
+impl warp::reject::Reject for InvalidRequest {}
+
+pub trait Validate {
+    fn field_errors(&self) -> Vec<FieldError>;
+
+    fn validate(&self) -> Result<(), InvalidRequest> {
+        let errors = self.field_errors();
+        if errors.is_empty() { Ok(()) } else { Err(InvalidRequest(errors)) }
+    }
+}
+
+// Deserializes and validates a JSON body. Type errors name the offending field.
+pub fn parse<T: DeserializeOwned + Validate>(body: &[u8]) -> Result<T, InvalidRequest> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+    let request: T = serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        // Drop serde_json's " at line 1 column 7" suffix
+        let message = e.inner().to_string();
+        let message = message.split(" at line ").next().unwrap_or_default().to_string();
+        // Missing fields are reported against the enclosing object
+        let missing = message.strip_prefix("missing field `").and_then(|rest| rest.split('`').next());
+        let field = match (e.path().to_string(), missing) {
+            (path, Some(missing)) if path == "." => missing.to_string(),
+            (path, _) if path == "." => "body".to_string(),
+            (path, _) => path,
+        };
+        InvalidRequest(vec![FieldError { field, message }])
+    })?;
+    request.validate()?;
+    Ok(request)
+}
+
+impl Validate for ProofRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        check_tx_hash(&mut errors, "tx_id", &self.tx_id);
+        check_monero_address(&mut errors, "recipient_address", &self.recipient_address);
+        errors
+    }
+}
+
+impl Validate for ValidationRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        check_tx_hash(&mut errors, "tx_id", &self.tx_id);
+        check_tx_key(&mut errors, "tx_key", &self.tx_key);
+        for (index, key) in self.additional_tx_keys.iter().enumerate() {
+            if key.len() != 64 || !is_hex(key) {
+                errors.push(FieldError::new(
+                    &format!("additional_tx_keys[{}]", index),
+                    "must be a 64-character hex key",
+                ));
+            }
+        }
+        check_monero_address(&mut errors, "recipient_address", &self.recipient_address);
+        errors
+    }
+}
+
+impl Validate for AttestRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if attestation::parse_address(&self.dest).is_err() {
+            errors.push(FieldError::new("dest", "must be a 0x-prefixed 20-byte hex address"));
+        }
+        if let Some(preimage) = &self.preimage {
+            errors.extend(preimage.field_errors().into_iter().map(|e| FieldError::new(&format!("preimage.{}", e.field), e.message)));
+        }
+        errors
+    }
+}
+
+impl Validate for ProofPreimage {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        check_tx_hash(&mut errors, "tx_id", &self.tx_id);
+        check_tx_key(&mut errors, "tx_key", &self.tx_key);
+        check_monero_address(&mut errors, "recipient_address", &self.recipient_address);
+        errors
+    }
+}
+
+impl Validate for CosignRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        check_tx_hash(&mut errors, "tx_id", &self.tx_id);
+        check_tx_key(&mut errors, "tx_key", &self.tx_key);
+        check_monero_address(&mut errors, "recipient_address", &self.recipient_address);
+        errors
+    }
+}
+
+impl Validate for RegisterWebhookRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            Ok(_) => errors.push(FieldError::new("url", "must be an http or https URL")),
+            Err(e) => errors.push(FieldError::new("url", format!("invalid URL: {}", e))),
+        }
+        if self.secret.as_deref() == Some("") {
+            errors.push(FieldError::new("secret", "must not be empty; omit it to have one generated"));
+        }
+        errors
+    }
+}
+
+fn is_hex(value: &str) -> bool {
+    value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// Transaction hashes are 32 bytes, hex without a 0x prefix
+fn check_tx_hash(errors: &mut Vec<FieldError>, field: &str, value: &str) {
+    if value.len() != 64 || !is_hex(value) {
+        errors.push(FieldError::new(field, "must be a 64-character hex transaction hash"));
+    }
+}
+
+// The main tx key, optionally followed by the additional keys of subaddress
+// outputs, as `get_tx_key` returns them
+fn check_tx_key(errors: &mut Vec<FieldError>, field: &str, value: &str) {
+    if value.is_empty() || !value.len().is_multiple_of(64) || !is_hex(value) {
+        errors.push(FieldError::new(
+            field,
+            "must be a 64-character hex tx key, optionally followed by 64-character additional keys",
+        ));
+    }
+}
+
+// Standard and subaddresses are 95 base58 characters, integrated addresses
+// 106, both ending in a checksum
+fn check_monero_address(errors: &mut Vec<FieldError>, field: &str, value: &str) {
+    if let Err(e) = Address::parse(value) {
+        errors.push(FieldError::new(field, format!("must be a Monero address: {}", e)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ADDRESS;
+
+    #[test]
+    fn reports_every_invalid_field() {
+        let body = serde_json::json!({ "tx_id": "abc123def456", "tx_key": "zz", "recipient_address": "addr" });
+        let InvalidRequest(errors) = parse::<ValidationRequest>(body.to_string().as_bytes()).unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["tx_id", "tx_key", "recipient_address"]);
+
+        // Additional keys for subaddress outputs are appended to the main key
+        let body = serde_json::json!({ "tx_id": "ab".repeat(32), "tx_key": "cd".repeat(96), "recipient_address": ADDRESS });
+        assert!(parse::<ValidationRequest>(body.to_string().as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_wrong_types() {
+        let body = serde_json::json!({ "tx_id": "ab".repeat(32), "recipient_address": ADDRESS, "amount": 1 });
+        let InvalidRequest(errors) = parse::<ProofRequest>(body.to_string().as_bytes()).unwrap_err();
+        assert!(errors[0].message.starts_with("unknown field `amount`"), "{:?}", errors);
+
+        let InvalidRequest(errors) = parse::<ProofRequest>(br#"{"tx_id": 5}"#).unwrap_err();
+        assert_eq!(errors[0].field, "tx_id");
+        assert!(!errors[0].message.contains("line"), "{:?}", errors);
+
+        let InvalidRequest(errors) = parse::<ProofRequest>(br#"{"tx_id": "ab"}"#).unwrap_err();
+        assert_eq!(errors[0].field, "recipient_address");
+    }
+}
@@ -0,0 +1,597 @@
+use anyhow::{Context, Result, anyhow};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+use crate::events::{EventBus, ProofEvent, ProofEventKind, unix_now};
+
+pub const SIGNATURE_HEADER: &str = "X-Foid-Signature";
+pub const EVENT_HEADER: &str = "X-Foid-Event";
+pub const DELIVERY_HEADER: &str = "X-Foid-Delivery";
+
+const STATE_FILE: &str = "webhooks.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub url: String,
+    pub secret: String,
+    // Empty means every event kind
+    pub events: Vec<ProofEventKind>,
+    pub created_at: u64,
+}
+
+impl WebhookEndpoint {
+    fn wants(&self, kind: ProofEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+
+    // Listing endpoints never echoes the secret back
+    pub fn redacted(&self) -> Self {
+        Self {
+            secret: "********".to_string(),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterWebhookRequest {
+    pub url: String,
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub events: Vec<ProofEventKind>,
+}
+
+// A delivery that exhausted its retries
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetter {
+    pub id: String,
+    pub endpoint_id: String,
+    pub url: String,
+    pub event: ProofEvent,
+    pub attempts: u32,
+    pub last_error: String,
+    pub failed_at: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub request_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(60),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // Exponential backoff before retry number `attempt` (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+// What survives a restart, in `<data_dir>/webhooks.json`
+#[derive(Default, Serialize, Deserialize)]
+struct State {
+    endpoints: Vec<WebhookEndpoint>,
+    dead_letters: Vec<DeadLetter>,
+}
+
+// Endpoints and dead letters stay in memory, and with a data directory are
+// rewritten to `webhooks.json` on every change. `dead_letter_log` is an
+// append-only record of failures; replays are not removed from it.
+#[derive(Clone)]
+pub struct Webhooks {
+    endpoints: Arc<RwLock<HashMap<String, WebhookEndpoint>>>,
+    dead_letters: Arc<RwLock<Vec<DeadLetter>>>,
+    dead_letter_log: Option<PathBuf>,
+    state_file: Option<PathBuf>,
+    // Serializes snapshot writes
+    persist_lock: Arc<tokio::sync::Mutex<()>>,
+    policy: RetryPolicy,
+    client: reqwest::Client,
+}
+
+impl Webhooks {
+    pub fn new(policy: RetryPolicy, dead_letter_log: Option<PathBuf>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(policy.request_timeout)
+            .build()
+            .expect("failed to build webhook HTTP client");
+        Self {
+            endpoints: Arc::new(RwLock::new(HashMap::new())),
+            dead_letters: Arc::new(RwLock::new(Vec::new())),
+            dead_letter_log,
+            state_file: None,
+            persist_lock: Arc::new(tokio::sync::Mutex::new(())),
+            policy,
+            client,
+        }
+    }
+
+    // Loads the endpoints and dead letters a previous run left in `data_dir`
+    pub fn open(policy: RetryPolicy, data_dir: Option<&Path>, dead_letter_log: Option<PathBuf>) -> Result<Self> {
+        let mut webhooks = Self::new(policy, dead_letter_log);
+        let Some(data_dir) = data_dir else {
+            return Ok(webhooks);
+        };
+        std::fs::create_dir_all(data_dir)
+            .with_context(|| format!("could not create data dir {}", data_dir.display()))?;
+        let path = data_dir.join(STATE_FILE);
+        if path.exists() {
+            let bytes = std::fs::read(&path).with_context(|| format!("could not read {}", path.display()))?;
+            let state: State =
+                serde_json::from_slice(&bytes).with_context(|| format!("corrupt webhook state {}", path.display()))?;
+            webhooks.endpoints = Arc::new(RwLock::new(
+                state.endpoints.into_iter().map(|endpoint| (endpoint.id.clone(), endpoint)).collect(),
+            ));
+            webhooks.dead_letters = Arc::new(RwLock::new(state.dead_letters));
+        }
+        webhooks.state_file = Some(path);
+        Ok(webhooks)
+    }
+
+    pub async fn counts(&self) -> (usize, usize) {
+        (self.endpoints.read().await.len(), self.dead_letters.read().await.len())
+    }
+
+    // Rewrites the state file; the change itself already took effect
+    async fn persist(&self) {
+        let Some(path) = &self.state_file else {
+            return;
+        };
+        let _persisting = self.persist_lock.lock().await;
+        let state = State {
+            endpoints: self.endpoints.read().await.values().cloned().collect(),
+            dead_letters: self.dead_letters.read().await.clone(),
+        };
+        if let Err(e) = write_secret_file(path, &state) {
+            println!("❌ Could not persist webhooks to {}: {:#}", path.display(), e);
+        }
+    }
+
+    pub async fn register(&self, request: RegisterWebhookRequest) -> Result<WebhookEndpoint> {
+        let url = reqwest::Url::parse(&request.url).map_err(|e| anyhow!("invalid webhook url: {}", e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(anyhow!("webhook url must be http or https"));
+        }
+        let endpoint = WebhookEndpoint {
+            id: Uuid::new_v4().to_string(),
+            url: request.url,
+            secret: request.secret.unwrap_or_else(generate_secret),
+            events: request.events,
+            created_at: unix_now(),
+        };
+        self.endpoints.write().await.insert(endpoint.id.clone(), endpoint.clone());
+        self.persist().await;
+        println!("🪝 Registered webhook {} -> {}", endpoint.id, endpoint.url);
+        Ok(endpoint)
+    }
+
+    pub async fn list(&self) -> Vec<WebhookEndpoint> {
+        self.endpoints.read().await.values().map(WebhookEndpoint::redacted).collect()
+    }
+
+    pub async fn remove(&self, id: &str) -> bool {
+        let removed = self.endpoints.write().await.remove(id).is_some();
+        if removed {
+            self.persist().await;
+        }
+        removed
+    }
+
+    pub async fn dead_letters(&self) -> Vec<DeadLetter> {
+        self.dead_letters.read().await.clone()
+    }
+
+    // Re-delivers a dead letter with the normal retry policy; it is dropped from
+    // the dead-letter list once delivered.
+    pub async fn replay(&self, dead_letter_id: &str) -> Result<DeadLetter> {
+        let dead_letter = self
+            .dead_letters
+            .read()
+            .await
+            .iter()
+            .find(|d| d.id == dead_letter_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("dead letter {} not found", dead_letter_id))?;
+        let endpoint = self
+            .endpoints
+            .read()
+            .await
+            .get(&dead_letter.endpoint_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("webhook {} no longer registered", dead_letter.endpoint_id))?;
+
+        match self.deliver(&endpoint, &dead_letter.event).await {
+            Ok(_) => {
+                self.dead_letters.write().await.retain(|d| d.id != dead_letter_id);
+                self.persist().await;
+                Ok(dead_letter)
+            }
+            Err((attempts, error)) => {
+                if let Some(entry) = self.dead_letters.write().await.iter_mut().find(|d| d.id == dead_letter_id) {
+                    entry.attempts += attempts;
+                    entry.last_error = error.clone();
+                    entry.failed_at = unix_now();
+                }
+                self.persist().await;
+                Err(anyhow!("replay failed: {}", error))
+            }
+        }
+    }
+
+    // Subscribes to the bus and fans every event out to matching endpoints
+    // Subscribes immediately, so no event published after this call is missed
+    pub fn dispatcher(&self, bus: &EventBus) -> impl Future<Output = ()> + Send + use<> {
+        let webhooks = self.clone();
+        let mut receiver = bus.subscribe();
+        async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => webhooks.dispatch(event).await,
+                    Err(RecvError::Lagged(skipped)) => {
+                        println!("⚠️  Webhook dispatcher lagged, {} events skipped", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    async fn dispatch(&self, event: ProofEvent) {
+        let endpoints: Vec<WebhookEndpoint> = self
+            .endpoints
+            .read()
+            .await
+            .values()
+            .filter(|e| e.wants(event.kind))
+            .cloned()
+            .collect();
+
+        for endpoint in endpoints {
+            let webhooks = self.clone();
+            let event = event.clone();
+            tokio::spawn(async move {
+                if let Err((attempts, error)) = webhooks.deliver(&endpoint, &event).await {
+                    webhooks.dead_letter(&endpoint, event, attempts, error).await;
+                }
+            });
+        }
+    }
+
+    // Returns the number of attempts on success, or attempts and last error
+    async fn deliver(&self, endpoint: &WebhookEndpoint, event: &ProofEvent) -> Result<u32, (u32, String)> {
+        let body = serde_json::to_vec(event).map_err(|e| (0, e.to_string()))?;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.post(endpoint, event, &body).await {
+                Ok(()) => return Ok(attempt),
+                Err(e) if attempt >= self.policy.max_attempts => return Err((attempt, e.to_string())),
+                Err(e) => {
+                    let delay = self.policy.delay(attempt);
+                    println!(
+                        "🔁 Webhook {} attempt {} failed ({}), retrying in {:?}",
+                        endpoint.url, attempt, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    async fn post(&self, endpoint: &WebhookEndpoint, event: &ProofEvent, body: &[u8]) -> Result<()> {
+        let timestamp = unix_now();
+        let response = self
+            .client
+            .post(&endpoint.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.kind.as_str())
+            .header(DELIVERY_HEADER, &event.id)
+            .header(SIGNATURE_HEADER, sign_payload(&endpoint.secret, timestamp, body))
+            .body(body.to_vec())
+            .send()
+            .await?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(anyhow!("HTTP {}", response.status()))
+        }
+    }
+
+    async fn dead_letter(&self, endpoint: &WebhookEndpoint, event: ProofEvent, attempts: u32, last_error: String) {
+        println!("💀 Webhook {} gave up on event {}: {}", endpoint.url, event.id, last_error);
+        let dead_letter = DeadLetter {
+            id: Uuid::new_v4().to_string(),
+            endpoint_id: endpoint.id.clone(),
+            url: endpoint.url.clone(),
+            event,
+            attempts,
+            last_error,
+            failed_at: unix_now(),
+        };
+        if let Some(path) = &self.dead_letter_log
+            && let Err(e) = append_json_line(path, &dead_letter)
+        {
+            println!("❌ Could not write dead-letter log {}: {}", path.display(), e);
+        }
+        self.dead_letters.write().await.push(dead_letter);
+        self.persist().await;
+    }
+}
+
+// `t=<unix>,v1=<hex hmac-sha256(secret, "<unix>.<body>")>`
+pub fn sign_payload(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes()))
+}
+
+// Checks a `sign_payload` header, rejecting timestamps more than
+// `tolerance_secs` away from `now`
+pub fn verify_payload(secret: &str, header: &str, body: &[u8], now: u64, tolerance_secs: u64) -> bool {
+    let mut timestamp = None;
+    let mut signature = None;
+    for part in header.split(',') {
+        match part.split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<u64>().ok(),
+            Some(("v1", value)) => signature = hex::decode(value).ok(),
+            _ => {}
+        }
+    }
+    let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+        return false;
+    };
+    if timestamp.abs_diff(now) > tolerance_secs {
+        return false;
+    }
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+// Holds endpoint secrets: written owner-only, to a temp file then renamed
+fn write_secret_file<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp).with_context(|| format!("could not write {}", tmp.display()))?;
+    file.write_all(&serde_json::to_vec_pretty(value)?)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path).with_context(|| format!("could not replace {}", path.display()))?;
+    Ok(())
+}
+
+fn append_json_line<T: Serialize>(path: &PathBuf, value: &T) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(value)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Piconero;
+    use crate::api::{ProofStatus, TransactionProof};
+    use std::net::SocketAddr;
+    use std::sync::Mutex;
+    use warp::Filter;
+
+    type Received = Arc<Mutex<Vec<(String, String, Vec<u8>)>>>;
+
+    // Local HTTP sink that records deliveries and fails the first `failures` requests
+    fn spawn_sink(failures: usize) -> (SocketAddr, Received) {
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::new(Mutex::new(0usize));
+        let store = received.clone();
+        let route = warp::post()
+            .and(warp::header::<String>(SIGNATURE_HEADER))
+            .and(warp::header::<String>(EVENT_HEADER))
+            .and(warp::body::bytes())
+            .map(move |signature: String, kind: String, body: warp::hyper::body::Bytes| {
+                let mut count = seen.lock().unwrap();
+                *count += 1;
+                if *count <= failures {
+                    return warp::http::StatusCode::INTERNAL_SERVER_ERROR;
+                }
+                store.lock().unwrap().push((signature, kind, body.to_vec()));
+                warp::http::StatusCode::OK
+            });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        (addr, received)
+    }
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(5),
+            max_delay: Duration::from_millis(20),
+            request_timeout: Duration::from_secs(2),
+        }
+    }
+
+    fn sample_event(kind: ProofEventKind) -> ProofEvent {
+        ProofEvent::new(
+            kind,
+            &TransactionProof {
+                tx_id: "aa".repeat(32),
+                tx_key: "bb".repeat(32),
+                recipient_address: "44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A".to_string(),
+                amount: Some(Piconero(1_000_000)),
+                proof_id: "proof-1".to_string(),
+                status: ProofStatus::Created,
+                updated_at: 0,
+                attestation: None,
+                policy: None,
+                mint: None,
+                flags: Vec::new(),
+                commitment: None,
+                block: None,
+            },
+        )
+    }
+
+    async fn wait_for<F: Fn() -> bool>(condition: F) {
+        for _ in 0..200 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("condition not met in time");
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_and_caps() {
+        let policy = fast_policy(10);
+        assert_eq!(policy.delay(1), Duration::from_millis(5));
+        assert_eq!(policy.delay(2), Duration::from_millis(10));
+        assert_eq!(policy.delay(5), Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn delivers_signed_events_after_retries() {
+        let (addr, received) = spawn_sink(2);
+        let bus = EventBus::new(16);
+        let webhooks = Webhooks::new(fast_policy(5), None);
+        tokio::spawn(webhooks.dispatcher(&bus));
+        let endpoint = webhooks
+            .register(RegisterWebhookRequest {
+                url: format!("http://{}/hook", addr),
+                secret: Some("shh".to_string()),
+                events: vec![ProofEventKind::Validated],
+            })
+            .await
+            .unwrap();
+
+        bus.publish(sample_event(ProofEventKind::Created));
+        bus.publish(sample_event(ProofEventKind::Validated));
+        wait_for(|| !received.lock().unwrap().is_empty()).await;
+
+        let (signature, kind, body) = received.lock().unwrap()[0].clone();
+        assert_eq!(kind, "proof.validated");
+        let timestamp: u64 = signature.trim_start_matches("t=").split(',').next().unwrap().parse().unwrap();
+        assert_eq!(signature, sign_payload(&endpoint.secret, timestamp, &body));
+        let event: ProofEvent = serde_json::from_slice(&body).unwrap();
+        assert_eq!(event.data.proof_id, "proof-1");
+        assert!(webhooks.dead_letters().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn exhausted_deliveries_are_dead_lettered_and_replayable() {
+        let (addr, received) = spawn_sink(3);
+        let bus = EventBus::new(16);
+        let log = tempfile::NamedTempFile::new().unwrap();
+        let webhooks = Webhooks::new(fast_policy(3), Some(log.path().to_path_buf()));
+        tokio::spawn(webhooks.dispatcher(&bus));
+        webhooks
+            .register(RegisterWebhookRequest {
+                url: format!("http://{}/hook", addr),
+                secret: None,
+                events: Vec::new(),
+            })
+            .await
+            .unwrap();
+
+        bus.publish(sample_event(ProofEventKind::Attested));
+        for _ in 0..200 {
+            if !webhooks.dead_letters().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let dead = webhooks.dead_letters().await;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, 3);
+        assert_eq!(std::fs::read_to_string(log.path()).unwrap().lines().count(), 1);
+
+        webhooks.replay(&dead[0].id).await.unwrap();
+        assert!(webhooks.dead_letters().await.is_empty());
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn endpoints_and_dead_letters_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let (addr, received) = spawn_sink(1);
+        let webhooks = Webhooks::open(fast_policy(1), Some(dir.path()), None).unwrap();
+        let endpoint = webhooks
+            .register(RegisterWebhookRequest {
+                url: format!("http://{}/hook", addr),
+                secret: Some("shh".to_string()),
+                events: Vec::new(),
+            })
+            .await
+            .unwrap();
+        let removed = webhooks
+            .register(RegisterWebhookRequest { url: "http://127.0.0.1:1/gone".to_string(), secret: None, events: Vec::new() })
+            .await
+            .unwrap();
+        assert!(webhooks.remove(&removed.id).await);
+        let bus = EventBus::new(16);
+        tokio::spawn(webhooks.dispatcher(&bus));
+        bus.publish(sample_event(ProofEventKind::Minted));
+        for _ in 0..200 {
+            if !webhooks.dead_letters().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // The file holds the secrets
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.path().join(STATE_FILE)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let restarted = Webhooks::open(fast_policy(1), Some(dir.path()), None).unwrap();
+        assert_eq!(restarted.counts().await, (1, 1));
+        assert_eq!(restarted.endpoints.read().await[&endpoint.id].secret, "shh");
+        let dead = restarted.dead_letters().await;
+        restarted.replay(&dead[0].id).await.unwrap();
+        assert_eq!(received.lock().unwrap().len(), 1);
+        let restarted = Webhooks::open(fast_policy(1), Some(dir.path()), None).unwrap();
+        assert_eq!(restarted.counts().await, (1, 0));
+    }
+}
@@ -12,11 +12,11 @@ echo ""
 
 # Generate a proof
 echo "2. Generating transaction proof..."
-PROOF_RESPONSE=$(curl -s -X POST "$BASE_URL/proof" \
+PROOF_RESPONSE=$(curl -s -X POST "$BASE_URL/v1/proof" \
   -H "Content-Type: application/json" \
   -d '{
-    "tx_id": "abc123def456",
-    "recipient_address": "9wviCeWe2D8XS82k2ovp5EUYLzBt9pYNW2LXUFsZiv8S3Mt21FZ5qQaAroko1enzw3eGr9qC7X1D7Geoo2RrAotYPw2Rts"
+    "tx_id": "c3b1d8f0a2e47d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e",
+    "recipient_address": "44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A"
   }')
 
 echo "$PROOF_RESPONSE" | jq .
@@ -27,21 +27,21 @@ TX_KEY=$(echo "$PROOF_RESPONSE" | jq -r '.tx_key')
 
 echo ""
 echo "3. Validating transaction..."
-curl -s -X POST "$BASE_URL/validate" \
+curl -s -X POST "$BASE_URL/v1/validate" \
   -H "Content-Type: application/json" \
   -d "{
-    \"tx_id\": \"abc123def456\",
+    \"tx_id\": \"c3b1d8f0a2e47d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e7d9e\",
     \"tx_key\": \"$TX_KEY\",
-    \"recipient_address\": \"9wviCeWe2D8XS82k2ovp5EUYLzBt9pYNW2LXUFsZiv8S3Mt21FZ5qQaAroko1enzw3eGr9qC7X1D7Geoo2RrAotYPw2Rts\"
+    \"recipient_address\": \"44AFFq5kSiGBoZ4NMDwYtN18obc8AemS33DBLWs3H7otXft3XjrpDtQGv7SqSsaBYBb98uNbr2VBBEt7f2wfn3RVGQBEP3A\"
   }" | jq .
 
 echo ""
 echo "4. Getting all proofs..."
-curl -s "$BASE_URL/proofs" | jq .
+curl -s "$BASE_URL/v1/proofs" | jq .
 
 echo ""
 echo "5. Getting specific proof..."
-curl -s "$BASE_URL/proof/$PROOF_ID" | jq .
+curl -s "$BASE_URL/v1/proof/$PROOF_ID" | jq .
 
 echo ""
 echo "✅ API testing complete!"